@@ -44,9 +44,37 @@ pub enum NovaError {
   /// returned when an invalid sum-check proof is provided
   #[error("InvalidSumcheckProof")]
   InvalidSumcheckProof,
+  /// returned when the assignment produced by a step circuit does not satisfy its constraints
+  #[error("StepCircuitUnsatisfiable at step {step}")]
+  StepCircuitUnsatisfiable {
+    /// The number of the step, as counted by `RecursiveSNARK::prove_step`
+    step: usize,
+  },
+  /// returned when a step used to pad an incremental computation changes its outputs
+  #[error("InvalidPaddingStep")]
+  InvalidPaddingStep,
+  /// returned when a step is proven while steps queued by `RecursiveSNARK::queue_primary_steps`
+  /// are not folded yet
+  #[error("PendingPrimarySteps")]
+  PendingPrimarySteps,
+  /// returned when a data commitment is supplied for a step that cannot absorb it
+  #[error("InvalidDataCommitment")]
+  InvalidDataCommitment,
+  /// returned when a step circuit has no inputs and outputs
+  #[error("ZeroArity")]
+  ZeroArity,
+  /// returned when a circuit synthesizes no constraints
+  #[error("EmptyCircuit")]
+  EmptyCircuit,
   /// returned when the initial input to an incremental computation differs from a previously declared arity
   #[error("InvalidInitialInputLength")]
   InvalidInitialInputLength,
+  /// returned when the initial input to an incremental computation violates a precondition of the step circuit
+  #[error("InvalidInitialState: {reason}")]
+  InvalidInitialState {
+    /// The reason reported by `StepCircuit::validate_initial`
+    reason: String,
+  },
   /// returned when the step execution produces an output whose length differs from a previously declared arity
   #[error("InvalidStepOutputLength")]
   InvalidStepOutputLength,
@@ -68,14 +96,94 @@ pub enum NovaError {
     /// The reason for circuit synthesis failure
     reason: String,
   },
+  /// returned when a serialized `RecursiveSNARK` cannot be decoded or fails validation
+  #[error("InvalidRecursiveSNARK: {reason}")]
+  InvalidRecursiveSNARK {
+    /// The reason for the validation failure
+    reason: String,
+  },
+  /// returned when a `RecursiveSNARK` cannot be rewound to a checkpoint
+  #[error("InvalidCheckpoint: {reason}")]
+  InvalidCheckpoint {
+    /// The reason the checkpoint was rejected
+    reason: String,
+  },
+  /// returned when a `RecursiveSNARK` cannot be diffed against or advanced by a delta
+  #[error("InvalidRecursiveSNARKDelta: {reason}")]
+  InvalidRecursiveSNARKDelta {
+    /// The reason the delta was rejected
+    reason: String,
+  },
+  /// returned when a serialized `CompressedSNARK` cannot be decoded
+  #[error("InvalidCompressedSNARK: {reason}")]
+  InvalidCompressedSNARK {
+    /// The reason for the decoding failure
+    reason: String,
+  },
+  /// returned when a proof was serialized by versions of the crate with no known compatible layout
+  #[error("IncompatibleVersion: {reason}")]
+  IncompatibleVersion {
+    /// The reason for the incompatibility
+    reason: String,
+  },
+  /// returned when bytes do not encode a scalar in the expected encoding
+  #[error("InvalidScalarEncoding: {reason}")]
+  InvalidScalarEncoding {
+    /// The reason the bytes are rejected
+    reason: String,
+  },
+  /// returned when the initial input supplied to the verifier does not match the one committed to in the proof
+  #[error("InputMismatch")]
+  InputMismatch,
+  /// returned when public parameters fail an internal consistency check
+  #[error("InvalidPublicParams: {reason}")]
+  InvalidPublicParams {
+    /// The reason for the validation failure
+    reason: String,
+  },
+  /// returned when a step circuit is incompatible with the public parameters it is used with
+  #[error("IncompatibleCircuit: {reason}")]
+  IncompatibleCircuit {
+    /// The reason for the incompatibility
+    reason: String,
+  },
+  /// returned when the commitment engine cannot derive a commitment key from a public seed
+  #[error("UnsupportedCommitmentKeySeed")]
+  UnsupportedCommitmentKeySeed,
+  /// returned when a commitment key cannot be extended with more generators
+  #[error("UnsupportedCommitmentKeyExtension")]
+  UnsupportedCommitmentKeyExtension,
   /// returned when there is an error creating a digest
   #[error("DigestError")]
   DigestError,
+  /// returned when the cached digest of public parameters does not match their contents
+  #[error("StalePublicParamsDigest")]
+  StalePublicParamsDigest,
   /// returned when the prover cannot prove the provided statement due to completeness error
   #[error("InternalError")]
   InternalError,
 }
 
+/// Errors returned when decoding an application state from its encoding as field elements
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+#[non_exhaustive]
+pub enum StateError {
+  /// returned if the number of field elements does not match the arity of the state
+  #[error("InvalidLength: expected {expected}, got {actual}")]
+  InvalidLength {
+    /// The arity of the state
+    expected: usize,
+    /// The number of field elements supplied
+    actual: usize,
+  },
+  /// returned if a field element does not encode a valid value of the state
+  #[error("InvalidValue: {reason}")]
+  InvalidValue {
+    /// The reason the value is invalid
+    reason: String,
+  },
+}
+
 impl From<SynthesisError> for NovaError {
   fn from(err: SynthesisError) -> Self {
     Self::SynthesisError {
@@ -51,6 +51,12 @@ pub enum SynthesisError {
   /// Incompatible vector length
   #[error("incompatible vector length: {0}")]
   IncompatibleLengthVector(String),
+  /// Repeated synthesis of the same circuit performed different operations
+  #[error("non-deterministic synthesis: {0}")]
+  NonDeterministicSynthesis(String),
+  /// During synthesis, more constraints were enforced than the limit of the constraint system
+  #[error("constraint limit of {0} exceeded")]
+  ConstraintLimitExceeded(usize),
 }
 
 /// Represents a constraint system which can have new variables
@@ -358,9 +358,7 @@ where
     let mut result: Vec<Elt<Scalar>> = Vec::with_capacity(self.constants.width());
 
     for j in 0..self.constants.width() {
-      let column = (0..self.constants.width())
-        .map(|i| matrix[i][j])
-        .collect::<Vec<_>>();
+      let column = matrix.iter().map(|row| row[j]).collect::<Vec<_>>();
 
       let product = scalar_product::<Scalar, CS>(self.elements.as_slice(), &column)?;
 
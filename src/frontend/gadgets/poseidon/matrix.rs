@@ -147,9 +147,9 @@ pub(crate) fn kronecker_delta<F: PrimeField>(i: usize, j: usize) -> F {
 }
 
 pub(crate) fn is_identity<F: PrimeField>(matrix: &Matrix<F>) -> bool {
-  for i in 0..rows(matrix) {
-    for j in 0..columns(matrix) {
-      if matrix[i][j] != kronecker_delta(i, j) {
+  for (i, row) in matrix.iter().enumerate() {
+    for (j, elt) in row.iter().enumerate() {
+      if *elt != kronecker_delta(i, j) {
         return false;
       }
     }
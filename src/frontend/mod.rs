@@ -23,6 +23,15 @@ pub mod solver;
 pub mod test_shape_cs;
 pub mod util_cs;
 
+pub use util_cs::determinism::assert_deterministic_synthesis;
+
+/// The version of the semantics of circuit synthesis, e.g., of the order in which variables are
+/// allocated, which determine the R1CS shapes produced for a circuit. It must be bumped by any
+/// change to these semantics, so that public parameters produced before and after the change are
+/// detectably incompatible. Any version other than the first is bound into the digest of
+/// `PublicParams`, which leaves the digest of parameters produced with the first version unchanged.
+pub const SHAPE_FORMAT_VERSION: u32 = 1;
+
 #[cfg(test)]
 pub use util_cs::test_cs;
 
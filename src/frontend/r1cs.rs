@@ -23,11 +23,22 @@ pub trait NovaWitness<E: Engine> {
 }
 
 /// `NovaShape` provides methods for acquiring `R1CSShape` and `CommitmentKey` from implementers.
+/// Implementers must provide at least one of `r1cs_shape` and `r1cs_shape_only`.
 pub trait NovaShape<E: Engine> {
   /// Return an appropriate `R1CSShape` and `CommitmentKey` structs.
   /// A `CommitmentKeyHint` should be provided to help guide the construction of the `CommitmentKey`.
   /// This parameter is documented in `r1cs::R1CS::commitment_key`.
-  fn r1cs_shape(&self, ck_hint: &CommitmentKeyHint<E>) -> (R1CSShape<E>, CommitmentKey<E>);
+  fn r1cs_shape(&self, ck_hint: &CommitmentKeyHint<E>) -> (R1CSShape<E>, CommitmentKey<E>) {
+    let S = self.r1cs_shape_only();
+    let ck = S.commitment_key(ck_hint);
+    (S, ck)
+  }
+
+  /// Return an appropriate `R1CSShape` without sampling a `CommitmentKey` for it.
+  /// By default, this samples the smallest `CommitmentKey` with `r1cs_shape` and discards it.
+  fn r1cs_shape_only(&self) -> R1CSShape<E> {
+    self.r1cs_shape(&|_| 0).0
+  }
 }
 
 impl<E: Engine> NovaWitness<E> for SatisfyingAssignment<E> {
@@ -53,7 +64,7 @@ macro_rules! impl_nova_shape {
     where
       E::Scalar: PrimeField,
     {
-      fn r1cs_shape(&self, ck_hint: &CommitmentKeyHint<E>) -> (R1CSShape<E>, CommitmentKey<E>) {
+      fn r1cs_shape_only(&self) -> R1CSShape<E> {
         let mut A = SparseMatrix::<E::Scalar>::empty();
         let mut B = SparseMatrix::<E::Scalar>::empty();
         let mut C = SparseMatrix::<E::Scalar>::empty();
@@ -80,10 +91,7 @@ macro_rules! impl_nova_shape {
         C.cols = num_vars + num_inputs;
 
         // Don't count One as an input for shape's purposes.
-        let S = R1CSShape::new(num_constraints, num_vars, num_inputs - 1, A, B, C).unwrap();
-        let ck = S.commitment_key(ck_hint);
-
-        (S, ck)
+        R1CSShape::new(num_constraints, num_vars, num_inputs - 1, A, B, C).unwrap()
       }
     }
   };
@@ -19,6 +19,9 @@ where
   )>,
   inputs: usize,
   aux: usize,
+  // the maximum number of constraints, if any, and whether more were enforced
+  constraint_limit: Option<usize>,
+  limit_exceeded: bool,
 }
 
 impl<E: Engine> ShapeCS<E> {
@@ -27,6 +30,25 @@ impl<E: Engine> ShapeCS<E> {
     ShapeCS::default()
   }
 
+  /// Create a new `ShapeCS` that stops recording constraints once `limit` constraints have been
+  /// enforced, and then returns `SynthesisError::ConstraintLimitExceeded` from every allocation,
+  /// so that a runaway circuit fails cleanly rather than exhausting memory.
+  pub fn with_constraint_limit(limit: usize) -> Self {
+    ShapeCS {
+      constraint_limit: Some(limit),
+      ..ShapeCS::default()
+    }
+  }
+
+  /// Returns `SynthesisError::ConstraintLimitExceeded` if more constraints were enforced than
+  /// the limit of this `ShapeCS`, which is never the case for one created with `new`.
+  pub fn check_constraint_limit(&self) -> Result<(), SynthesisError> {
+    match self.constraint_limit {
+      Some(limit) if self.limit_exceeded => Err(SynthesisError::ConstraintLimitExceeded(limit)),
+      _ => Ok(()),
+    }
+  }
+
   /// Returns the number of constraints defined for this `ShapeCS`.
   pub fn num_constraints(&self) -> usize {
     self.constraints.len()
@@ -49,6 +71,8 @@ impl<E: Engine> Default for ShapeCS<E> {
       constraints: vec![],
       inputs: 1,
       aux: 0,
+      constraint_limit: None,
+      limit_exceeded: false,
     }
   }
 }
@@ -62,6 +86,7 @@ impl<E: Engine> ConstraintSystem<E::Scalar> for ShapeCS<E> {
     A: FnOnce() -> AR,
     AR: Into<String>,
   {
+    self.check_constraint_limit()?;
     self.aux += 1;
 
     Ok(Variable::new_unchecked(Index::Aux(self.aux - 1)))
@@ -73,6 +98,7 @@ impl<E: Engine> ConstraintSystem<E::Scalar> for ShapeCS<E> {
     A: FnOnce() -> AR,
     AR: Into<String>,
   {
+    self.check_constraint_limit()?;
     self.inputs += 1;
 
     Ok(Variable::new_unchecked(Index::Input(self.inputs - 1)))
@@ -86,6 +112,14 @@ impl<E: Engine> ConstraintSystem<E::Scalar> for ShapeCS<E> {
     LB: FnOnce(LinearCombination<E::Scalar>) -> LinearCombination<E::Scalar>,
     LC: FnOnce(LinearCombination<E::Scalar>) -> LinearCombination<E::Scalar>,
   {
+    if self
+      .constraint_limit
+      .is_some_and(|limit| self.constraints.len() >= limit)
+    {
+      self.limit_exceeded = true;
+      return;
+    }
+
     let a = a(LinearCombination::zero());
     let b = b(LinearCombination::zero());
     let c = c(LinearCombination::zero());
@@ -0,0 +1,230 @@
+//! Support for checking that a step circuit allocates variables and constraints in a deterministic order.
+//!
+//! The shape of a circuit and its witness are produced by two separate synthesis passes
+//! (`ShapeCS` and `SatisfyingAssignment`), so they only agree if the circuit performs its
+//! allocations in the same order every time it is synthesized.
+use crate::{
+  frontend::{
+    num::AllocatedNum, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable,
+  },
+  traits::circuit::StepCircuit,
+};
+use ff::PrimeField;
+
+/// A single operation performed on a constraint system during synthesis
+#[derive(Clone, Debug, PartialEq)]
+enum SynthesisStep<Scalar: PrimeField> {
+  Alloc,
+  AllocInput,
+  Enforce(Box<[LinearCombination<Scalar>; 3]>),
+}
+
+/// A `ConstraintSystem` that records every allocation and constraint in the order it is made,
+/// along with its annotated path for diagnostics.
+struct TraceCS<Scalar: PrimeField> {
+  steps: Vec<(SynthesisStep<Scalar>, String)>,
+  current_namespace: Vec<String>,
+  num_inputs: usize,
+  num_aux: usize,
+}
+
+impl<Scalar: PrimeField> TraceCS<Scalar> {
+  fn path(&self, name: String) -> String {
+    self
+      .current_namespace
+      .iter()
+      .cloned()
+      .chain(Some(name))
+      .collect::<Vec<_>>()
+      .join("/")
+  }
+}
+
+impl<Scalar: PrimeField> ConstraintSystem<Scalar> for TraceCS<Scalar> {
+  type Root = Self;
+
+  fn new() -> Self {
+    Self {
+      steps: Vec::new(),
+      current_namespace: Vec::new(),
+      num_inputs: 1,
+      num_aux: 0,
+    }
+  }
+
+  fn alloc<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
+  where
+    F: FnOnce() -> Result<Scalar, SynthesisError>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    f()?;
+    let path = self.path(annotation().into());
+    self.steps.push((SynthesisStep::Alloc, path));
+    self.num_aux += 1;
+
+    Ok(Variable::new_unchecked(Index::Aux(self.num_aux - 1)))
+  }
+
+  fn alloc_input<F, A, AR>(&mut self, annotation: A, f: F) -> Result<Variable, SynthesisError>
+  where
+    F: FnOnce() -> Result<Scalar, SynthesisError>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    f()?;
+    let path = self.path(annotation().into());
+    self.steps.push((SynthesisStep::AllocInput, path));
+    self.num_inputs += 1;
+
+    Ok(Variable::new_unchecked(Index::Input(self.num_inputs - 1)))
+  }
+
+  fn enforce<A, AR, LA, LB, LC>(&mut self, annotation: A, a: LA, b: LB, c: LC)
+  where
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+    LA: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+  {
+    let path = self.path(annotation().into());
+    let a = a(LinearCombination::zero());
+    let b = b(LinearCombination::zero());
+    let c = c(LinearCombination::zero());
+    self
+      .steps
+      .push((SynthesisStep::Enforce(Box::new([a, b, c])), path));
+  }
+
+  fn push_namespace<NR, N>(&mut self, name_fn: N)
+  where
+    NR: Into<String>,
+    N: FnOnce() -> NR,
+  {
+    self.current_namespace.push(name_fn().into());
+  }
+
+  fn pop_namespace(&mut self) {
+    assert!(self.current_namespace.pop().is_some());
+  }
+
+  fn get_root(&mut self) -> &mut Self::Root {
+    self
+  }
+}
+
+/// Synthesizes `c` on the inputs `z` and returns the ordered trace of its allocations and constraints
+fn trace<Scalar: PrimeField, C: StepCircuit<Scalar>>(
+  c: &C,
+  z: &[Scalar],
+) -> Result<Vec<(SynthesisStep<Scalar>, String)>, SynthesisError> {
+  let mut cs = TraceCS::<Scalar>::new();
+  let z = z
+    .iter()
+    .enumerate()
+    .map(|(i, z_i)| AllocatedNum::alloc_infallible(cs.namespace(|| format!("z_{i}")), || *z_i))
+    .collect::<Vec<_>>();
+  c.synthesize(&mut cs, &z)?;
+  Ok(cs.steps)
+}
+
+/// Synthesizes the step circuit `c` on the inputs `z` a total of `iterations` times and checks
+/// that every run allocates the same variables and enforces the same constraints in the same order.
+///
+/// This is a debugging aid for circuits whose synthesis depends on a non-deterministic order,
+/// such as iterating over a `HashMap`, which leads to intermittent mismatches between the shape
+/// and the witness. On the first divergence, it returns `SynthesisError::NonDeterministicSynthesis`
+/// describing the run and the operation at which the traces differ.
+pub fn assert_deterministic_synthesis<F: PrimeField, C: StepCircuit<F>>(
+  c: &C,
+  z: &[F],
+  iterations: usize,
+) -> Result<(), SynthesisError> {
+  if iterations == 0 {
+    return Ok(());
+  }
+
+  let expected = trace(c, z)?;
+  for iteration in 1..iterations {
+    let actual = trace(c, z)?;
+    for (j, (expected_step, actual_step)) in expected.iter().zip(actual.iter()).enumerate() {
+      if expected_step.0 != actual_step.0 {
+        return Err(SynthesisError::NonDeterministicSynthesis(format!(
+          "run {iteration} diverges from run 0 at operation {j}: expected {:?} at `{}`, found {:?} at `{}`",
+          expected_step.0, expected_step.1, actual_step.0, actual_step.1
+        )));
+      }
+    }
+    if expected.len() != actual.len() {
+      return Err(SynthesisError::NonDeterministicSynthesis(format!(
+        "run {iteration} performs {} operations, while run 0 performs {}",
+        actual.len(),
+        expected.len()
+      )));
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    provider::PallasEngine,
+    traits::{circuit::TrivialCircuit, Engine},
+  };
+  use ff::Field;
+  use std::collections::HashMap;
+
+  type F = <PallasEngine as Engine>::Scalar;
+
+  #[derive(Clone, Debug, Default)]
+  struct HashMapCircuit {}
+
+  impl StepCircuit<F> for HashMapCircuit {
+    fn arity(&self) -> usize {
+      1
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+      &self,
+      cs: &mut CS,
+      z: &[AllocatedNum<F>],
+    ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+      // each `HashMap` is seeded at random, so its iteration order changes from one run to the next
+      let coeffs = (0..16u64)
+        .map(|i| (format!("c_{i}"), F::from(i + 1)))
+        .collect::<HashMap<_, _>>();
+
+      let mut acc = z[0].clone();
+      for (name, coeff) in coeffs.iter() {
+        let y = AllocatedNum::alloc(cs.namespace(|| name.clone()), || {
+          Ok(acc.get_value().unwrap_or(F::ZERO) * coeff)
+        })?;
+        cs.enforce(
+          || format!("{name} constraint"),
+          |lc| lc + (*coeff, acc.get_variable()),
+          |lc| lc + CS::one(),
+          |lc| lc + y.get_variable(),
+        );
+        acc = y;
+      }
+      Ok(vec![acc])
+    }
+  }
+
+  #[test]
+  fn test_assert_deterministic_synthesis() {
+    let z = [F::ONE];
+
+    assert!(assert_deterministic_synthesis(&TrivialCircuit::<F>::default(), &z, 10).is_ok());
+
+    let res = assert_deterministic_synthesis(&HashMapCircuit::default(), &z, 10);
+    assert!(matches!(
+      res,
+      Err(SynthesisError::NonDeterministicSynthesis(ref msg)) if msg.contains("diverges from run 0")
+    ));
+  }
+}
@@ -123,6 +123,20 @@ impl<Scalar: PrimeField> TestConstraintSystem<Scalar> {
     }
   }
 
+  /// Overwrites the value of the variable at `path`, e.g., to check that a gadget's constraints
+  /// reject a witness it would not compute itself
+  pub fn set(&mut self, path: &str, to: Scalar) {
+    match self
+      .inputs
+      .iter_mut()
+      .chain(self.aux.iter_mut())
+      .find(|(_, p)| p == path)
+    {
+      Some((value, _)) => *value = to,
+      None => panic!("no variable exists at path: {path}"),
+    }
+  }
+
   fn set_named_obj(&mut self, path: String, to: NamedObject) {
     assert!(
       !self.named_objects.contains_key(&path),
@@ -41,12 +41,49 @@ where
   // Assignments of variables
   pub(crate) input_assignment: Vec<Scalar>,
   pub(crate) aux_assignment: Vec<Scalar>,
+  // the number of constraints enforced, and the maximum number of constraints, if any
+  num_constraints: usize,
+  constraint_limit: Option<usize>,
 }
 
 impl<Scalar> WitnessCS<Scalar>
 where
   Scalar: PrimeField,
 {
+  /// Create a new empty constraint system with room for `num_aux` auxiliary variables
+  /// and `num_inputs` inputs (including the constant one), so that synthesizing a circuit
+  /// of a known size does not repeatedly reallocate the assignments
+  pub fn with_capacity(num_aux: usize, num_inputs: usize) -> Self {
+    let mut input_assignment = Vec::with_capacity(num_inputs.max(1));
+    input_assignment.push(Scalar::ONE);
+
+    Self {
+      input_assignment,
+      aux_assignment: Vec::with_capacity(num_aux),
+      num_constraints: 0,
+      constraint_limit: None,
+    }
+  }
+
+  /// Sets the maximum number of constraints that may be enforced, after which every allocation
+  /// returns `SynthesisError::ConstraintLimitExceeded`, so that a runaway circuit fails cleanly
+  /// rather than exhausting memory.
+  pub fn with_constraint_limit(mut self, limit: usize) -> Self {
+    self.constraint_limit = Some(limit);
+    self
+  }
+
+  /// Returns `SynthesisError::ConstraintLimitExceeded` if more constraints were enforced than
+  /// the limit of this constraint system, which is never the case if it has no limit.
+  pub fn check_constraint_limit(&self) -> Result<(), SynthesisError> {
+    match self.constraint_limit {
+      Some(limit) if self.num_constraints > limit => {
+        Err(SynthesisError::ConstraintLimitExceeded(limit))
+      }
+      _ => Ok(()),
+    }
+  }
+
   /// Get input assignment
   pub fn input_assignment(&self) -> &[Scalar] {
     &self.input_assignment
@@ -70,6 +107,8 @@ where
     Self {
       input_assignment,
       aux_assignment: vec![],
+      num_constraints: 0,
+      constraint_limit: None,
     }
   }
 
@@ -79,6 +118,7 @@ where
     A: FnOnce() -> AR,
     AR: Into<String>,
   {
+    self.check_constraint_limit()?;
     self.aux_assignment.push(f()?);
 
     Ok(Variable(Index::Aux(self.aux_assignment.len() - 1)))
@@ -90,6 +130,7 @@ where
     A: FnOnce() -> AR,
     AR: Into<String>,
   {
+    self.check_constraint_limit()?;
     self.input_assignment.push(f()?);
 
     Ok(Variable(Index::Input(self.input_assignment.len() - 1)))
@@ -103,7 +144,8 @@ where
     LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
     LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
   {
-    // Do nothing: we don't care about linear-combination evaluations in this context.
+    // We don't care about linear-combination evaluations in this context, only count them.
+    self.num_constraints += 1;
   }
 
   fn push_namespace<NR, N>(&mut self, _: N)
@@ -173,3 +215,71 @@ where
     &self.aux_assignment
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{frontend::num::AllocatedNum, provider::PallasEngine, traits::Engine};
+
+  type F = <PallasEngine as Engine>::Scalar;
+
+  const NUM_AUX: usize = 10_000;
+  const NUM_INPUTS: usize = 100;
+
+  /// Synthesizes a large circuit and returns the number of times the storage of the
+  /// assignments was reallocated, i.e., the number of times its capacity changed
+  fn count_reallocs(cs: &mut WitnessCS<F>) -> usize {
+    let capacity =
+      |cs: &WitnessCS<F>| (cs.aux_assignment.capacity(), cs.input_assignment.capacity());
+
+    let mut reallocs = 0;
+    let mut last = capacity(cs);
+    for i in 0..NUM_AUX + NUM_INPUTS - 1 {
+      if i < NUM_AUX {
+        let _ =
+          AllocatedNum::alloc_infallible(cs.namespace(|| format!("aux {i}")), || F::from(i as u64));
+      } else {
+        let _ = cs.alloc_input(|| format!("input {i}"), || Ok(F::from(i as u64)));
+      }
+      if capacity(cs) != last {
+        reallocs += 1;
+        last = capacity(cs);
+      }
+    }
+    reallocs
+  }
+
+  #[test]
+  fn test_with_capacity() {
+    let mut cs_new = WitnessCS::<F>::new();
+    let mut cs_with_capacity = WitnessCS::<F>::with_capacity(NUM_AUX, NUM_INPUTS);
+
+    let reallocs_new = count_reallocs(&mut cs_new);
+    let reallocs_with_capacity = count_reallocs(&mut cs_with_capacity);
+
+    // the assignments are the same, but pre-sizing the storage avoids the reallocations
+    assert_eq!(cs_new, cs_with_capacity);
+    assert!(reallocs_new > 0);
+    assert_eq!(reallocs_with_capacity, 0);
+  }
+
+  #[test]
+  fn test_constraint_limit() {
+    let limit = 100;
+    let mut cs = WitnessCS::<F>::new().with_constraint_limit(limit);
+
+    // a circuit that squares its input forever stops at the limit rather than running out of memory
+    let mut x = AllocatedNum::alloc_infallible(cs.namespace(|| "x"), || F::from(3u64));
+    let mut i = 0;
+    let err = loop {
+      match x.square(cs.namespace(|| format!("square {i}"))) {
+        Ok(y) => x = y,
+        Err(e) => break e,
+      }
+      i += 1;
+    };
+    assert!(matches!(err, SynthesisError::ConstraintLimitExceeded(l) if l == limit));
+    assert_eq!(i, limit + 1);
+    assert!(cs.aux_assignment().len() <= limit + 2);
+  }
+}
@@ -0,0 +1,92 @@
+//! This module implements arithmetic gadgets over allocated field elements whose naive
+//! implementations are unsound for some inputs.
+use crate::frontend::{num::AllocatedNum, Assignment, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+/// Divides `a` by `b`, returning `q = a * b^{-1}`.
+///
+/// Enforcing `q * b = a` alone would leave `q` unconstrained when `b = 0` and `a = 0`, so this
+/// also enforces that `b` is nonzero by allocating its inverse `b_inv` and enforcing
+/// `b * b_inv = 1`. Returns `SynthesisError::DivisionByZero` when computing the witness if `b = 0`.
+pub fn div<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  a: &AllocatedNum<F>,
+  b: &AllocatedNum<F>,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+  let b_inv = AllocatedNum::alloc(cs.namespace(|| "b_inv"), || {
+    Option::from(b.get_value().get()?.invert()).ok_or(SynthesisError::DivisionByZero)
+  })?;
+  cs.enforce(
+    || "b * b_inv = 1",
+    |lc| lc + b.get_variable(),
+    |lc| lc + b_inv.get_variable(),
+    |lc| lc + CS::one(),
+  );
+
+  let q = AllocatedNum::alloc(cs.namespace(|| "q"), || {
+    Ok(*a.get_value().get()? * *b_inv.get_value().get()?)
+  })?;
+  cs.enforce(
+    || "q * b = a",
+    |lc| lc + q.get_variable(),
+    |lc| lc + b.get_variable(),
+    |lc| lc + a.get_variable(),
+  );
+
+  Ok(q)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{frontend::test_cs::TestConstraintSystem, provider::pasta::pallas::Scalar as Fp};
+  use ff::Field;
+
+  fn alloc_pair(
+    cs: &mut TestConstraintSystem<Fp>,
+    a: Fp,
+    b: Fp,
+  ) -> (AllocatedNum<Fp>, AllocatedNum<Fp>) {
+    let a = AllocatedNum::alloc(cs.namespace(|| "a"), || Ok(a)).unwrap();
+    let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(b)).unwrap();
+    (a, b)
+  }
+
+  #[test]
+  fn test_div() {
+    let mut cs = TestConstraintSystem::<Fp>::new();
+    let (a, b) = alloc_pair(&mut cs, Fp::from(21u64), Fp::from(7u64));
+    let q = div(cs.namespace(|| "div"), &a, &b).unwrap();
+    assert_eq!(q.get_value(), Some(Fp::from(3u64)));
+    assert!(cs.is_satisfied());
+
+    // zero divided by a nonzero number is zero
+    let mut cs = TestConstraintSystem::<Fp>::new();
+    let (a, b) = alloc_pair(&mut cs, Fp::ZERO, Fp::from(7u64));
+    let q = div(cs.namespace(|| "div"), &a, &b).unwrap();
+    assert_eq!(q.get_value(), Some(Fp::ZERO));
+    assert!(cs.is_satisfied());
+  }
+
+  #[test]
+  fn test_div_by_zero() {
+    // the witness cannot be computed
+    for a in [Fp::ZERO, Fp::ONE] {
+      let mut cs = TestConstraintSystem::<Fp>::new();
+      let (a, b) = alloc_pair(&mut cs, a, Fp::ZERO);
+      assert!(matches!(
+        div(cs.namespace(|| "div"), &a, &b),
+        Err(SynthesisError::DivisionByZero)
+      ));
+    }
+
+    // and no assignment of the quotient and the inverse satisfies the constraints, e.g., the
+    // quotient zero of zero by zero that `q * b = a` alone would accept
+    let mut cs = TestConstraintSystem::<Fp>::new();
+    let (a, b) = alloc_pair(&mut cs, Fp::ZERO, Fp::ONE);
+    div(cs.namespace(|| "div"), &a, &b).unwrap();
+    cs.set("b/num", Fp::ZERO);
+    cs.set("div/q/num", Fp::ZERO);
+    assert!(!cs.is_satisfied());
+  }
+}
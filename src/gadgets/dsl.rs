@@ -0,0 +1,235 @@
+//! This module implements a minimal DSL for step circuits whose transition function is an
+//! arithmetic expression over the inputs, built from `input`, `constant`, `add`, and `mul`.
+//!
+//! Additions and multiplications by a constant are folded into linear combinations, so
+//! a transition costs one constraint per multiplication of two non-constant expressions
+//! and one constraint per output.
+use crate::{
+  errors::NovaError,
+  frontend::{num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError},
+  traits::circuit::StepCircuit,
+};
+use ff::PrimeField;
+
+/// An arithmetic expression over the inputs of a step
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr<F: PrimeField> {
+  /// The `i`-th input of the step
+  Input(usize),
+  /// A constant
+  Constant(F),
+  /// The sum of two expressions
+  Add(Box<Expr<F>>, Box<Expr<F>>),
+  /// The product of two expressions
+  Mul(Box<Expr<F>>, Box<Expr<F>>),
+}
+
+/// Returns the expression for the `i`-th input of the step
+pub fn input<F: PrimeField>(i: usize) -> Expr<F> {
+  Expr::Input(i)
+}
+
+/// Returns the expression for the constant `c`
+pub fn constant<F: PrimeField>(c: F) -> Expr<F> {
+  Expr::Constant(c)
+}
+
+/// Returns the expression for `a + b`
+pub fn add<F: PrimeField>(a: Expr<F>, b: Expr<F>) -> Expr<F> {
+  Expr::Add(Box::new(a), Box::new(b))
+}
+
+/// Returns the expression for `a * b`
+pub fn mul<F: PrimeField>(a: Expr<F>, b: Expr<F>) -> Expr<F> {
+  Expr::Mul(Box::new(a), Box::new(b))
+}
+
+impl<F: PrimeField> Expr<F> {
+  /// Evaluates the expression on the inputs `z`
+  pub fn eval(&self, z: &[F]) -> F {
+    match self {
+      Expr::Input(i) => z[*i],
+      Expr::Constant(c) => *c,
+      Expr::Add(a, b) => a.eval(z) + b.eval(z),
+      Expr::Mul(a, b) => a.eval(z) * b.eval(z),
+    }
+  }
+
+  // returns the largest input index that the expression refers to, if any
+  fn max_input(&self) -> Option<usize> {
+    match self {
+      Expr::Input(i) => Some(*i),
+      Expr::Constant(_) => None,
+      Expr::Add(a, b) | Expr::Mul(a, b) => a.max_input().max(b.max_input()),
+    }
+  }
+
+  // returns the constant value of the expression if it does not depend on the inputs
+  fn as_constant(&self) -> Option<F> {
+    match self {
+      Expr::Input(_) => None,
+      Expr::Constant(c) => Some(*c),
+      Expr::Add(a, b) => Some(a.as_constant()? + b.as_constant()?),
+      Expr::Mul(a, b) => Some(a.as_constant()? * b.as_constant()?),
+    }
+  }
+
+  // returns a linear combination equal to the expression along with its value, if known,
+  // allocating a variable for every product of two non-constant expressions
+  fn synthesize<CS: ConstraintSystem<F>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<F>],
+    num_products: &mut usize,
+  ) -> Result<(LinearCombination<F>, Option<F>), SynthesisError> {
+    if let Some(c) = self.as_constant() {
+      return Ok((LinearCombination::zero() + (c, CS::one()), Some(c)));
+    }
+
+    match self {
+      Expr::Input(i) => Ok((
+        LinearCombination::zero() + z[*i].get_variable(),
+        z[*i].get_value(),
+      )),
+      Expr::Constant(_) => unreachable!(),
+      Expr::Add(a, b) => {
+        let (lc_a, val_a) = a.synthesize(cs, z, num_products)?;
+        let (lc_b, val_b) = b.synthesize(cs, z, num_products)?;
+        Ok((lc_a + &lc_b, val_a.zip(val_b).map(|(a, b)| a + b)))
+      }
+      Expr::Mul(a, b) => {
+        // a product with a constant scales the linear combination of the other factor
+        if let Some(c) = a.as_constant().or(b.as_constant()) {
+          let other = if a.as_constant().is_some() { b } else { a };
+          let (lc, val) = other.synthesize(cs, z, num_products)?;
+          return Ok((LinearCombination::zero() + (c, &lc), val.map(|v| v * c)));
+        }
+
+        let (lc_a, val_a) = a.synthesize(cs, z, num_products)?;
+        let (lc_b, val_b) = b.synthesize(cs, z, num_products)?;
+        let val = val_a.zip(val_b).map(|(a, b)| a * b);
+        let k = *num_products;
+        *num_products += 1;
+
+        let product = AllocatedNum::alloc(cs.namespace(|| format!("product {k}")), || {
+          val.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce(
+          || format!("product {k} constraint"),
+          |_| lc_a,
+          |_| lc_b,
+          |lc| lc + product.get_variable(),
+        );
+        Ok((LinearCombination::zero() + product.get_variable(), val))
+      }
+    }
+  }
+}
+
+/// A step circuit whose transition maps the inputs `z` to the outputs `outputs[j].eval(z)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransitionCircuit<F: PrimeField> {
+  outputs: Vec<Expr<F>>,
+}
+
+impl<F: PrimeField> TransitionCircuit<F> {
+  /// Creates a step circuit with an output for each of the provided expressions, so its arity
+  /// is the number of expressions.
+  ///
+  /// Returns `NovaError::InvalidIndex` if an expression refers to an input beyond the arity.
+  pub fn new(outputs: Vec<Expr<F>>) -> Result<Self, NovaError> {
+    if outputs
+      .iter()
+      .any(|e| e.max_input().is_some_and(|i| i >= outputs.len()))
+    {
+      return Err(NovaError::InvalidIndex);
+    }
+    Ok(Self { outputs })
+  }
+
+  /// Returns the outputs of the transition on the inputs `z`
+  pub fn output(&self, z: &[F]) -> Vec<F> {
+    self.outputs.iter().map(|e| e.eval(z)).collect()
+  }
+}
+
+impl<F: PrimeField> StepCircuit<F> for TransitionCircuit<F> {
+  fn arity(&self) -> usize {
+    self.outputs.len()
+  }
+
+  fn synthesize<CS: ConstraintSystem<F>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<F>],
+  ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+    let mut num_products = 0;
+    self
+      .outputs
+      .iter()
+      .enumerate()
+      .map(|(j, e)| {
+        let (lc, val) = e.synthesize(
+          &mut cs.namespace(|| format!("output {j} expr")),
+          z,
+          &mut num_products,
+        )?;
+        let out = AllocatedNum::alloc(cs.namespace(|| format!("output {j}")), || {
+          val.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce(
+          || format!("output {j} constraint"),
+          |_| lc,
+          |lc| lc + CS::one(),
+          |lc| lc + out.get_variable(),
+        );
+        Ok(out)
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{frontend::test_cs::TestConstraintSystem, provider::pasta::pallas::Scalar as Fp};
+
+  #[test]
+  fn test_transition_circuit() {
+    // (z0, z1) -> (z0 * z1 + 2 * z0, 3 + z1)
+    let circuit = TransitionCircuit::new(vec![
+      add(
+        mul(input(0), input(1)),
+        mul(constant(Fp::from(2u64)), input(0)),
+      ),
+      add(constant(Fp::from(3u64)), input(1)),
+    ])
+    .unwrap();
+    let z = [Fp::from(5u64), Fp::from(7u64)];
+    assert_eq!(circuit.output(&z), vec![Fp::from(45u64), Fp::from(10u64)]);
+
+    let mut cs = TestConstraintSystem::<Fp>::new();
+    let z_alloc = z
+      .iter()
+      .enumerate()
+      .map(|(i, v)| AllocatedNum::alloc_infallible(cs.namespace(|| format!("z{i}")), || *v))
+      .collect::<Vec<_>>();
+    let out = circuit.synthesize(&mut cs, &z_alloc).unwrap();
+    assert!(cs.is_satisfied());
+    assert_eq!(
+      out
+        .iter()
+        .map(|o| o.get_value().unwrap())
+        .collect::<Vec<_>>(),
+      circuit.output(&z)
+    );
+    // one product of inputs and one constraint per output
+    assert_eq!(cs.num_constraints(), 3);
+
+    // an expression cannot refer to an input beyond the arity
+    assert_eq!(
+      TransitionCircuit::new(vec![input::<Fp>(1)]),
+      Err(NovaError::InvalidIndex)
+    );
+  }
+}
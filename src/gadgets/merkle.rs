@@ -0,0 +1,166 @@
+//! This module implements a minimal binary Merkle tree over SHA3-256, used to check that a
+//! digest of public parameters belongs to a published set of digests.
+//!
+//! Leaves and internal nodes are hashed with distinct prefixes so that an internal node
+//! cannot be passed off as a leaf. A level with an odd number of nodes promotes its last
+//! node to the level above unhashed, and the root commits to the number of leaves, so that
+//! trees with different numbers of leaves have different roots.
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+const ROOT_PREFIX: u8 = 2;
+
+fn hash_leaf(leaf: &[u8]) -> [u8; 32] {
+  let mut hasher = Sha3_256::new();
+  hasher.update([LEAF_PREFIX]);
+  hasher.update(leaf);
+  hasher.finalize().into()
+}
+
+fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+  let mut hasher = Sha3_256::new();
+  hasher.update([NODE_PREFIX]);
+  hasher.update(left);
+  hasher.update(right);
+  hasher.finalize().into()
+}
+
+fn hash_root(num_leaves: u64, top: &[u8; 32]) -> [u8; 32] {
+  let mut hasher = Sha3_256::new();
+  hasher.update([ROOT_PREFIX]);
+  hasher.update(num_leaves.to_le_bytes());
+  hasher.update(top);
+  hasher.finalize().into()
+}
+
+/// Computes the nodes of the level above `level`
+fn parent_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+  level
+    .chunks(2)
+    .map(|pair| match pair {
+      [left, right] => hash_node(left, right),
+      _ => pair[0],
+    })
+    .collect()
+}
+
+/// Returns the Merkle root of the provided leaves, or `None` if there are no leaves
+pub fn merkle_root<L: AsRef<[u8]>>(leaves: &[L]) -> Option<[u8; 32]> {
+  let mut level = leaves
+    .iter()
+    .map(|leaf| hash_leaf(leaf.as_ref()))
+    .collect::<Vec<_>>();
+  while level.len() > 1 {
+    level = parent_level(&level);
+  }
+  level.first().map(|top| hash_root(leaves.len() as u64, top))
+}
+
+/// A proof that a leaf is included in a Merkle tree at a given position
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+  /// The position of the leaf in the tree
+  pub index: u64,
+  /// The number of leaves in the tree
+  pub num_leaves: u64,
+  /// The siblings of the nodes on the path from the leaf to the root, from the bottom up,
+  /// skipping the levels where the node is promoted unhashed
+  pub siblings: Vec<[u8; 32]>,
+}
+
+impl MerkleProof {
+  /// Produces a proof of inclusion of the leaf at position `index`, or `None` if it is out of range
+  pub fn new<L: AsRef<[u8]>>(leaves: &[L], index: usize) -> Option<Self> {
+    if index >= leaves.len() {
+      return None;
+    }
+
+    let mut level = leaves
+      .iter()
+      .map(|leaf| hash_leaf(leaf.as_ref()))
+      .collect::<Vec<_>>();
+    let mut siblings = Vec::new();
+    let mut i = index;
+    while level.len() > 1 {
+      if let Some(sibling) = level.get(i ^ 1) {
+        siblings.push(*sibling);
+      }
+      level = parent_level(&level);
+      i /= 2;
+    }
+
+    Some(Self {
+      index: index as u64,
+      num_leaves: leaves.len() as u64,
+      siblings,
+    })
+  }
+
+  /// Checks that `leaf` is included at position `self.index` in the tree with the given `root`
+  pub fn verify(&self, leaf: &[u8], root: &[u8; 32]) -> bool {
+    if self.index >= self.num_leaves {
+      return false;
+    }
+
+    let mut siblings = self.siblings.iter();
+    let (mut node, mut i, mut len) = (hash_leaf(leaf), self.index, self.num_leaves);
+    while len > 1 {
+      // the last node of a level with an odd number of nodes has no sibling
+      if i ^ 1 < len {
+        let Some(sibling) = siblings.next() else {
+          return false;
+        };
+        node = if i & 1 == 0 {
+          hash_node(&node, sibling)
+        } else {
+          hash_node(sibling, &node)
+        };
+      }
+      i /= 2;
+      len = len.div_ceil(2);
+    }
+    siblings.next().is_none() && &hash_root(self.num_leaves, &node) == root
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_merkle_proof() {
+    let leaves = (0..7u8).map(|i| [i; 32]).collect::<Vec<_>>();
+    for n in 1..=leaves.len() {
+      let root = merkle_root(&leaves[..n]).unwrap();
+      for (i, leaf) in leaves[..n].iter().enumerate() {
+        let proof = MerkleProof::new(&leaves[..n], i).unwrap();
+        assert!(proof.verify(leaf, &root));
+
+        // the proof does not hold for a different leaf, any other position, or another tree size
+        assert!(!proof.verify(&[0xff; 32], &root));
+        for j in 0..=leaves.len() as u64 {
+          let mut moved = proof.clone();
+          moved.index = j;
+          assert_eq!(moved.verify(leaf, &root), j == i as u64);
+
+          let mut resized = proof.clone();
+          resized.num_leaves = j;
+          assert_eq!(resized.verify(leaf, &root), j == n as u64);
+        }
+      }
+      assert!(MerkleProof::new(&leaves[..n], n).is_none());
+    }
+    assert!(merkle_root::<[u8; 32]>(&[]).is_none());
+  }
+
+  #[test]
+  fn test_merkle_root_odd_level() {
+    // duplicating the last leaf of a level with an odd number of nodes changes the root
+    let leaves = (0..3u8).map(|i| [i; 32]).collect::<Vec<_>>();
+    let mut padded = leaves.clone();
+    padded.push(leaves[2]);
+    assert_ne!(merkle_root(&leaves), merkle_root(&padded));
+  }
+}
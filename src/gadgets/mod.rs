@@ -1,4 +1,7 @@
 //! This module implements various gadgets necessary for Nova and applications built with Nova.
+pub mod arith;
+pub mod dsl;
 pub(crate) mod ecc;
+pub mod merkle;
 pub(crate) mod nonnative;
 pub(crate) mod utils;
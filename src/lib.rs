@@ -29,6 +29,9 @@ mod constants;
 mod digest;
 mod r1cs;
 
+// the R1CS types folded by `nova::NIFS` and `nova::NIFSMulti`
+pub use r1cs::{R1CSInstance, R1CSShape, R1CSWitness, RelaxedR1CSInstance, RelaxedR1CSWitness};
+
 use traits::{commitment::CommitmentEngineTrait, Engine};
 
 // some type aliases
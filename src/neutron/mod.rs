@@ -113,6 +113,9 @@ where
     _ck_hint2: &CommitmentKeyHint<E2>,
   ) -> Result<Self, NovaError> {
     let F_arity = c.arity();
+    if F_arity == 0 {
+      return Err(NovaError::ZeroArity);
+    }
 
     let ro_consts: RO2Constants<E1> = RO2Constants::<E1>::default();
     let ro_consts_circuit: RO2ConstantsCircuit<E1> = RO2ConstantsCircuit::<E1>::default();
@@ -194,6 +197,8 @@ where
     if z0.len() != pp.F_arity {
       return Err(NovaError::InvalidInitialInputLength);
     }
+    c.validate_initial(z0)
+      .map_err(|reason| NovaError::InvalidInitialState { reason })?;
 
     let ri = E1::Scalar::random(&mut OsRng);
 
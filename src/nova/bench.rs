@@ -0,0 +1,139 @@
+//! This module provides a micro-benchmark harness that measures the folding throughput of a `RecursiveSNARK`.
+use super::{PublicParams, RecursiveSNARK};
+use crate::{
+  errors::NovaError,
+  traits::{circuit::StepCircuit, Engine},
+};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Statistics on the latency of `prove_step` collected by `RecursiveSNARK::benchmark_throughput`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ThroughputReport {
+  /// The number of timed steps
+  pub steps: usize,
+  /// The mean latency of a step
+  pub mean: Duration,
+  /// The median latency of a step
+  pub median: Duration,
+  /// The 99th percentile latency of a step
+  pub p99: Duration,
+  /// The number of steps proven per second
+  pub steps_per_sec: f64,
+}
+
+impl ThroughputReport {
+  fn from_latencies(mut latencies: Vec<Duration>) -> Self {
+    latencies.sort();
+
+    let steps = latencies.len();
+    let total: Duration = latencies.iter().sum();
+    let mean = total / steps as u32;
+    let median = if steps % 2 == 0 {
+      (latencies[steps / 2 - 1] + latencies[steps / 2]) / 2
+    } else {
+      latencies[steps / 2]
+    };
+    // nearest-rank percentile
+    let p99 = latencies[(steps * 99).div_ceil(100) - 1];
+    let steps_per_sec = steps as f64 / total.as_secs_f64();
+
+    Self {
+      steps,
+      mean,
+      median,
+      p99,
+      steps_per_sec,
+    }
+  }
+}
+
+impl<E1, E2, C> RecursiveSNARK<E1, E2, C>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+{
+  /// Measures the latency of `prove_step` for the circuit `c` starting from `z0`.
+  ///
+  /// A fresh `RecursiveSNARK` is created and `warmup` steps are proven untimed, followed by
+  /// `measured` timed steps. The first call to `prove_step` after `new` does not fold anything
+  /// (the base case is handled by the constructor), so it is always executed untimed in addition
+  /// to the `warmup` steps.
+  pub fn benchmark_throughput(
+    pp: &PublicParams<E1, E2, C>,
+    c: &C,
+    z0: &[E1::Scalar],
+    warmup: usize,
+    measured: usize,
+  ) -> Result<ThroughputReport, NovaError> {
+    if measured == 0 {
+      return Err(NovaError::InvalidNumSteps);
+    }
+
+    let mut recursive_snark = Self::new(pp, c, z0)?;
+
+    // the base case step, followed by the warmup steps
+    for _ in 0..warmup + 1 {
+      recursive_snark.prove_step(pp, c)?;
+    }
+
+    let latencies = (0..measured)
+      .map(|_| {
+        let start = Instant::now();
+        recursive_snark.prove_step(pp, c)?;
+        Ok(start.elapsed())
+      })
+      .collect::<Result<Vec<Duration>, NovaError>>()?;
+
+    Ok(ThroughputReport::from_latencies(latencies))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    provider::{PallasEngine, VestaEngine},
+    traits::{circuit::TrivialCircuit, snark::default_ck_hint},
+  };
+  use ff::Field;
+
+  type E1 = PallasEngine;
+  type E2 = VestaEngine;
+
+  #[test]
+  fn test_benchmark_throughput() {
+    let circuit = TrivialCircuit::<<E1 as Engine>::Scalar>::default();
+    let pp = PublicParams::<E1, E2, TrivialCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let report =
+      RecursiveSNARK::benchmark_throughput(&pp, &circuit, &[<E1 as Engine>::Scalar::ZERO], 1, 3)
+        .unwrap();
+
+    assert_eq!(report.steps, 3);
+    assert!(report.mean > Duration::ZERO);
+    assert!(report.median > Duration::ZERO);
+    assert!(report.p99 >= report.median);
+    assert!(report.steps_per_sec > 0.0);
+
+    let res =
+      RecursiveSNARK::benchmark_throughput(&pp, &circuit, &[<E1 as Engine>::Scalar::ZERO], 1, 0);
+    assert_eq!(res, Err(NovaError::InvalidNumSteps));
+  }
+
+  #[test]
+  fn test_report_statistics() {
+    let latencies = (1..=100).map(Duration::from_millis).collect::<Vec<_>>();
+    let report = ThroughputReport::from_latencies(latencies);
+    assert_eq!(report.steps, 100);
+    assert_eq!(report.mean, Duration::from_micros(50_500));
+    assert_eq!(report.median, Duration::from_micros(50_500));
+    assert_eq!(report.p99, Duration::from_millis(99));
+  }
+}
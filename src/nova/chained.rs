@@ -0,0 +1,111 @@
+//! This module implements the parts shared by the step circuits that extend the state of a wrapped
+//! step circuit with a hash chain, i.e., `TracedCircuit` and `StepParamsCircuit`.
+//!
+//! The chain is carried as an extra element of the state, after the outputs of the wrapped step
+//! and before its public signals, and is zero initially. It is computed with the constants of the
+//! circuit RO of the primary augmented circuit, i.e., those of the RO of `E2` in the public
+//! parameters, so the verifier recomputes it with the constants of the public parameters.
+use crate::{
+  constants::NUM_HASH_BITS,
+  frontend::{num::AllocatedNum, ConstraintSystem, SynthesisError},
+  gadgets::utils::le_bits_to_num,
+  traits::{Engine, ROCircuitTrait, ROConstantsCircuit, ROTrait},
+};
+use core::fmt;
+use ff::Field;
+
+/// A hash chain carried after the state of a wrapped step circuit
+#[derive(Clone)]
+pub(super) struct HashChain<E: Engine> {
+  ro_consts: ROConstantsCircuit<E>,
+}
+
+// the RO constants are not `Debug`
+impl<E: Engine> fmt::Debug for HashChain<E> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("HashChain").finish_non_exhaustive()
+  }
+}
+
+impl<E: Engine> HashChain<E> {
+  pub(super) fn new(ro_consts: ROConstantsCircuit<E>) -> Self {
+    Self { ro_consts }
+  }
+
+  // hashes `elements` outside the circuit, as `synthesize_hash` does inside it
+  pub(super) fn hash(&self, elements: &[E::Base]) -> E::Base {
+    hash::<E>(&self.ro_consts, elements)
+  }
+
+  // hashes the allocated `elements`
+  pub(super) fn synthesize_hash<CS: ConstraintSystem<E::Base>>(
+    &self,
+    mut cs: CS,
+    elements: &[AllocatedNum<E::Base>],
+  ) -> Result<AllocatedNum<E::Base>, SynthesisError> {
+    let mut ro = E::ROCircuit::new(self.ro_consts.clone());
+    for e in elements {
+      ro.absorb(e);
+    }
+    let hash_bits = ro.squeeze(cs.namespace(|| "squeeze"), NUM_HASH_BITS)?;
+    le_bits_to_num(cs.namespace(|| "bits to hash"), &hash_bits)
+  }
+
+  // synthesizes a step of a wrapped step circuit of arity `arity` with `num_public_signals`
+  // signals, where `step` returns the outputs and signals of the wrapped step along with the next
+  // link of the chain, given the state of the wrapped step and the current link
+  pub(super) fn synthesize_step<CS, S>(
+    cs: &mut CS,
+    arity: usize,
+    num_public_signals: usize,
+    z: &[AllocatedNum<E::Base>],
+    step: S,
+  ) -> Result<Vec<AllocatedNum<E::Base>>, SynthesisError>
+  where
+    CS: ConstraintSystem<E::Base>,
+    S: FnOnce(
+      &mut CS,
+      &[AllocatedNum<E::Base>],
+      &AllocatedNum<E::Base>,
+    ) -> Result<(Vec<AllocatedNum<E::Base>>, AllocatedNum<E::Base>), SynthesisError>,
+  {
+    let (z, chain) = z.split_at(arity);
+    let (mut z_next, chain_next) = step(cs, z, &chain[0])?;
+    if z_next.len() != arity + num_public_signals {
+      return Err(SynthesisError::IncompatibleLengthVector(
+        "z_next of the wrapped step".to_string(),
+      ));
+    }
+
+    // the chain follows the outputs of the step, and precedes its public signals
+    z_next.insert(arity, chain_next);
+    Ok(z_next)
+  }
+
+  // checks that the chain of the initial state `z0` is zero, and the rest with `validate_initial`
+  pub(super) fn validate_initial(
+    arity: usize,
+    z0: &[E::Base],
+    validate_initial: impl FnOnce(&[E::Base]) -> Result<(), String>,
+  ) -> Result<(), String> {
+    let (z0, chain) = z0.split_at(arity);
+    if chain[0] != E::Base::ZERO {
+      return Err("the initial hash chain must be zero".to_string());
+    }
+    validate_initial(z0)
+  }
+}
+
+/// Hashes `elements` outside the circuit with the RO constants `ro_consts`
+pub(super) fn hash<E: Engine>(ro_consts: &ROConstantsCircuit<E>, elements: &[E::Base]) -> E::Base {
+  let mut ro = <E::ROCircuit as ROCircuitTrait<E::Base>>::NativeRO::new(ro_consts.clone());
+  for e in elements {
+    ro.absorb(*e);
+  }
+  ro.squeeze(NUM_HASH_BITS)
+}
+
+/// Returns the initial state `z0` of a wrapped step circuit followed by the initial chain
+pub(super) fn chained<F: Field>(z0: &[F]) -> Vec<F> {
+  [z0, &[F::ZERO]].concat()
+}
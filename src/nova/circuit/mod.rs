@@ -21,7 +21,7 @@ use crate::{
   },
   Commitment,
 };
-use ff::Field;
+use ff::{Field, PrimeFieldBits};
 use serde::{Deserialize, Serialize};
 
 mod r1cs;
@@ -39,6 +39,8 @@ pub struct NovaAugmentedCircuitInputs<E: Engine> {
   r_next: E::Base,
   u: Option<R1CSInstance<E>>,
   T: Option<Commitment<E>>,
+  signals: Vec<E::Base>,
+  num_steps: usize,
 }
 
 impl<E: Engine> NovaAugmentedCircuitInputs<E> {
@@ -64,8 +66,24 @@ impl<E: Engine> NovaAugmentedCircuitInputs<E> {
       r_next,
       u,
       T,
+      signals: Vec::new(),
+      num_steps: 1,
     }
   }
+
+  /// Supplies the public signals of the previous step, which are absorbed in the input hash
+  /// along with `zi`, and are zero in the base case
+  pub fn with_public_signals(mut self, signals: Vec<E::Base>) -> Self {
+    self.signals = signals;
+    self
+  }
+
+  /// Sets the number of steps executed by the fold, which is one unless the circuit executes
+  /// several steps per fold, as set by `NovaAugmentedCircuit::with_steps_per_fold`
+  pub fn with_num_steps(mut self, num_steps: usize) -> Self {
+    self.num_steps = num_steps;
+    self
+  }
 }
 
 /// The augmented circuit F' in Nova that includes a step circuit F
@@ -74,7 +92,12 @@ pub struct NovaAugmentedCircuit<'a, E: Engine, SC: StepCircuit<E::Base>> {
   is_primary_circuit: bool, // A boolean indicating if this is the primary circuit
   ro_consts: ROConstantsCircuit<E>,
   inputs: Option<NovaAugmentedCircuitInputs<E>>,
-  step_circuit: &'a SC, // The function that is applied for each step
+  step_circuit: &'a SC,      // The function that is applied for each step
+  trivial_step: bool,        // A boolean indicating if the step state is elided from the hashes
+  output_hash: bool,         // A boolean indicating if the hashes absorb a hash of zi instead of zi
+  folded_num_signals: usize, // The number of public signals of the instances of the other circuit
+  steps_per_fold: usize,     // The maximum number of steps executed per fold
+  further_steps: &'a [SC],   // The functions applied for the steps after the first one of a fold
 }
 
 impl<'a, E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'a, E, SC> {
@@ -90,9 +113,58 @@ impl<'a, E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'a, E, SC> {
       inputs,
       step_circuit,
       ro_consts,
+      trivial_step: false,
+      output_hash: false,
+      folded_num_signals: 0,
+      steps_per_fold: 1,
+      further_steps: &[],
     }
   }
 
+  /// Specializes the circuit for a trivial step circuit whose state never changes.
+  ///
+  /// The state `z0`, `zi` of such a circuit is a constant, so it is neither allocated nor
+  /// absorbed in the hashes of the running instance, and the step circuit is synthesized on an
+  /// empty state. This is only sound if the step circuit is the identity on a fixed initial state,
+  /// which holds for the secondary circuit, and is ignored for the primary circuit.
+  pub fn with_trivial_step(mut self, trivial_step: bool) -> Self {
+    self.trivial_step = trivial_step && !self.is_primary_circuit;
+    self
+  }
+
+  /// Absorbs a hash `H(zi)` of the state in the hashes of the running instance instead of its
+  /// elements, so that a verifier can check the hashes given `H(zi)` alone.
+  ///
+  /// The hash is computed in the circuit with the RO constants of the circuit, and does not
+  /// apply to `z0`, which the verifier supplies.
+  pub fn with_output_hash(mut self, output_hash: bool) -> Self {
+    self.output_hash = output_hash;
+    self
+  }
+
+  /// Sets the number of public signals of the instances of the other circuit that this circuit
+  /// folds, which the other circuit outputs after its two hashes. Only the primary circuit has
+  /// public signals, so this is the number of public signals of the primary step circuit for the
+  /// secondary circuit, and zero for the primary circuit.
+  pub fn with_folded_public_signals(mut self, num_signals: usize) -> Self {
+    self.folded_num_signals = num_signals;
+    self
+  }
+
+  /// Executes up to `steps_per_fold` steps per fold instead of one, where the first step is
+  /// executed by the step circuit and the following ones by `further_steps`.
+  ///
+  /// Each step after the first has a flag that selects whether it is executed, and `i` advances by
+  /// the number of executed steps, as set by `NovaAugmentedCircuitInputs::with_num_steps`. A step
+  /// that is not executed synthesizes the circuit of the previous step on the same input, so that
+  /// it is satisfied whenever that step is, and its output is discarded. The secondary circuit
+  /// executes its step circuit once, and only advances `i` by the number of steps of the primary.
+  pub fn with_steps_per_fold(mut self, steps_per_fold: usize, further_steps: &'a [SC]) -> Self {
+    self.steps_per_fold = steps_per_fold;
+    self.further_steps = further_steps;
+    self
+  }
+
   /// Allocate all witnesses and return
   fn alloc_witness<CS: ConstraintSystem<<E as Engine>::Base>>(
     &self,
@@ -144,6 +216,7 @@ impl<'a, E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'a, E, SC> {
     let U: AllocatedRelaxedR1CSInstance<E> = AllocatedRelaxedR1CSInstance::alloc(
       cs.namespace(|| "Allocate U"),
       self.inputs.as_ref().and_then(|inputs| inputs.U.as_ref()),
+      self.folded_num_signals,
     )?;
 
     // Allocate ri
@@ -158,6 +231,7 @@ impl<'a, E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'a, E, SC> {
     let u = AllocatedR1CSInstance::alloc(
       cs.namespace(|| "allocate instance u to fold"),
       self.inputs.as_ref().and_then(|inputs| inputs.u.as_ref()),
+      self.folded_num_signals,
     )?;
 
     // Allocate T
@@ -180,17 +254,32 @@ impl<'a, E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'a, E, SC> {
     i: &AllocatedNum<E::Base>,
     z_0: &[AllocatedNum<E::Base>],
     z_i: &[AllocatedNum<E::Base>],
+    signals_i: &[AllocatedNum<E::Base>],
     U: &AllocatedRelaxedR1CSInstance<E>,
     r_i: &AllocatedNum<E::Base>,
   ) -> Result<AllocatedNum<E::Base>, SynthesisError> {
-    // Check that u.x[0] = Hash(pp_digest, i, z_0, z_i, U, r_i)
+    // Check that u.x[0] = Hash(pp_digest, i, z_0, z_i, signals_i, U, r_i), where z_i is replaced
+    // by H(z_i) if the circuit outputs a hash of the state
     let mut ro = E::ROCircuit::new(self.ro_consts.clone());
     ro.absorb(pp_digest);
     ro.absorb(i);
     for e in z_0 {
       ro.absorb(e);
     }
-    for e in z_i {
+    if self.output_hash {
+      let mut ro_z = E::ROCircuit::new(self.ro_consts.clone());
+      for e in z_i {
+        ro_z.absorb(e);
+      }
+      let z_hash_bits = ro_z.squeeze(cs.namespace(|| "Hash of zi"), NUM_HASH_BITS)?;
+      let z_hash = le_bits_to_num(cs.namespace(|| "bits to hash of zi"), &z_hash_bits)?;
+      ro.absorb(&z_hash);
+    } else {
+      for e in z_i {
+        ro.absorb(e);
+      }
+    }
+    for e in signals_i {
       ro.absorb(e);
     }
     U.absorb_in_ro(cs.namespace(|| "absorb U"), &mut ro)?;
@@ -210,7 +299,10 @@ impl<'a, E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'a, E, SC> {
   ) -> Result<AllocatedRelaxedR1CSInstance<E>, SynthesisError> {
     let U_default: AllocatedRelaxedR1CSInstance<E> = if self.is_primary_circuit {
       // The primary circuit just returns the default R1CS instance
-      AllocatedRelaxedR1CSInstance::default(cs.namespace(|| "Allocate U_default"))?
+      AllocatedRelaxedR1CSInstance::default(
+        cs.namespace(|| "Allocate U_default"),
+        self.folded_num_signals,
+      )?
     } else {
       // The secondary circuit returns the incoming R1CS instance
       AllocatedRelaxedR1CSInstance::from_r1cs_instance(cs.namespace(|| "Allocate U_default"), u)?
@@ -247,12 +339,30 @@ impl<E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'_, E, SC> {
     self,
     cs: &mut CS,
   ) -> Result<Vec<AllocatedNum<E::Base>>, SynthesisError> {
-    let arity = self.step_circuit.arity();
+    // a trivial step circuit carries no state
+    let (arity, num_signals) = if self.trivial_step {
+      (0, 0)
+    } else {
+      (
+        self.step_circuit.arity(),
+        self.step_circuit.num_public_signals(),
+      )
+    };
 
     // Allocate all witnesses
     let (pp_digest, i, z_0, z_i, U, r_i, r_next, u, T) =
       self.alloc_witness(cs.namespace(|| "allocate the circuit witness"), arity)?;
 
+    // Allocate the public signals of the previous step, which are zero in the base case
+    let signals_i = (0..num_signals)
+      .map(|j| {
+        AllocatedNum::alloc(cs.namespace(|| format!("signals_i_{j}")), || {
+          let signals = &self.inputs.get()?.signals;
+          Ok(signals.get(j).copied().unwrap_or(E::Base::ZERO))
+        })
+      })
+      .collect::<Result<Vec<AllocatedNum<E::Base>>, _>>()?;
+
     // Compute variable indicating if this is the base case
     let zero = alloc_zero(cs.namespace(|| "zero"));
     let is_base_case = alloc_num_equals(cs.namespace(|| "Check if base case"), &i.clone(), &zero)?;
@@ -264,6 +374,7 @@ impl<E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'_, E, SC> {
       &i,
       &z_0,
       &z_i,
+      &signals_i,
       &U,
       &r_i,
     )?;
@@ -307,15 +418,37 @@ impl<E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'_, E, SC> {
       &Boolean::from(is_base_case.clone()),
     )?;
 
-    // Compute i + 1
+    // Allocate the flags of the steps after the first one of the fold, which are executed if set
+    let num_steps = self.inputs.as_ref().map(|inputs| inputs.num_steps);
+    let flags = (1..self.steps_per_fold)
+      .map(|j| {
+        AllocatedBit::alloc(
+          cs.namespace(|| format!("execute step {j} of the fold")),
+          num_steps.map(|n| j < n),
+        )
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    // Compute i + 1, plus the number of further steps executed
     let i_new = AllocatedNum::alloc(cs.namespace(|| "i + 1"), || {
-      Ok(*i.get_value().get()? + E::Base::ONE)
+      let mut i_new = *i.get_value().get()? + E::Base::ONE;
+      for flag in &flags {
+        if *flag.get_value().get()? {
+          i_new += E::Base::ONE;
+        }
+      }
+      Ok(i_new)
     })?;
     cs.enforce(
       || "check i + 1",
       |lc| lc,
       |lc| lc,
-      |lc| lc + i_new.get_variable() - CS::one() - i.get_variable(),
+      |lc| {
+        flags.iter().fold(
+          lc + i_new.get_variable() - CS::one() - i.get_variable(),
+          |lc, flag| lc - flag.get_variable(),
+        )
+      },
     );
 
     // Compute z_{i+1}
@@ -326,23 +459,66 @@ impl<E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'_, E, SC> {
       &Boolean::from(is_base_case),
     )?;
 
-    let z_next = self
+    let mut z_next = self
       .step_circuit
       .synthesize(&mut cs.namespace(|| "F"), &z_input)?;
 
-    if z_next.len() != arity {
+    if z_next.len() != arity + num_signals {
       return Err(SynthesisError::IncompatibleLengthVector(
         "z_next".to_string(),
       ));
     }
+    let mut signals_next = z_next.split_off(arity);
+
+    // Execute the further steps of the fold, only the first of which is executed by the secondary
+    if self.is_primary_circuit {
+      let mut step_circuit = self.step_circuit;
+      let mut step_input = z_input;
+      for (j, flag) in flags.iter().enumerate() {
+        let flag = Boolean::from(flag.clone());
+
+        // a step that is not executed repeats the previous one
+        step_circuit = self.further_steps.get(j).unwrap_or(step_circuit);
+        step_input = conditionally_select_vec(
+          cs.namespace(|| format!("select input to F_{}", j + 1)),
+          &z_next,
+          &step_input,
+          &flag,
+        )?;
+
+        let mut z_step =
+          step_circuit.synthesize(&mut cs.namespace(|| format!("F_{}", j + 1)), &step_input)?;
+        if z_step.len() != arity + num_signals {
+          return Err(SynthesisError::IncompatibleLengthVector(format!(
+            "z_next of step {}",
+            j + 1
+          )));
+        }
+        let signals_step = z_step.split_off(arity);
+
+        z_next = conditionally_select_vec(
+          cs.namespace(|| format!("select output of F_{}", j + 1)),
+          &z_step,
+          &z_next,
+          &flag,
+        )?;
+        signals_next = conditionally_select_vec(
+          cs.namespace(|| format!("select public signals of F_{}", j + 1)),
+          &signals_step,
+          &signals_next,
+          &flag,
+        )?;
+      }
+    }
 
-    // Compute the new hash H(pp_digest, Unew, i+1, z0, z_{i+1})
+    // Compute the new hash H(pp_digest, Unew, i+1, z0, z_{i+1}, signals_{i+1})
     let hash = self.synthesize_hash_check(
       cs.namespace(|| "synthesize output hash check"),
       &pp_digest,
       &i_new,
       &z_0,
       &z_next,
+      &signals_next,
       &Unew,
       &r_next,
     )?;
@@ -352,10 +528,111 @@ impl<E: Engine, SC: StepCircuit<E::Base>> NovaAugmentedCircuit<'_, E, SC> {
       .inputize(cs.namespace(|| "Output unmodified hash of the other circuit"))?;
     hash.inputize(cs.namespace(|| "output new hash of this circuit"))?;
 
+    // Outputs the public signals after the hashes. Like the hashes, they are range checked so
+    // that they have the same representation in the scalar field of the other curve, whose circuit
+    // folds them
+    for (j, signal) in signals_next.iter().enumerate() {
+      range_check(
+        cs.namespace(|| format!("range check public signal {j}")),
+        signal,
+        NUM_HASH_BITS,
+      )?;
+      signal.inputize(cs.namespace(|| format!("output public signal {j}")))?;
+    }
+
+    // the public signals follow the outputs, as returned by the step circuit
+    z_next.extend(signals_next);
     Ok(z_next)
   }
 }
 
+// checks that `num` is less than 2^num_bits
+fn range_check<F: PrimeFieldBits, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  num: &AllocatedNum<F>,
+  num_bits: usize,
+) -> Result<(), SynthesisError> {
+  let bits = (0..num_bits)
+    .map(|i| {
+      AllocatedBit::alloc(
+        cs.namespace(|| format!("bit {i}")),
+        num.get_value().map(|v| v.to_le_bits()[i]),
+      )
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+  let packed = le_bits_to_num(cs.namespace(|| "pack bits"), &bits)?;
+  cs.enforce(
+    || "num = packed bits",
+    |lc| lc + num.get_variable(),
+    |lc| lc + CS::one(),
+    |lc| lc + packed.get_variable(),
+  );
+  Ok(())
+}
+
+/// The step circuit of the secondary augmented circuit.
+///
+/// When enabled, its state is a hash chain `d_{i+1} = H(d_i, D_i)` over the commitments `D_i`
+/// to external data supplied at each step, which binds the sequence of data commitments into
+/// the hash of the running instance. Otherwise, it is the identity on its state.
+#[derive(Clone)]
+pub struct DataChainCircuit<E: Engine> {
+  ro_consts: Option<ROConstantsCircuit<E>>,
+  data: Option<Commitment<E>>,
+}
+
+impl<E: Engine> DataChainCircuit<E> {
+  /// Create the identity step circuit
+  pub const fn disabled() -> Self {
+    Self {
+      ro_consts: None,
+      data: None,
+    }
+  }
+
+  /// Create a step circuit that absorbs `data` in the chain, or the identity point if it is `None`
+  pub const fn new(ro_consts: ROConstantsCircuit<E>, data: Option<Commitment<E>>) -> Self {
+    Self {
+      ro_consts: Some(ro_consts),
+      data,
+    }
+  }
+}
+
+impl<E: Engine> StepCircuit<E::Base> for DataChainCircuit<E> {
+  fn arity(&self) -> usize {
+    1
+  }
+
+  fn synthesize<CS: ConstraintSystem<E::Base>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<E::Base>],
+  ) -> Result<Vec<AllocatedNum<E::Base>>, SynthesisError> {
+    let Some(ro_consts) = &self.ro_consts else {
+      return Ok(z.to_vec());
+    };
+
+    // Allocate the data commitment
+    let data = AllocatedPoint::<E>::alloc(
+      cs.namespace(|| "allocate data commitment"),
+      Some(self.data.unwrap_or_default().to_coordinates()),
+    )?;
+    data.check_on_curve(cs.namespace(|| "check data commitment on curve"))?;
+
+    // d_{i+1} = H(d_i, D_i)
+    let mut ro = E::ROCircuit::new(ro_consts.clone());
+    ro.absorb(&z[0]);
+    ro.absorb(&data.x);
+    ro.absorb(&data.y);
+    ro.absorb(&data.is_infinity);
+    let hash_bits = ro.squeeze(cs.namespace(|| "data chain hash"), NUM_HASH_BITS)?;
+    let hash = le_bits_to_num(cs.namespace(|| "bits to data chain hash"), &hash_bits)?;
+
+    Ok(vec![hash])
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -24,13 +24,16 @@ pub struct AllocatedR1CSInstance<E: Engine> {
   pub(crate) comm_W: AllocatedPoint<E>,
   pub(crate) X0: AllocatedNum<E::Base>,
   pub(crate) X1: AllocatedNum<E::Base>,
+  // the public signals that follow the two hashes
+  pub(crate) X_signals: Vec<AllocatedNum<E::Base>>,
 }
 
 impl<E: Engine> AllocatedR1CSInstance<E> {
-  /// Takes the r1cs instance and creates a new allocated r1cs instance
+  /// Takes the r1cs instance with `num_signals` public signals and creates a new allocated r1cs instance
   pub fn alloc<CS: ConstraintSystem<<E as Engine>::Base>>(
     mut cs: CS,
     u: Option<&R1CSInstance<E>>,
+    num_signals: usize,
   ) -> Result<Self, SynthesisError> {
     let comm_W = AllocatedPoint::alloc(
       cs.namespace(|| "allocate comm_W"),
@@ -40,8 +43,21 @@ impl<E: Engine> AllocatedR1CSInstance<E> {
 
     let X0 = alloc_scalar_as_base::<E, _>(cs.namespace(|| "allocate X[0]"), u.map(|u| u.X[0]))?;
     let X1 = alloc_scalar_as_base::<E, _>(cs.namespace(|| "allocate X[1]"), u.map(|u| u.X[1]))?;
+    let X_signals = (2..2 + num_signals)
+      .map(|j| {
+        alloc_scalar_as_base::<E, _>(
+          cs.namespace(|| format!("allocate X[{j}]")),
+          u.map(|u| u.X[j]),
+        )
+      })
+      .collect::<Result<Vec<_>, _>>()?;
 
-    Ok(AllocatedR1CSInstance { comm_W, X0, X1 })
+    Ok(AllocatedR1CSInstance {
+      comm_W,
+      X0,
+      X1,
+      X_signals,
+    })
   }
 
   /// Absorb the provided instance in the RO
@@ -51,6 +67,9 @@ impl<E: Engine> AllocatedR1CSInstance<E> {
     ro.absorb(&self.comm_W.is_infinity);
     ro.absorb(&self.X0);
     ro.absorb(&self.X1);
+    for x in &self.X_signals {
+      ro.absorb(x);
+    }
   }
 }
 
@@ -61,13 +80,17 @@ pub struct AllocatedRelaxedR1CSInstance<E: Engine> {
   pub(crate) u: AllocatedNum<E::Base>,
   pub(crate) X0: BigNat<E::Base>,
   pub(crate) X1: BigNat<E::Base>,
+  // the public signals that follow the two hashes
+  pub(crate) X_signals: Vec<BigNat<E::Base>>,
 }
 
 impl<E: Engine> AllocatedRelaxedR1CSInstance<E> {
-  /// Allocates the given `RelaxedR1CSInstance` as a witness of the circuit
+  /// Allocates the given `RelaxedR1CSInstance` with `num_signals` public signals as a witness of
+  /// the circuit
   pub fn alloc<CS: ConstraintSystem<<E as Engine>::Base>>(
     mut cs: CS,
     inst: Option<&RelaxedR1CSInstance<E>>,
+    num_signals: usize,
   ) -> Result<Self, SynthesisError> {
     // We do not need to check that W or E are well-formed (e.g., on the curve) as we do a hash check
     // in the Nova augmented circuit, which ensures that the relaxed instance
@@ -101,13 +124,32 @@ impl<E: Engine> AllocatedRelaxedR1CSInstance<E> {
       BN_N_LIMBS,
     )?;
 
-    Ok(AllocatedRelaxedR1CSInstance { W, E, u, X0, X1 })
+    let X_signals = (2..2 + num_signals)
+      .map(|j| {
+        BigNat::alloc_from_nat(
+          cs.namespace(|| format!("allocate X[{j}]")),
+          || Ok(f_to_nat(&inst.map_or(E::Scalar::ZERO, |inst| inst.X[j]))),
+          BN_LIMB_WIDTH,
+          BN_N_LIMBS,
+        )
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(AllocatedRelaxedR1CSInstance {
+      W,
+      E,
+      u,
+      X0,
+      X1,
+      X_signals,
+    })
   }
 
-  /// Allocates the hardcoded default `RelaxedR1CSInstance` in the circuit.
-  /// W = E = 0, u = 0, X0 = X1 = 0
+  /// Allocates the hardcoded default `RelaxedR1CSInstance` with `num_signals` public signals in the
+  /// circuit. W = E = 0, u = 0, X = 0
   pub fn default<CS: ConstraintSystem<<E as Engine>::Base>>(
     mut cs: CS,
+    num_signals: usize,
   ) -> Result<Self, SynthesisError> {
     let W = AllocatedPoint::default(cs.namespace(|| "allocate W"))?;
     let E = W.clone();
@@ -131,7 +173,25 @@ impl<E: Engine> AllocatedRelaxedR1CSInstance<E> {
       BN_N_LIMBS,
     )?;
 
-    Ok(AllocatedRelaxedR1CSInstance { W, E, u, X0, X1 })
+    let X_signals = (2..2 + num_signals)
+      .map(|j| {
+        BigNat::alloc_from_nat(
+          cs.namespace(|| format!("allocate x_default[{j}]")),
+          || Ok(f_to_nat(&E::Scalar::ZERO)),
+          BN_LIMB_WIDTH,
+          BN_N_LIMBS,
+        )
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(AllocatedRelaxedR1CSInstance {
+      W,
+      E,
+      u,
+      X0,
+      X1,
+      X_signals,
+    })
   }
 
   /// Allocates the R1CS Instance as a `RelaxedR1CSInstance` in the circuit.
@@ -158,12 +218,27 @@ impl<E: Engine> AllocatedRelaxedR1CSInstance<E> {
       BN_N_LIMBS,
     )?;
 
+    let X_signals = inst
+      .X_signals
+      .into_iter()
+      .enumerate()
+      .map(|(j, x)| {
+        BigNat::from_num(
+          cs.namespace(|| format!("allocate X{} from relaxed r1cs", j + 2)),
+          &Num::from(x),
+          BN_LIMB_WIDTH,
+          BN_N_LIMBS,
+        )
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
     Ok(AllocatedRelaxedR1CSInstance {
       W: inst.comm_W,
       E,
       u,
       X0,
       X1,
+      X_signals,
     })
   }
 
@@ -213,6 +288,16 @@ impl<E: Engine> AllocatedRelaxedR1CSInstance<E> {
       ro.absorb(&limb);
     }
 
+    // absorb each of the limbs of the public signals
+    for (j, x) in self.X_signals.iter().enumerate() {
+      for (i, limb) in x.as_limbs().iter().enumerate() {
+        let limb = limb.as_allocated_num(
+          cs.namespace(|| format!("convert limb {i} of X_r[{}] to num", j + 2)),
+        )?;
+        ro.absorb(&limb);
+      }
+    }
+
     Ok(())
   }
 
@@ -304,12 +389,33 @@ impl<E: Engine> AllocatedRelaxedR1CSInstance<E> {
     // Now reduce
     let X1_fold = r_new_1.red_mod(cs.namespace(|| "reduce folded X[1]"), &m_bn)?;
 
+    // Fold the public signals in the same way
+    let X_signals_fold = self
+      .X_signals
+      .iter()
+      .zip(&u.X_signals)
+      .enumerate()
+      .map(|(j, (X_r, X))| {
+        let j = j + 2;
+        let X_bn = BigNat::from_num(
+          cs.namespace(|| format!("allocate X{j}_bn")),
+          &Num::from(X.clone()),
+          BN_LIMB_WIDTH,
+          BN_N_LIMBS,
+        )?;
+        let (_, r_j) = X_bn.mult_mod(cs.namespace(|| format!("r*X[{j}]")), &r_bn, &m_bn)?;
+        let r_new_j = X_r.add(&r_j)?;
+        r_new_j.red_mod(cs.namespace(|| format!("reduce folded X[{j}]")), &m_bn)
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
     Ok(Self {
       W: W_fold,
       E: E_fold,
       u: u_fold,
       X0: X0_fold,
       X1: X1_fold,
+      X_signals: X_signals_fold,
     })
   }
 
@@ -355,6 +461,28 @@ impl<E: Engine> AllocatedRelaxedR1CSInstance<E> {
       condition,
     )?;
 
-    Ok(AllocatedRelaxedR1CSInstance { W, E, u, X0, X1 })
+    let X_signals = self
+      .X_signals
+      .iter()
+      .zip(&other.X_signals)
+      .enumerate()
+      .map(|(j, (x, other_x))| {
+        conditionally_select_bignat(
+          cs.namespace(|| format!("X[{0}] = cond ? self.X[{0}] : other.X[{0}]", j + 2)),
+          x,
+          other_x,
+          condition,
+        )
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(AllocatedRelaxedR1CSInstance {
+      W,
+      E,
+      u,
+      X0,
+      X1,
+      X_signals,
+    })
   }
 }
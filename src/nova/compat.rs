@@ -0,0 +1,256 @@
+//! This module maps the serialized layouts of `CompressedSNARK` produced by older releases of the
+//! crate to the current one, so that their proofs can still be decoded and verified.
+use super::{nifs::NIFSRelaxed, nifs::NIFS, CompressedSNARK};
+use crate::{
+  errors::NovaError,
+  r1cs::{R1CSInstance, RelaxedR1CSInstance},
+  traits::{circuit::StepCircuit, snark::RelaxedR1CSSNARKTrait, Engine},
+};
+use bincode::Options;
+use ff::Field;
+use serde::{Deserialize, Serialize};
+
+/// A half-open range `[start, end)` of versions of the crate, each given as `[major, minor, patch]`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SemverRange {
+  start: [u64; 3],
+  end: [u64; 3],
+}
+
+impl SemverRange {
+  /// Creates the range of versions from `start` (inclusive) to `end` (exclusive)
+  pub const fn new(start: [u64; 3], end: [u64; 3]) -> Self {
+    Self { start, end }
+  }
+
+  /// Creates the range containing the single version `version`
+  pub const fn exact(version: [u64; 3]) -> Self {
+    let [major, minor, patch] = version;
+    Self::new(version, [major, minor, patch + 1])
+  }
+
+  fn is_empty(&self) -> bool {
+    self.start >= self.end
+  }
+
+  // checks if every version in `self` is also in `other`
+  fn is_within(&self, other: &Self) -> bool {
+    other.start <= self.start && self.end <= other.end
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Layout {
+  // the layout of 0.41.0, without the commitment to `z0`, the data commitment chain, and the
+  // public signals
+  WithoutBindings,
+  // the layout since 0.42.0
+  Current,
+}
+
+// the releases of the crate that share a serialized layout of `CompressedSNARK`; a release is only
+// listed once a proof it serialized is checked in under `testdata`
+const LAYOUTS: [(SemverRange, Layout); 2] = [
+  (SemverRange::exact([0, 41, 0]), Layout::WithoutBindings),
+  (SemverRange::new([0, 42, 0], [0, 43, 0]), Layout::Current),
+];
+
+// `CompressedSNARK` in the `WithoutBindings` layout
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct CompressedSNARKWithoutBindings<E1, E2, S1, S2>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<E2>,
+{
+  r_U_secondary: RelaxedR1CSInstance<E2>,
+  ri_secondary: E2::Scalar,
+  l_u_secondary: R1CSInstance<E2>,
+  nifs_Uf_secondary: NIFS<E2>,
+
+  l_ur_secondary: RelaxedR1CSInstance<E2>,
+  nifs_Un_secondary: NIFSRelaxed<E2>,
+
+  r_U_primary: RelaxedR1CSInstance<E1>,
+  ri_primary: E1::Scalar,
+  l_ur_primary: RelaxedR1CSInstance<E1>,
+  nifs_Un_primary: NIFSRelaxed<E1>,
+
+  wit_blind_r_Wn_primary: E1::Scalar,
+  err_blind_r_Wn_primary: E1::Scalar,
+  wit_blind_r_Wn_secondary: E2::Scalar,
+  err_blind_r_Wn_secondary: E2::Scalar,
+
+  snark_primary: S1,
+  snark_secondary: S2,
+
+  zn: Vec<E1::Scalar>,
+}
+
+impl<E1, E2, C, S1, S2> From<CompressedSNARKWithoutBindings<E1, E2, S1, S2>>
+  for CompressedSNARK<E1, E2, C, S1, S2>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<E2>,
+{
+  fn from(snark: CompressedSNARKWithoutBindings<E1, E2, S1, S2>) -> Self {
+    Self {
+      r_U_secondary: snark.r_U_secondary,
+      ri_secondary: snark.ri_secondary,
+      l_u_secondary: snark.l_u_secondary,
+      nifs_Uf_secondary: snark.nifs_Uf_secondary,
+
+      l_ur_secondary: snark.l_ur_secondary,
+      nifs_Un_secondary: snark.nifs_Un_secondary,
+
+      r_U_primary: snark.r_U_primary,
+      ri_primary: snark.ri_primary,
+      l_ur_primary: snark.l_ur_primary,
+      nifs_Un_primary: snark.nifs_Un_primary,
+
+      wit_blind_r_Wn_primary: snark.wit_blind_r_Wn_primary,
+      err_blind_r_Wn_primary: snark.err_blind_r_Wn_primary,
+      wit_blind_r_Wn_secondary: snark.wit_blind_r_Wn_secondary,
+      err_blind_r_Wn_secondary: snark.err_blind_r_Wn_secondary,
+
+      snark_primary: snark.snark_primary,
+      snark_secondary: snark.snark_secondary,
+
+      zn: snark.zn,
+
+      // 0.41.0 has no commitment to `z0`, and its hashes absorb a zero secondary state and no
+      // public signals, as those of a computation without a data chain and signals do
+      z0_commitment: None,
+      data_commitment_chain: E2::Scalar::ZERO,
+      public_signals: Vec::new(),
+
+      _p: Default::default(),
+    }
+  }
+}
+
+impl<E1, E2, C, S1, S2> CompressedSNARK<E1, E2, C, S1, S2>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<E2>,
+{
+  /// Decodes a `CompressedSNARK` serialized with `bincode` by a release of the crate in `from_version`.
+  ///
+  /// The layout is looked up in a table of the layouts used by past releases, and fields that did
+  /// not exist in it are filled with the values they take in a proof of that release: such a proof
+  /// carries no commitment to `z0`, so `check_z0` cannot check `z0` early, and `verify` checks it
+  /// through the hashes of the running instances only.
+  ///
+  /// The digest of public parameters set up with `PublicParams::setup` is unchanged since 0.41.0,
+  /// so a proof of that release verifies against a verifier key for the same circuit.
+  ///
+  /// Returns `NovaError::IncompatibleVersion` if `from_version` is empty or not contained in the
+  /// range of a single known layout.
+  pub fn deserialize_compat(bytes: &[u8], from_version: SemverRange) -> Result<Self, NovaError> {
+    let layout = LAYOUTS
+      .iter()
+      .find(|(range, _)| !from_version.is_empty() && from_version.is_within(range))
+      .map(|(_, layout)| *layout)
+      .ok_or_else(|| NovaError::IncompatibleVersion {
+        reason: format!("no known layout for versions {from_version:?}"),
+      })?;
+
+    let invalid = |e: bincode::Error| NovaError::InvalidCompressedSNARK {
+      reason: e.to_string(),
+    };
+    // the encoding of `bincode::serialize`, except that trailing bytes are rejected, as a proof in a
+    // later layout would otherwise decode in an earlier one
+    let config = bincode::DefaultOptions::new()
+      .with_little_endian()
+      .with_fixint_encoding()
+      .reject_trailing_bytes();
+    match layout {
+      Layout::WithoutBindings => config
+        .deserialize::<CompressedSNARKWithoutBindings<E1, E2, S1, S2>>(bytes)
+        .map(Self::from)
+        .map_err(invalid),
+      Layout::Current => config.deserialize(bytes).map_err(invalid),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    nova::{PublicParams, RecursiveSNARK},
+    provider::{ipa_pc, PallasEngine, VestaEngine},
+    spartan::snark::RelaxedR1CSSNARK,
+    traits::{circuit::NonTrivialCircuit, snark::default_ck_hint},
+  };
+
+  type E1 = PallasEngine;
+  type E2 = VestaEngine;
+  type S<E> = RelaxedR1CSSNARK<E, ipa_pc::EvaluationEngine<E>>;
+  type C = NonTrivialCircuit<<E1 as Engine>::Scalar>;
+
+  // a proof of two steps of `NonTrivialCircuit::new(4)` from `z0 = [2]`, serialized by 0.41.0
+  const PROOF_0_41_0: &[u8] = include_bytes!("testdata/compressed_snark_0.41.0.bin");
+
+  #[test]
+  fn test_deserialize_compat() {
+    let circuit = C::new(4);
+    let pp =
+      PublicParams::<E1, E2, C>::setup(&circuit, &*default_ck_hint(), &*default_ck_hint()).unwrap();
+    let z0 = [<E1 as Engine>::Scalar::from(2u64)];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _ in 0..2 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+    let (pk, vk) = CompressedSNARK::<_, _, _, S<E1>, S<E2>>::setup(&pp).unwrap();
+    let snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+    let zn = snark.verify(&vk, 2, &z0).unwrap();
+
+    // a proof in the current layout round-trips
+    let bytes = bincode::serialize(&snark).unwrap();
+    let current = SemverRange::exact([0, 42, 0]);
+    let decoded =
+      CompressedSNARK::<E1, E2, C, S<E1>, S<E2>>::deserialize_compat(&bytes, current).unwrap();
+    assert_eq!(bincode::serialize(&decoded).unwrap(), bytes);
+
+    // a proof of 0.41.0 verifies with the current verifier key, and is still bound to `z0`
+    let old = SemverRange::exact([0, 41, 0]);
+    let decoded =
+      CompressedSNARK::<E1, E2, C, S<E1>, S<E2>>::deserialize_compat(PROOF_0_41_0, old).unwrap();
+    assert_eq!(decoded.verify(&vk, 2, &z0), Ok(zn));
+    assert!(decoded
+      .verify(&vk, 2, &[<E1 as Engine>::Scalar::ZERO])
+      .is_err());
+
+    // mismatched layouts fail to decode
+    assert!(matches!(
+      CompressedSNARK::<E1, E2, C, S<E1>, S<E2>>::deserialize_compat(PROOF_0_41_0, current),
+      Err(NovaError::InvalidCompressedSNARK { .. })
+    ));
+    assert!(matches!(
+      CompressedSNARK::<E1, E2, C, S<E1>, S<E2>>::deserialize_compat(&bytes, old),
+      Err(NovaError::InvalidCompressedSNARK { .. })
+    ));
+
+    // versions spanning several layouts, unknown versions, and empty ranges are rejected
+    for range in [
+      SemverRange::new([0, 41, 0], [0, 43, 0]),
+      SemverRange::exact([0, 40, 0]),
+      SemverRange::exact([0, 41, 1]),
+      SemverRange::new([0, 42, 0], [0, 42, 0]),
+    ] {
+      assert!(matches!(
+        CompressedSNARK::<E1, E2, C, S<E1>, S<E2>>::deserialize_compat(&bytes, range),
+        Err(NovaError::IncompatibleVersion { .. })
+      ));
+    }
+  }
+}
@@ -0,0 +1,402 @@
+//! This module implements the replication of the progress of an incremental computation by
+//! sending the difference between two of its states, e.g., from a prover to its replicas.
+//!
+//! The vectors of the witnesses, which dominate the size of a `RecursiveSNARK`, are sent as the
+//! entries that differ from those of the older state when there are few enough of them, and in
+//! full otherwise. The other parts of the newer state are small, and change at every step.
+use super::{RecursiveSNARK, RecursiveSNARKBytes};
+use crate::{
+  errors::NovaError,
+  r1cs::{R1CSInstance, R1CSWitness, RelaxedR1CSInstance, RelaxedR1CSWitness},
+  traits::{circuit::StepCircuit, Engine},
+};
+use core::marker::PhantomData;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+/// The difference between two states of the same incremental computation, produced by
+/// `RecursiveSNARK::diff`, which advances a replica at the older state to the newer one with
+/// `RecursiveSNARK::apply_delta`.
+///
+/// The delta carries the parts of the newer state that differ from the older one, i.e., everything
+/// except the initial input, along with a fingerprint of the older state it applies to.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct RecursiveSNARKDelta<E1, E2, C>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+{
+  // the step and the fingerprint of the state the delta applies to
+  from_step: usize,
+  from_fingerprint: [u8; 32],
+
+  r_W_primary: RelaxedWitnessDelta<E1>,
+  r_U_primary: RelaxedR1CSInstance<E1>,
+  ri_primary: E1::Scalar,
+
+  r_W_secondary: RelaxedWitnessDelta<E2>,
+  r_U_secondary: RelaxedR1CSInstance<E2>,
+  ri_secondary: E2::Scalar,
+
+  l_w_secondary: WitnessDelta<E2>,
+  l_u_secondary: R1CSInstance<E2>,
+
+  i: usize,
+
+  zi: Vec<E1::Scalar>,
+  public_signals: Vec<E1::Scalar>,
+  data_commitment_chain: E2::Scalar,
+
+  _p: PhantomData<C>,
+}
+
+impl<E1, E2, C> RecursiveSNARKDelta<E1, E2, C>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+{
+  /// Returns the number of steps of the state the delta applies to
+  pub fn from_step(&self) -> usize {
+    self.from_step
+  }
+
+  /// Returns the number of steps of the state the delta advances to
+  pub fn to_step(&self) -> usize {
+    self.i
+  }
+}
+
+// the entries of a vector that differ from those of an older vector of the same length, or the
+// whole vector if that is smaller
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+enum VecDelta<E: Engine> {
+  Dense(Vec<E::Scalar>),
+  Sparse(Vec<(u64, E::Scalar)>),
+}
+
+impl<E: Engine> VecDelta<E> {
+  fn new(older: &[E::Scalar], newer: &[E::Scalar]) -> Self {
+    if older.len() == newer.len() {
+      let entries = older
+        .iter()
+        .zip(newer)
+        .enumerate()
+        .filter(|(_, (o, n))| o != n)
+        .map(|(i, (_, n))| (i as u64, *n))
+        .collect::<Vec<_>>();
+      // an entry holds an 8-byte index along with a 32-byte scalar
+      if entries.len() * 5 < newer.len() * 4 {
+        return Self::Sparse(entries);
+      }
+    }
+    Self::Dense(newer.to_vec())
+  }
+
+  // whether the delta can be applied to `v`, i.e., its entries are within `v`
+  fn fits(&self, v: &[E::Scalar]) -> bool {
+    match self {
+      Self::Dense(_) => true,
+      Self::Sparse(entries) => entries.iter().all(|(i, _)| *i < v.len() as u64),
+    }
+  }
+
+  // the caller checks that `v` is the older vector the delta was computed against
+  fn apply(self, v: &mut Vec<E::Scalar>) {
+    match self {
+      Self::Dense(newer) => *v = newer,
+      Self::Sparse(entries) => {
+        for (i, s) in entries {
+          v[i as usize] = s;
+        }
+      }
+    }
+  }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+struct RelaxedWitnessDelta<E: Engine> {
+  W: VecDelta<E>,
+  r_W: E::Scalar,
+  E: VecDelta<E>,
+  r_E: E::Scalar,
+}
+
+impl<E: Engine> RelaxedWitnessDelta<E> {
+  fn new(older: &RelaxedR1CSWitness<E>, newer: &RelaxedR1CSWitness<E>) -> Self {
+    Self {
+      W: VecDelta::new(&older.W, &newer.W),
+      r_W: newer.r_W,
+      E: VecDelta::new(&older.E, &newer.E),
+      r_E: newer.r_E,
+    }
+  }
+
+  fn fits(&self, w: &RelaxedR1CSWitness<E>) -> bool {
+    self.W.fits(&w.W) && self.E.fits(&w.E)
+  }
+
+  fn apply(self, w: &mut RelaxedR1CSWitness<E>) {
+    self.W.apply(&mut w.W);
+    w.r_W = self.r_W;
+    self.E.apply(&mut w.E);
+    w.r_E = self.r_E;
+  }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+struct WitnessDelta<E: Engine> {
+  W: VecDelta<E>,
+  r_W: E::Scalar,
+}
+
+impl<E: Engine> WitnessDelta<E> {
+  fn new(older: &R1CSWitness<E>, newer: &R1CSWitness<E>) -> Self {
+    Self {
+      W: VecDelta::new(&older.W, &newer.W),
+      r_W: newer.r_W,
+    }
+  }
+
+  fn fits(&self, w: &R1CSWitness<E>) -> bool {
+    self.W.fits(&w.W)
+  }
+
+  fn apply(self, w: &mut R1CSWitness<E>) {
+    self.W.apply(&mut w.W);
+    w.r_W = self.r_W;
+  }
+}
+
+// a hash of the canonical encoding of the whole state, which identifies it exactly
+fn fingerprint<E1, E2, C>(recursive_snark: &RecursiveSNARK<E1, E2, C>) -> [u8; 32]
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+{
+  Sha3_256::digest(RecursiveSNARKBytes::from(recursive_snark).as_bytes()).into()
+}
+
+impl<E1, E2, C> RecursiveSNARK<E1, E2, C>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+{
+  /// Returns the delta that advances a replica of `older`, an earlier state of the same
+  /// incremental computation, to `self` with `apply_delta`.
+  ///
+  /// Returns `NovaError::InvalidRecursiveSNARKDelta` if `older` cannot be a prefix of `self`,
+  /// i.e., if it has a different initial input or more steps, and
+  /// `NovaError::PendingPrimarySteps` if either has steps queued by `queue_primary_steps`.
+  pub fn diff(&self, older: &Self) -> Result<RecursiveSNARKDelta<E1, E2, C>, NovaError> {
+    let invalid = |reason: String| NovaError::InvalidRecursiveSNARKDelta { reason };
+
+    if !self.pending_steps.is_empty() || !older.pending_steps.is_empty() {
+      return Err(NovaError::PendingPrimarySteps);
+    }
+    if older.z0 != self.z0 {
+      return Err(invalid(
+        "older state has a different initial input".to_string(),
+      ));
+    }
+    if older.i > self.i {
+      return Err(invalid(format!(
+        "older state is at step {}, ahead of step {}",
+        older.i, self.i
+      )));
+    }
+
+    Ok(RecursiveSNARKDelta {
+      from_step: older.i,
+      from_fingerprint: fingerprint(older),
+
+      r_W_primary: RelaxedWitnessDelta::new(&older.r_W_primary, &self.r_W_primary),
+      r_U_primary: self.r_U_primary.clone(),
+      ri_primary: self.ri_primary,
+
+      r_W_secondary: RelaxedWitnessDelta::new(&older.r_W_secondary, &self.r_W_secondary),
+      r_U_secondary: self.r_U_secondary.clone(),
+      ri_secondary: self.ri_secondary,
+
+      l_w_secondary: WitnessDelta::new(&older.l_w_secondary, &self.l_w_secondary),
+      l_u_secondary: self.l_u_secondary.clone(),
+
+      i: self.i,
+
+      zi: self.zi.clone(),
+      public_signals: self.public_signals.clone(),
+      data_commitment_chain: self.data_commitment_chain,
+
+      _p: Default::default(),
+    })
+  }
+
+  /// Advances `self` to the state `delta` was computed for by `diff`.
+  ///
+  /// Returns `NovaError::InvalidRecursiveSNARKDelta` if `self` is not exactly the older state
+  /// the delta was computed against, and `NovaError::PendingPrimarySteps` if it has steps queued
+  /// by `queue_primary_steps`, in which case `self` is left unchanged.
+  pub fn apply_delta(&mut self, delta: RecursiveSNARKDelta<E1, E2, C>) -> Result<(), NovaError> {
+    let invalid = |reason: String| NovaError::InvalidRecursiveSNARKDelta { reason };
+
+    if !self.pending_steps.is_empty() {
+      return Err(NovaError::PendingPrimarySteps);
+    }
+    if self.i != delta.from_step {
+      return Err(invalid(format!(
+        "delta applies to step {}, but the replica is at step {}",
+        delta.from_step, self.i
+      )));
+    }
+    if fingerprint(self) != delta.from_fingerprint {
+      return Err(invalid(
+        "replica differs from the state the delta was computed against".to_string(),
+      ));
+    }
+    if !(delta.r_W_primary.fits(&self.r_W_primary)
+      && delta.r_W_secondary.fits(&self.r_W_secondary)
+      && delta.l_w_secondary.fits(&self.l_w_secondary))
+    {
+      return Err(invalid("delta has entries out of range".to_string()));
+    }
+
+    delta.r_W_primary.apply(&mut self.r_W_primary);
+    self.r_U_primary = delta.r_U_primary;
+    self.ri_primary = delta.ri_primary;
+
+    delta.r_W_secondary.apply(&mut self.r_W_secondary);
+    self.r_U_secondary = delta.r_U_secondary;
+    self.ri_secondary = delta.ri_secondary;
+
+    delta.l_w_secondary.apply(&mut self.l_w_secondary);
+    self.l_u_secondary = delta.l_u_secondary;
+
+    self.i = delta.i;
+
+    self.zi = delta.zi;
+    self.public_signals = delta.public_signals;
+    self.data_commitment_chain = delta.data_commitment_chain;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    gadgets::dsl::{add, constant, input, mul, TransitionCircuit},
+    nova::PublicParams,
+    provider::{PallasEngine, VestaEngine},
+    traits::snark::default_ck_hint,
+  };
+  use ff::Field;
+
+  #[test]
+  fn test_vec_delta() {
+    type F = <PallasEngine as Engine>::Scalar;
+
+    let older = (0..10u64).map(F::from).collect::<Vec<_>>();
+
+    // a few changed entries are sent alone, and many as the whole vector
+    let mut newer = older.clone();
+    newer[3] = F::from(100u64);
+    let delta = VecDelta::<PallasEngine>::new(&older, &newer);
+    assert_eq!(delta, VecDelta::Sparse(vec![(3, F::from(100u64))]));
+    let mut v = older.clone();
+    delta.apply(&mut v);
+    assert_eq!(v, newer);
+
+    let newer = older.iter().map(|s| s.double()).collect::<Vec<_>>();
+    let delta = VecDelta::<PallasEngine>::new(&older, &newer);
+    assert_eq!(delta, VecDelta::Dense(newer.clone()));
+    let mut v = older.clone();
+    delta.apply(&mut v);
+    assert_eq!(v, newer);
+
+    // as is a vector of a different length
+    let delta = VecDelta::<PallasEngine>::new(&older[..5], &older);
+    assert_eq!(delta, VecDelta::Dense(older.clone()));
+  }
+
+  #[test]
+  fn test_apply_delta() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    // z -> z^2 + 1
+    let circuit =
+      TransitionCircuit::new(vec![add(mul(input(0), input(0)), constant(F::ONE))]).unwrap();
+    let pp = PublicParams::<E1, E2, TransitionCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let z0 = [F::from(2u64)];
+
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _ in 0..2 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+    let mut replica = recursive_snark.clone();
+    let mut stale = recursive_snark.clone();
+    stale.prove_step(&pp, &circuit).unwrap();
+    let older = recursive_snark.clone();
+    for _ in 0..3 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+
+    // applying the delta to the stale replica reproduces the current state exactly
+    let delta = recursive_snark.diff(&older).unwrap();
+    assert_eq!((delta.from_step(), delta.to_step()), (2, 5));
+    replica.apply_delta(delta.clone()).unwrap();
+    assert_eq!(
+      RecursiveSNARKBytes::from(&replica),
+      RecursiveSNARKBytes::from(&recursive_snark)
+    );
+    assert_eq!(replica.outputs(), recursive_snark.outputs());
+    assert!(replica.verify(&pp, 5, &z0).is_ok());
+
+    // the replica can extend the computation
+    replica.prove_step(&pp, &circuit).unwrap();
+    assert!(replica.verify(&pp, 6, &z0).is_ok());
+
+    // a delta with entries out of range is rejected, leaving the replica unchanged
+    let mut bad = delta.clone();
+    bad.l_w_secondary.W = VecDelta::Sparse(vec![(u64::MAX, <E2 as Engine>::Scalar::ZERO)]);
+    let mut replica = older.clone();
+    assert!(matches!(
+      replica.apply_delta(bad),
+      Err(NovaError::InvalidRecursiveSNARKDelta { .. })
+    ));
+    assert_eq!(
+      RecursiveSNARKBytes::from(&replica),
+      RecursiveSNARKBytes::from(&older)
+    );
+
+    // a delta only applies to the state it was computed against
+    assert!(matches!(
+      stale.apply_delta(delta.clone()),
+      Err(NovaError::InvalidRecursiveSNARKDelta { .. })
+    ));
+    let mut tampered = older.clone();
+    tampered.zi[0] += F::ONE;
+    assert!(matches!(
+      tampered.apply_delta(delta),
+      Err(NovaError::InvalidRecursiveSNARKDelta { .. })
+    ));
+
+    // and the older state must be a prefix of the newer one
+    assert!(older.diff(&recursive_snark).is_err());
+    let other = RecursiveSNARK::new(&pp, &circuit, &[F::ONE]).unwrap();
+    assert!(recursive_snark.diff(&other).is_err());
+  }
+}
@@ -0,0 +1,201 @@
+//! This module encodes the inputs and outputs of a `CompressedSNARK` as bytes in the formats that
+//! native verifiers on other chains expect, e.g., for bridges that check Nova proofs.
+use super::{CompressedSNARK, VerifierKey};
+use crate::{
+  errors::NovaError,
+  traits::{circuit::StepCircuit, snark::RelaxedR1CSSNARKTrait, Engine},
+};
+use ff::PrimeField;
+
+/// The byte encoding of a scalar
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScalarEncoding {
+  /// The canonical value of the scalar, most significant byte first
+  BigEndian,
+  /// The canonical value of the scalar, least significant byte first
+  LittleEndian,
+  /// The Montgomery form `a * R mod p` of the scalar `a`, least significant byte first, where
+  /// `R = 2^(64 * n)` for the number `n` of 64-bit limbs of the scalar
+  Montgomery,
+}
+
+impl ScalarEncoding {
+  /// Encodes the scalar `s`
+  pub fn encode<F: PrimeField>(&self, s: &F) -> Vec<u8> {
+    match self {
+      ScalarEncoding::BigEndian => {
+        let mut bytes = le_bytes(s);
+        bytes.reverse();
+        bytes
+      }
+      ScalarEncoding::LittleEndian => le_bytes(s),
+      ScalarEncoding::Montgomery => le_bytes(&(*s * montgomery_r::<F>())),
+    }
+  }
+
+  /// Decodes a scalar from `bytes`, or returns `NovaError::InvalidScalarEncoding` if they do not
+  /// encode a scalar
+  pub fn decode<F: PrimeField>(&self, bytes: &[u8]) -> Result<F, NovaError> {
+    let invalid = |reason: &str| NovaError::InvalidScalarEncoding {
+      reason: reason.to_string(),
+    };
+
+    let mut repr = F::Repr::default();
+    if bytes.len() != repr.as_ref().len() {
+      return Err(invalid("unexpected number of bytes"));
+    }
+    repr.as_mut().copy_from_slice(bytes);
+    if *self == ScalarEncoding::BigEndian {
+      repr.as_mut().reverse();
+    }
+    let s = Option::<F>::from(F::from_repr(repr)).ok_or_else(|| invalid("non-canonical scalar"))?;
+
+    match self {
+      ScalarEncoding::Montgomery => Ok(s * montgomery_r::<F>().invert().unwrap()),
+      _ => Ok(s),
+    }
+  }
+
+  /// Encodes each of the scalars `s`
+  pub fn encode_all<F: PrimeField>(&self, s: &[F]) -> Vec<Vec<u8>> {
+    s.iter().map(|s| self.encode(s)).collect()
+  }
+
+  /// Decodes a scalar from each of `bytes`
+  pub fn decode_all<F: PrimeField>(&self, bytes: &[Vec<u8>]) -> Result<Vec<F>, NovaError> {
+    bytes.iter().map(|b| self.decode(b)).collect()
+  }
+}
+
+// the canonical representation of the scalars of the supported curves is little-endian
+fn le_bytes<F: PrimeField>(s: &F) -> Vec<u8> {
+  s.to_repr().as_ref().to_vec()
+}
+
+// returns the Montgomery constant `R = 2^(64 * n)` for the number `n` of 64-bit limbs of `F`
+fn montgomery_r<F: PrimeField>() -> F {
+  let num_limbs = (F::NUM_BITS as u64).div_ceil(64);
+  F::from(2u64).pow_vartime([64 * num_limbs])
+}
+
+impl<E1, E2, C, S1, S2> CompressedSNARK<E1, E2, C, S1, S2>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<E2>,
+{
+  /// Verifies the `CompressedSNARK` as in `verify`, with the initial input `z0` and the returned
+  /// outputs encoded with `encoding`
+  pub fn verify_with_encoding(
+    &self,
+    vk: &VerifierKey<E1, E2, C, S1, S2>,
+    num_steps: usize,
+    z0: &[Vec<u8>],
+    encoding: ScalarEncoding,
+  ) -> Result<Vec<Vec<u8>>, NovaError> {
+    let z0 = encoding.decode_all(z0)?;
+    let zn = self.verify(vk, num_steps, &z0)?;
+    Ok(encoding.encode_all(&zn))
+  }
+
+  /// Returns the outputs that the proof attests to encoded with `encoding`, which are only
+  /// meaningful once the proof is verified
+  pub fn zn_encoded(&self, encoding: ScalarEncoding) -> Vec<Vec<u8>> {
+    encoding.encode_all(&self.zn)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    gadgets::dsl::{add, constant, input, mul, TransitionCircuit},
+    nova::{PublicParams, RecursiveSNARK},
+    provider::{bn256_grumpkin::bn256, pasta::pallas, PallasEngine, VestaEngine},
+    traits::snark::default_ck_hint,
+  };
+  use ff::Field;
+
+  type EE<E> = crate::provider::ipa_pc::EvaluationEngine<E>;
+  type S<E> = crate::spartan::snark::RelaxedR1CSSNARK<E, EE<E>>;
+
+  fn test_round_trip_with<F: PrimeField>() {
+    let encodings = [
+      ScalarEncoding::BigEndian,
+      ScalarEncoding::LittleEndian,
+      ScalarEncoding::Montgomery,
+    ];
+    let s = F::from(0x0102u64);
+    for encoding in encodings {
+      assert_eq!(encoding.decode::<F>(&encoding.encode(&s)), Ok(s));
+      assert_eq!(encoding.decode::<F>(&encoding.encode(&-s)), Ok(-s));
+    }
+
+    // the byte orders are as specified
+    let le = ScalarEncoding::LittleEndian.encode(&s);
+    assert_eq!(le[..3], [0x02, 0x01, 0x00]);
+    let be = ScalarEncoding::BigEndian.encode(&s);
+    assert_eq!(be[be.len() - 3..], [0x00, 0x01, 0x02]);
+    assert_eq!(
+      ScalarEncoding::Montgomery.encode(&F::ONE),
+      ScalarEncoding::LittleEndian.encode(&montgomery_r::<F>())
+    );
+
+    // a wrong number of bytes or a non-canonical scalar is rejected
+    assert!(ScalarEncoding::LittleEndian.decode::<F>(&le[1..]).is_err());
+    assert!(ScalarEncoding::LittleEndian
+      .decode::<F>(&vec![0xff; le.len()])
+      .is_err());
+  }
+
+  #[test]
+  fn test_round_trip() {
+    test_round_trip_with::<pallas::Scalar>();
+    test_round_trip_with::<bn256::Scalar>();
+  }
+
+  #[test]
+  fn test_verify_with_encoding() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    // z -> z^2 + 1
+    let circuit =
+      TransitionCircuit::new(vec![add(mul(input(0), input(0)), constant(F::ONE))]).unwrap();
+    let pp = PublicParams::<E1, E2, TransitionCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let z0 = [F::from(3u64)];
+    let recursive_snark = RecursiveSNARK::from_transition(&pp, &circuit, &z0, 3).unwrap();
+    let (pk, vk) = CompressedSNARK::<_, _, _, S<E1>, S<E2>>::setup(&pp).unwrap();
+    let compressed_snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+
+    // verification succeeds with any encoding the caller uses consistently
+    for encoding in [
+      ScalarEncoding::BigEndian,
+      ScalarEncoding::LittleEndian,
+      ScalarEncoding::Montgomery,
+    ] {
+      let zn = compressed_snark
+        .verify_with_encoding(&vk, 3, &encoding.encode_all(&z0), encoding)
+        .unwrap();
+      assert_eq!(zn, compressed_snark.zn_encoded(encoding));
+      assert_eq!(
+        encoding.decode_all::<F>(&zn).unwrap(),
+        recursive_snark.outputs()
+      );
+    }
+
+    // but not if the input is encoded differently
+    let z0_le = ScalarEncoding::LittleEndian.encode_all(&z0);
+    assert!(compressed_snark
+      .verify_with_encoding(&vk, 3, &z0_le, ScalarEncoding::BigEndian)
+      .is_err());
+  }
+}
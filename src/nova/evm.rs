@@ -0,0 +1,211 @@
+//! This module provides a flat, EVM-friendly export of Nova's `VerifierKey` over the BN256/Grumpkin cycle.
+//!
+//! Every element is encoded as a 32-byte big-endian word (a Solidity `uint256`), so an on-chain
+//! verifier can consume the components directly as calldata.
+use super::VerifierKey;
+use crate::{
+  provider::{
+    bn256_grumpkin::{bn256, grumpkin},
+    poseidon::{PoseidonConstantsCircuit, PoseidonRO},
+  },
+  traits::{
+    circuit::StepCircuit,
+    commitment::{CommitmentEngineTrait, CommitmentTrait},
+    snark::{DigestHelperTrait, RelaxedR1CSSNARKTrait},
+    Engine,
+  },
+  Commitment, DerandKey,
+};
+use ff::{Field, PrimeField};
+use serde::{Deserialize, Serialize};
+
+/// A `VerifierKey` flattened into 32-byte big-endian words for an on-chain verifier.
+///
+/// The components are laid out in the following order, which is stable across releases:
+/// 1. `f_arity`: the arity of the step circuit
+/// 2. `pp_digest`: the digest of the public parameters
+/// 3. `vk_digests`: the digests of the primary and the secondary SNARK verifier keys, in that order
+/// 4. `dk`: the blinding generators `(x, y)` of the primary and the secondary commitment keys, in that order
+/// 5. `ro_params_primary`, `ro_round_constants_primary`, `ro_mds_primary`: the Poseidon parameters
+///    `(width, full_rounds, partial_rounds)`, round constants, and row-major MDS matrix used to hash
+///    the secondary running instance (over the BN256 base field)
+/// 6. `ro_params_secondary`, `ro_round_constants_secondary`, `ro_mds_secondary`: the same for the
+///    hash of the primary running instance (over the BN256 scalar field)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvmVerifierKey {
+  /// The arity of the step circuit
+  pub f_arity: [u8; 32],
+  /// The digest of the public parameters
+  pub pp_digest: [u8; 32],
+  /// The digests of the primary and the secondary SNARK verifier keys
+  pub vk_digests: Vec<[u8; 32]>,
+  /// The blinding generators of the primary and the secondary commitment keys
+  pub dk: Vec<[u8; 32]>,
+  /// The width, number of full rounds, and number of partial rounds of the primary RO
+  pub ro_params_primary: Vec<[u8; 32]>,
+  /// The round constants of the primary RO
+  pub ro_round_constants_primary: Vec<[u8; 32]>,
+  /// The MDS matrix of the primary RO in row-major order
+  pub ro_mds_primary: Vec<[u8; 32]>,
+  /// The width, number of full rounds, and number of partial rounds of the secondary RO
+  pub ro_params_secondary: Vec<[u8; 32]>,
+  /// The round constants of the secondary RO
+  pub ro_round_constants_secondary: Vec<[u8; 32]>,
+  /// The MDS matrix of the secondary RO in row-major order
+  pub ro_mds_secondary: Vec<[u8; 32]>,
+}
+
+impl EvmVerifierKey {
+  /// Returns all the words of the key concatenated in the documented order
+  pub fn to_words(&self) -> Vec<[u8; 32]> {
+    [
+      vec![self.f_arity, self.pp_digest],
+      self.vk_digests.clone(),
+      self.dk.clone(),
+      self.ro_params_primary.clone(),
+      self.ro_round_constants_primary.clone(),
+      self.ro_mds_primary.clone(),
+      self.ro_params_secondary.clone(),
+      self.ro_round_constants_secondary.clone(),
+      self.ro_mds_secondary.clone(),
+    ]
+    .concat()
+  }
+}
+
+/// Encodes a field element as a 32-byte big-endian word
+fn to_word<F: PrimeField>(f: &F) -> [u8; 32] {
+  let repr = f.to_repr();
+  let mut word = [0u8; 32];
+  // field representations in halo2curves are little-endian
+  for (w, b) in word.iter_mut().rev().zip(repr.as_ref()) {
+    *w = *b;
+  }
+  word
+}
+
+/// Encodes a `usize` as a 32-byte big-endian word
+fn usize_to_word(n: usize) -> [u8; 32] {
+  let mut word = [0u8; 32];
+  word[24..].copy_from_slice(&(n as u64).to_be_bytes());
+  word
+}
+
+/// Returns the coordinates of the blinding generator held in a derandomization key
+fn dk_to_words<E: Engine>(dk: &DerandKey<E>) -> Vec<[u8; 32]> {
+  // derandomizing the identity with a blind of -1 yields the blinding generator itself
+  let h = E::CE::derandomize(dk, &Commitment::<E>::default(), &-E::Scalar::ONE);
+  let (x, y, _is_infinity) = h.to_coordinates();
+  vec![to_word(&x), to_word(&y)]
+}
+
+/// Returns the parameters, round constants, and MDS matrix of a Poseidon RO
+fn ro_to_words<F: PrimeField>(
+  consts: &PoseidonConstantsCircuit<F>,
+) -> (Vec<[u8; 32]>, Vec<[u8; 32]>, Vec<[u8; 32]>) {
+  let (width, full_rounds, partial_rounds) = consts.rounds();
+  (
+    vec![
+      usize_to_word(width),
+      usize_to_word(full_rounds),
+      usize_to_word(partial_rounds),
+    ],
+    consts.round_constants().iter().map(to_word).collect(),
+    consts.mds_matrix().iter().flatten().map(to_word).collect(),
+  )
+}
+
+impl<E1, E2, C, S1, S2> VerifierKey<E1, E2, C, S1, S2>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar, Scalar = bn256::Scalar, RO = PoseidonRO<bn256::Base>>,
+  E2: Engine<Base = <E1 as Engine>::Scalar, Scalar = bn256::Base, RO = PoseidonRO<grumpkin::Base>>,
+  C: StepCircuit<E1::Scalar>,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<E2>,
+{
+  /// Flattens the verifier key into 32-byte big-endian words for an EVM verifier.
+  /// See `EvmVerifierKey` for the layout of the result.
+  pub fn to_evm_calldata(&self) -> EvmVerifierKey {
+    let (ro_params_primary, ro_round_constants_primary, ro_mds_primary) =
+      ro_to_words(&self.ro_consts_primary);
+    let (ro_params_secondary, ro_round_constants_secondary, ro_mds_secondary) =
+      ro_to_words(&self.ro_consts_secondary);
+
+    EvmVerifierKey {
+      f_arity: usize_to_word(self.F_arity),
+      pp_digest: to_word(&self.pp_digest),
+      vk_digests: vec![
+        to_word(&self.vk_primary.digest()),
+        to_word(&self.vk_secondary.digest()),
+      ],
+      dk: [
+        dk_to_words::<E1>(&self.dk_primary),
+        dk_to_words::<E2>(&self.dk_secondary),
+      ]
+      .concat(),
+      ro_params_primary,
+      ro_round_constants_primary,
+      ro_mds_primary,
+      ro_params_secondary,
+      ro_round_constants_secondary,
+      ro_mds_secondary,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    nova::{CompressedSNARK, PublicParams},
+    provider::{Bn256EngineIPA, GrumpkinEngine},
+    traits::{circuit::TrivialCircuit, snark::default_ck_hint},
+  };
+  use expect_test::expect;
+  use sha3::{Digest, Sha3_256};
+
+  // use the IPA-based engine so that the commitment keys, and hence the key below, are deterministic
+  type E1 = Bn256EngineIPA;
+  type E2 = GrumpkinEngine;
+  type EE1 = crate::provider::ipa_pc::EvaluationEngine<E1>;
+  type EE2 = crate::provider::ipa_pc::EvaluationEngine<E2>;
+  type S1 = crate::spartan::snark::RelaxedR1CSSNARK<E1, EE1>;
+  type S2 = crate::spartan::snark::RelaxedR1CSSNARK<E2, EE2>;
+
+  #[test]
+  fn test_to_evm_calldata() {
+    let circuit = TrivialCircuit::<<E1 as Engine>::Scalar>::default();
+    let pp = PublicParams::<E1, E2, TrivialCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let (_pk, vk) = CompressedSNARK::<_, _, _, S1, S2>::setup(&pp).unwrap();
+
+    let evm_vk = vk.to_evm_calldata();
+
+    // the scalar-valued components are big-endian
+    assert_eq!(evm_vk.f_arity, usize_to_word(1));
+    assert_eq!(evm_vk.pp_digest, to_word(&pp.digest()));
+    assert_eq!(evm_vk.pp_digest[31], pp.digest().to_repr()[0]);
+
+    // the Poseidon constants have the expected sizes
+    let (width, full_rounds, partial_rounds) = vk.ro_consts_primary.rounds();
+    assert_eq!(evm_vk.ro_params_primary[0], usize_to_word(width));
+    assert_eq!(evm_vk.ro_mds_primary.len(), width * width);
+    assert!(evm_vk.ro_round_constants_primary.len() >= width * (full_rounds + partial_rounds));
+    assert_eq!(evm_vk.vk_digests.len(), 2);
+    assert_eq!(evm_vk.dk.len(), 4);
+
+    // lock down the full layout
+    let words = evm_vk.to_words();
+    assert_eq!(words.len(), 4614);
+    expect!["0000000000000000000000000000000000000000000000000000000000000019"]
+      .assert_eq(&hex::encode(evm_vk.ro_params_primary[0]));
+    expect!["29045c2e9ed7800fdb8129a40f32ec443fb901b24582b1a9b8bff34ad3522570"]
+      .assert_eq(&hex::encode(evm_vk.dk[0]));
+    expect!["35fc1cdf80242d1fa946b3c8c5b8bf6ec097f65aed7557d9d9e812048e28be63"]
+      .assert_eq(&hex::encode(Sha3_256::digest(words.concat())));
+  }
+}
@@ -2,38 +2,69 @@
 
 use crate::{
   constants::NUM_HASH_BITS,
-  digest::{DigestComputer, SimpleDigestible},
-  errors::NovaError,
+  digest::{DigestComputer, Digestible, SimpleDigestible},
+  errors::{NovaError, StateError},
   frontend::{
+    num::AllocatedNum,
     r1cs::{NovaShape, NovaWitness},
     shape_cs::ShapeCS,
     solver::SatisfyingAssignment,
-    ConstraintSystem, SynthesisError,
+    ConstraintSystem, SynthesisError, SHAPE_FORMAT_VERSION,
+  },
+  gadgets::{
+    dsl::TransitionCircuit,
+    merkle::MerkleProof,
+    utils::{base_as_scalar, scalar_as_base},
   },
-  gadgets::utils::{base_as_scalar, scalar_as_base},
   r1cs::{
     CommitmentKeyHint, R1CSInstance, R1CSShape, R1CSWitness, RelaxedR1CSInstance,
     RelaxedR1CSWitness,
   },
   traits::{
-    circuit::{StepCircuit, TrivialCircuit},
-    commitment::CommitmentEngineTrait,
+    circuit::{IvcState, StepCircuit},
+    commitment::{CommitmentEngineTrait, CommitmentTrait, Len},
     snark::RelaxedR1CSSNARKTrait,
-    AbsorbInROTrait, Engine, ROConstants, ROConstantsCircuit, ROTrait,
+    AbsorbInROTrait, Engine, ROCircuitTrait, ROConstants, ROConstantsCircuit, ROTrait,
   },
-  CommitmentKey, DerandKey,
+  Commitment, CommitmentKey, DerandKey,
 };
-use core::marker::PhantomData;
-use ff::Field;
+use bincode::Options;
+use core::{cmp::max, marker::PhantomData};
+use ff::{Field, PrimeField};
 use once_cell::sync::OnceCell;
-use rand_core::OsRng;
-use serde::{Deserialize, Serialize};
-
+use rand_core::{OsRng, RngCore, SeedableRng};
+use rayon::prelude::*;
+use serde::{ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use sha3::{Digest, Sha3_256};
+use std::io;
+use subtle::ConstantTimeEq;
+
+#[cfg(feature = "bench-utils")]
+mod bench;
+mod chained;
 mod circuit;
+mod compat;
+mod delta;
+mod encoding;
+mod evm;
 pub(crate) mod nifs;
-
-use circuit::{NovaAugmentedCircuit, NovaAugmentedCircuitInputs};
-use nifs::{NIFSRelaxed, NIFS};
+mod step_params;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
+mod trace;
+
+#[cfg(feature = "bench-utils")]
+pub use bench::ThroughputReport;
+pub use compat::SemverRange;
+pub use delta::RecursiveSNARKDelta;
+pub use encoding::ScalarEncoding;
+pub use evm::EvmVerifierKey;
+pub use nifs::{NIFSMulti, NIFS};
+pub use step_params::StepParamsCircuit;
+pub use trace::{TraceOpening, TracedCircuit, TracedRecursiveSNARK};
+
+use circuit::{DataChainCircuit, NovaAugmentedCircuit, NovaAugmentedCircuitInputs};
+use nifs::NIFSRelaxed;
 
 /// A type that holds public parameters of Nova
 #[derive(Serialize, Deserialize)]
@@ -58,19 +89,212 @@ where
   ck_secondary: CommitmentKey<E2>,
   r1cs_shape_secondary: R1CSShape<E2>,
 
+  // a commitment to the seed the commitment keys were derived from, if any
+  #[serde(default)]
+  ck_seed_commitment: Option<[u8; 32]>,
+
+  // the options the parameters were set up with
+  #[serde(default)]
+  options: SetupOptions<E1>,
+
+  // the version of the semantics of synthesis that produced the shapes
+  #[serde(default = "first_shape_format_version")]
+  shape_format_version: u32,
+
   #[serde(skip, default = "OnceCell::new")]
   digest: OnceCell<E1::Scalar>,
+  // a fingerprint of the digest and of the parts of the parameters that are cheap to hash,
+  // recorded along with the digest, see `check_digest_fingerprint`
+  #[serde(skip, default = "OnceCell::new")]
+  digest_fingerprint: OnceCell<[u8; 32]>,
   _p: PhantomData<C>,
 }
 
-impl<E1, E2, C> SimpleDigestible for PublicParams<E1, E2, C>
+// parameters serialized before the shape format version was recorded were synthesized with its
+// first version
+const fn first_shape_format_version() -> u32 {
+  1
+}
+
+/// Options for `PublicParams::setup_with_options`, which select the variant of the augmented
+/// circuits and bind an application-defined context. The options are kept in the public
+/// parameters, and those that differ from their defaults are bound into the digest.
+///
+/// The default options are those used by `PublicParams::setup`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct SetupOptions<E: Engine> {
+  trivial_secondary: bool,
+  data_chain: bool,
+  steps_per_fold: usize,
+  output_hash: bool,
+  context: Vec<E::Scalar>,
+}
+
+impl<E: Engine> Default for SetupOptions<E> {
+  fn default() -> Self {
+    Self {
+      trivial_secondary: false,
+      data_chain: false,
+      steps_per_fold: 1,
+      output_hash: false,
+      context: Vec::new(),
+    }
+  }
+}
+
+impl<E: Engine> SetupOptions<E> {
+  /// Specializes the secondary circuit for its trivial step circuit, as in
+  /// `PublicParams::setup_optimized_trivial_secondary`
+  pub fn with_trivial_secondary(mut self, trivial_secondary: bool) -> Self {
+    self.trivial_secondary = trivial_secondary;
+    self
+  }
+
+  /// Makes the secondary circuit maintain a chain of data commitments, as in
+  /// `PublicParams::setup_with_data_chain`
+  pub fn with_data_chain(mut self, data_chain: bool) -> Self {
+    self.data_chain = data_chain;
+    self
+  }
+
+  /// Executes up to `steps_per_fold` steps of the step circuit per fold, as in
+  /// `PublicParams::setup_with_steps_per_fold`
+  pub fn with_steps_per_fold(mut self, steps_per_fold: usize) -> Self {
+    self.steps_per_fold = steps_per_fold;
+    self
+  }
+
+  /// Makes the augmented circuits absorb a hash of their outputs instead of the outputs, so that a
+  /// `CompressedSNARK` can be verified against the hash alone, see
+  /// `CompressedSNARK::verify_against_output_hash`
+  pub fn with_output_hash(mut self, output_hash: bool) -> Self {
+    self.output_hash = output_hash;
+    self
+  }
+
+  /// Binds the application-defined `context` into the digest, as in
+  /// `PublicParams::setup_with_context`
+  pub fn with_context(mut self, context: &[E::Scalar]) -> Self {
+    self.context = context.to_vec();
+    self
+  }
+
+  /// Returns whether the secondary circuit is specialized for its trivial step circuit
+  pub const fn trivial_secondary(&self) -> bool {
+    self.trivial_secondary
+  }
+
+  /// Returns whether the secondary circuit maintains a chain of data commitments
+  pub const fn data_chain(&self) -> bool {
+    self.data_chain
+  }
+
+  /// Returns the maximum number of steps of the step circuit executed per fold
+  pub const fn steps_per_fold(&self) -> usize {
+    self.steps_per_fold
+  }
+
+  /// Returns whether the augmented circuits absorb a hash of their outputs
+  pub const fn output_hash(&self) -> bool {
+    self.output_hash
+  }
+
+  /// Returns the application-defined context bound into the digest
+  pub fn context(&self) -> &[E::Scalar] {
+    &self.context
+  }
+}
+
+impl<E1, E2, C> Digestible for PublicParams<E1, E2, C>
 where
   E1: Engine<Base = <E2 as Engine>::Scalar>,
   E2: Engine<Base = <E1 as Engine>::Scalar>,
   C: StepCircuit<E1::Scalar>,
 {
+  fn write_bytes<W: Sized + io::Write>(&self, byte_sink: &mut W) -> Result<(), io::Error> {
+    let config = bincode::DefaultOptions::new()
+      .with_little_endian()
+      .with_fixint_encoding();
+    config
+      .serialize_into(
+        &mut *byte_sink,
+        &(
+          &self.F_arity,
+          &self.ro_consts_primary,
+          &self.ro_consts_circuit_primary,
+          &self.ro_consts_secondary,
+          &self.ro_consts_circuit_secondary,
+          &self.ck_primary,
+          &self.r1cs_shape_primary,
+          &self.ck_secondary,
+          &self.r1cs_shape_secondary,
+        ),
+      )
+      .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    // the remaining fields are only absorbed, after a tag, when they differ from their defaults,
+    // so that the digest of public parameters set up with `PublicParams::setup` is unaffected
+    if let Some(comm) = &self.ck_seed_commitment {
+      write_tagged(byte_sink, b"ck_seed_commitment", comm)?;
+    }
+    if self.options.trivial_secondary {
+      write_tagged(byte_sink, b"trivial_secondary", &())?;
+    }
+    if self.options.data_chain {
+      write_tagged(byte_sink, b"data_chain", &())?;
+    }
+    if self.options.steps_per_fold != 1 {
+      write_tagged(byte_sink, b"steps_per_fold", &self.options.steps_per_fold)?;
+    }
+    if self.options.output_hash {
+      write_tagged(byte_sink, b"output_hash", &())?;
+    }
+    if !self.options.context.is_empty() {
+      write_tagged(byte_sink, b"context", &self.options.context)?;
+    }
+    if self.shape_format_version != first_shape_format_version() {
+      write_tagged(
+        byte_sink,
+        b"shape_format_version",
+        &self.shape_format_version,
+      )?;
+    }
+    Ok(())
+  }
+}
+
+// hashes the state `z` of an augmented circuit as it does when it absorbs a hash of its outputs
+fn state_hash<E: Engine>(ro_consts: &ROConstants<E>, z: &[E::Base]) -> E::Base {
+  let mut hasher = E::RO::new(ro_consts.clone());
+  hasher.absorb_batch(z);
+  hasher.squeeze(NUM_HASH_BITS)
+}
+
+// writes a length-prefixed tag followed by `value`, with the encoding of `Digestible::write_bytes`
+fn write_tagged<W: io::Write, T: Serialize + ?Sized>(
+  byte_sink: &mut W,
+  tag: &[u8],
+  value: &T,
+) -> Result<(), io::Error> {
+  bincode::DefaultOptions::new()
+    .with_little_endian()
+    .with_fixint_encoding()
+    .serialize_into(byte_sink, &(tag, value))
+    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
 }
 
+// the part of the public parameters that identifies the augmented circuits, digested by `shape_digest`
+#[derive(Serialize)]
+#[serde(bound = "")]
+struct ShapeParams<'a, E1: Engine, E2: Engine> {
+  F_arity: usize,
+  r1cs_shape_primary: &'a R1CSShape<E1>,
+  r1cs_shape_secondary: &'a R1CSShape<E2>,
+  trivial_secondary: bool,
+}
+
+impl<E1: Engine, E2: Engine> SimpleDigestible for ShapeParams<'_, E1, E2> {}
+
 impl<E1, E2, C> PublicParams<E1, E2, C>
 where
   E1: Engine<Base = <E2 as Engine>::Scalar>,
@@ -122,34 +346,282 @@ where
     ck_hint1: &CommitmentKeyHint<E1>,
     ck_hint2: &CommitmentKeyHint<E2>,
   ) -> Result<Self, NovaError> {
-    let ro_consts_primary: ROConstants<E1> = ROConstants::<E1>::default();
-    let ro_consts_secondary: ROConstants<E2> = ROConstants::<E2>::default();
+    Self::setup_inner(
+      c,
+      ck_hint1,
+      ck_hint2,
+      None,
+      SetupOptions::default(),
+      None,
+      None,
+    )
+  }
+
+  /// Creates a new `PublicParams` for a circuit `C` as in `setup`, with the variant of the
+  /// augmented circuits and the context selected by `options`, which combines the settings of
+  /// `setup_optimized_trivial_secondary`, `setup_with_data_chain`, `setup_with_steps_per_fold`,
+  /// and `setup_with_context`.
+  ///
+  /// Returns `NovaError::InvalidNumSteps` if the number of steps per fold is zero, and
+  /// `NovaError::InvalidPublicParams` if `options` both specialize the secondary circuit for its
+  /// trivial step circuit and enable the data chain, whose state makes that step circuit nontrivial,
+  /// or the hash of the outputs, which a trivial step circuit does not have.
+  pub fn setup_with_options(
+    c: &C,
+    ck_hint1: &CommitmentKeyHint<E1>,
+    ck_hint2: &CommitmentKeyHint<E2>,
+    options: SetupOptions<E1>,
+  ) -> Result<Self, NovaError> {
+    Self::setup_inner(c, ck_hint1, ck_hint2, None, options, None, None)
+  }
 
-    let F_arity = c.arity();
+  /// Creates a new `PublicParams` for a circuit `C` as in `setup`, except that the generators of
+  /// both commitment keys are derived deterministically from `seed` with the provider's hash-to-curve,
+  /// so anyone holding the seed can reproduce identical keys.
+  ///
+  /// A commitment to the seed is folded into the digest of the public parameters.
+  /// Returns `NovaError::UnsupportedCommitmentKeySeed` if a commitment engine cannot derive its key
+  /// from a public seed, as is the case for HyperKZG.
+  pub fn setup_with_ck_seed(
+    c: &C,
+    ck_hint1: &CommitmentKeyHint<E1>,
+    ck_hint2: &CommitmentKeyHint<E2>,
+    seed: [u8; 32],
+  ) -> Result<Self, NovaError> {
+    Self::setup_inner(
+      c,
+      ck_hint1,
+      ck_hint2,
+      Some(&seed),
+      SetupOptions::default(),
+      None,
+      None,
+    )
+  }
 
-    // ro_consts_circuit_primary are parameterized by E2 because the type alias uses E2::Base = E1::Scalar
-    let ro_consts_circuit_primary: ROConstantsCircuit<E2> = ROConstantsCircuit::<E2>::default();
-    let ro_consts_circuit_secondary: ROConstantsCircuit<E1> = ROConstantsCircuit::<E1>::default();
+  /// Creates a new `PublicParams` for a circuit `C` as in `setup`, except that the secondary
+  /// circuit is specialized for its trivial step circuit, which reduces its number of constraints.
+  ///
+  /// The state of the trivial step circuit is a constant, so it is omitted from the hash of the
+  /// primary running instance computed in the secondary circuit. This changes the shape of the
+  /// secondary circuit, and thus the digest, so the resulting parameters are not interchangeable
+  /// with those produced by `setup`.
+  pub fn setup_optimized_trivial_secondary(
+    c: &C,
+    ck_hint1: &CommitmentKeyHint<E1>,
+    ck_hint2: &CommitmentKeyHint<E2>,
+  ) -> Result<Self, NovaError> {
+    Self::setup_with_options(
+      c,
+      ck_hint1,
+      ck_hint2,
+      SetupOptions::default().with_trivial_secondary(true),
+    )
+  }
 
-    // Initialize ck for the primary
-    let circuit_primary: NovaAugmentedCircuit<'_, E2, C> =
-      NovaAugmentedCircuit::new(true, None, c, ro_consts_circuit_primary.clone());
-    let mut cs: ShapeCS<E1> = ShapeCS::new();
-    let _ = circuit_primary.synthesize(&mut cs);
-    let (r1cs_shape_primary, ck_primary) = cs.r1cs_shape(ck_hint1);
+  /// Creates a new `PublicParams` for a circuit `C` as in `setup`, except that the secondary
+  /// circuit maintains a hash chain of commitments to external data, one of which is absorbed at
+  /// each step executed by `RecursiveSNARK::prove_step_with_data`.
+  ///
+  /// The chain is part of the hash of the primary running instance, so a proof attests to the
+  /// sequence of data commitments it processed, as returned by
+  /// `RecursiveSNARK::data_commitment_chain`. This changes the shape of the secondary circuit, and
+  /// thus the digest, so the resulting parameters are not interchangeable with those produced by
+  /// `setup`.
+  pub fn setup_with_data_chain(
+    c: &C,
+    ck_hint1: &CommitmentKeyHint<E1>,
+    ck_hint2: &CommitmentKeyHint<E2>,
+  ) -> Result<Self, NovaError> {
+    Self::setup_with_options(
+      c,
+      ck_hint1,
+      ck_hint2,
+      SetupOptions::default().with_data_chain(true),
+    )
+  }
 
-    // Initialize ck for the secondary
-    let tc = TrivialCircuit::<E2::Scalar>::default();
-    let circuit_secondary: NovaAugmentedCircuit<'_, E1, _> =
-      NovaAugmentedCircuit::new(false, None, &tc, ro_consts_circuit_secondary.clone());
-    let mut cs: ShapeCS<E2> = ShapeCS::new();
-    let _ = circuit_secondary.synthesize(&mut cs);
-    let (r1cs_shape_secondary, ck_secondary) = cs.r1cs_shape(ck_hint2);
+  /// Creates a new `PublicParams` for a circuit `C` as in `setup`, and binds the application-defined
+  /// `context`, e.g., a version number or a chain identifier, into the digest.
+  ///
+  /// Since the digest is absorbed when proving and verifying, a proof produced under one context
+  /// does not verify under another. The context can be retrieved with `context`.
+  pub fn setup_with_context(
+    c: &C,
+    ck_hint1: &CommitmentKeyHint<E1>,
+    ck_hint2: &CommitmentKeyHint<E2>,
+    context: &[E1::Scalar],
+  ) -> Result<Self, NovaError> {
+    Self::setup_with_options(
+      c,
+      ck_hint1,
+      ck_hint2,
+      SetupOptions::default().with_context(context),
+    )
+  }
 
-    if r1cs_shape_primary.num_io != 2 || r1cs_shape_secondary.num_io != 2 {
-      return Err(NovaError::InvalidStepCircuitIO);
+  /// Creates a new `PublicParams` for a circuit `C` as in `setup`, except that the synthesis of
+  /// the primary circuit, which includes the step circuit, stops once it enforces more than
+  /// `constraint_limit` constraints. This is a safety rail for untrusted or buggy step circuits,
+  /// which then fail with a `NovaError::SynthesisError` instead of exhausting memory.
+  ///
+  /// The limit only affects setup, and the resulting parameters are identical to those of `setup`.
+  pub fn setup_with_constraint_limit(
+    c: &C,
+    ck_hint1: &CommitmentKeyHint<E1>,
+    ck_hint2: &CommitmentKeyHint<E2>,
+    constraint_limit: usize,
+  ) -> Result<Self, NovaError> {
+    Self::setup_inner(
+      c,
+      ck_hint1,
+      ck_hint2,
+      None,
+      SetupOptions::default(),
+      Some(constraint_limit),
+      None,
+    )
+  }
+
+  /// Creates a new `PublicParams` for a circuit `C` as in `setup`, except that the ROs of `E1` and
+  /// `E2` are initialized with the constants `ro_consts_primary` and `ro_consts_secondary` rather
+  /// than their defaults, e.g., to match an external verifier that instantiates the same sponge
+  /// with different parameters.
+  ///
+  /// The constants are used both outside and inside the augmented circuits, so the circuit RO of
+  /// each engine must share its constants with the native one, as is the case for an `ROTrait`
+  /// and its `ROTrait::CircuitRO`. The constants are bound into the digest.
+  pub fn setup_with_ro_constants(
+    c: &C,
+    ck_hint1: &CommitmentKeyHint<E1>,
+    ck_hint2: &CommitmentKeyHint<E2>,
+    ro_consts_primary: ROConstants<E1>,
+    ro_consts_secondary: ROConstants<E2>,
+  ) -> Result<Self, NovaError>
+  where
+    E1::ROCircuit: ROCircuitTrait<E1::Base, Constants = ROConstants<E1>>,
+    E2::ROCircuit: ROCircuitTrait<E2::Base, Constants = ROConstants<E2>>,
+  {
+    // the primary circuit verifies the folding of the secondary instances, and vice versa
+    let ro_consts = (
+      ro_consts_primary.clone(),
+      ro_consts_secondary.clone(),
+      ro_consts_secondary,
+      ro_consts_primary,
+    );
+    Self::setup_inner(
+      c,
+      ck_hint1,
+      ck_hint2,
+      None,
+      SetupOptions::default(),
+      None,
+      Some(ro_consts),
+    )
+  }
+
+  /// Creates a new `PublicParams` for a circuit `C` as in `setup`, except that the primary circuit
+  /// executes up to `steps_per_fold` steps of `C` per fold, so that the secondary circuit is folded
+  /// once for every `steps_per_fold` steps, as executed by `RecursiveSNARK::queue_primary_steps`
+  /// and `RecursiveSNARK::sync_secondary`, which amortizes the cost of the cross-curve folding.
+  ///
+  /// The primary circuit synthesizes `C` `steps_per_fold` times, so it grows accordingly, whether
+  /// or not a fold executes all of them. This changes the shapes of both circuits, and thus the
+  /// digest, so the resulting parameters are not interchangeable with those produced by `setup`.
+  /// Returns `NovaError::InvalidNumSteps` if `steps_per_fold` is zero.
+  pub fn setup_with_steps_per_fold(
+    c: &C,
+    ck_hint1: &CommitmentKeyHint<E1>,
+    ck_hint2: &CommitmentKeyHint<E2>,
+    steps_per_fold: usize,
+  ) -> Result<Self, NovaError> {
+    Self::setup_with_options(
+      c,
+      ck_hint1,
+      ck_hint2,
+      SetupOptions::default().with_steps_per_fold(steps_per_fold),
+    )
+  }
+
+  /// Returns the minimum sizes of the primary and secondary commitment keys that folding the
+  /// circuit `c` requires with public parameters created with `setup`, computed from the shapes
+  /// of the augmented circuits without generating any key. This is a floor for the sizes that
+  /// `ck_hint1` and `ck_hint2` lead to, to which a SNARK may add its own with `ck_floor`.
+  pub fn min_key_sizes(c: &C) -> Result<(usize, usize), NovaError> {
+    let (r1cs_shape_primary, r1cs_shape_secondary) = Self::circuit_shapes(
+      c,
+      &ROConstantsCircuit::<E2>::default(),
+      &ROConstantsCircuit::<E1>::default(),
+      &SetupOptions::default(),
+      None,
+    )?;
+    Ok((
+      r1cs_shape_primary.min_commitment_key_size(),
+      r1cs_shape_secondary.min_commitment_key_size(),
+    ))
+  }
+
+  fn setup_inner(
+    c: &C,
+    ck_hint1: &CommitmentKeyHint<E1>,
+    ck_hint2: &CommitmentKeyHint<E2>,
+    ck_seed: Option<&[u8; 32]>,
+    options: SetupOptions<E1>,
+    constraint_limit: Option<usize>,
+    ro_consts: Option<(
+      ROConstants<E1>,
+      ROConstantsCircuit<E2>,
+      ROConstants<E2>,
+      ROConstantsCircuit<E1>,
+    )>,
+  ) -> Result<Self, NovaError> {
+    if options.steps_per_fold == 0 {
+      return Err(NovaError::InvalidNumSteps);
+    }
+    // the data chain is part of the state of the secondary step circuit, which is then not trivial
+    if options.trivial_secondary && options.data_chain {
+      return Err(NovaError::InvalidPublicParams {
+        reason: "a trivial secondary circuit cannot maintain a data chain".to_string(),
+      });
+    }
+    // the output hash of the secondary circuit is a hash of its state
+    if options.trivial_secondary && options.output_hash {
+      return Err(NovaError::InvalidPublicParams {
+        reason: "a trivial secondary circuit has no outputs to hash".to_string(),
+      });
     }
 
+    // ro_consts_circuit_primary are parameterized by E2 because the type alias uses E2::Base = E1::Scalar
+    let (
+      ro_consts_primary,
+      ro_consts_circuit_primary,
+      ro_consts_secondary,
+      ro_consts_circuit_secondary,
+    ) = ro_consts.unwrap_or_default();
+
+    let F_arity = c.arity();
+
+    let (r1cs_shape_primary, r1cs_shape_secondary) = Self::circuit_shapes(
+      c,
+      &ro_consts_circuit_primary,
+      &ro_consts_circuit_secondary,
+      &options,
+      constraint_limit,
+    )?;
+
+    let (ck_primary, ck_secondary, ck_seed_commitment) = match ck_seed {
+      Some(seed) => (
+        r1cs_shape_primary.commitment_key_from_seed(ck_hint1, seed)?,
+        r1cs_shape_secondary.commitment_key_from_seed(ck_hint2, seed)?,
+        Some(Sha3_256::digest(seed).into()),
+      ),
+      None => (
+        r1cs_shape_primary.commitment_key(ck_hint1),
+        r1cs_shape_secondary.commitment_key(ck_hint2),
+        None,
+      ),
+    };
+
     let pp = PublicParams {
       F_arity,
 
@@ -165,7 +637,139 @@ where
       ck_secondary,
       r1cs_shape_secondary,
 
+      ck_seed_commitment,
+
+      options,
+
+      shape_format_version: SHAPE_FORMAT_VERSION,
+
+      digest: OnceCell::new(),
+      digest_fingerprint: OnceCell::new(),
+      _p: Default::default(),
+    };
+
+    // call pp.digest() so the digest is computed here rather than in RecursiveSNARK methods
+    let _ = pp.digest();
+
+    Ok(pp)
+  }
+
+  /// Synthesizes the shapes of the primary and secondary augmented circuits for the step circuit `c`
+  fn circuit_shapes(
+    c: &C,
+    ro_consts_circuit_primary: &ROConstantsCircuit<E2>,
+    ro_consts_circuit_secondary: &ROConstantsCircuit<E1>,
+    options: &SetupOptions<E1>,
+    constraint_limit: Option<usize>,
+  ) -> Result<(R1CSShape<E1>, R1CSShape<E2>), NovaError> {
+    // a step circuit without state cannot be chained across steps
+    if c.arity() == 0 {
+      return Err(NovaError::ZeroArity);
+    }
+
+    // Initialize the shape for the primary
+    let circuit_primary: NovaAugmentedCircuit<'_, E2, C> =
+      NovaAugmentedCircuit::new(true, None, c, ro_consts_circuit_primary.clone())
+        .with_output_hash(options.output_hash)
+        .with_steps_per_fold(options.steps_per_fold, &[]);
+    let mut cs: ShapeCS<E1> = match constraint_limit {
+      Some(limit) => ShapeCS::with_constraint_limit(limit),
+      None => ShapeCS::new(),
+    };
+    let _ = circuit_primary.synthesize(&mut cs);
+    cs.check_constraint_limit()?;
+    let r1cs_shape_primary = cs.r1cs_shape_only();
+
+    // Initialize the shape for the secondary
+    let tc = if options.data_chain {
+      DataChainCircuit::<E1>::new(ro_consts_circuit_secondary.clone(), None)
+    } else {
+      DataChainCircuit::disabled()
+    };
+    let circuit_secondary: NovaAugmentedCircuit<'_, E1, _> =
+      NovaAugmentedCircuit::new(false, None, &tc, ro_consts_circuit_secondary.clone())
+        .with_trivial_step(options.trivial_secondary)
+        .with_output_hash(options.output_hash)
+        .with_folded_public_signals(c.num_public_signals())
+        .with_steps_per_fold(options.steps_per_fold, &[]);
+    let mut cs: ShapeCS<E2> = ShapeCS::new();
+    let _ = circuit_secondary.synthesize(&mut cs);
+    let r1cs_shape_secondary = cs.r1cs_shape_only();
+
+    // the primary circuit outputs the public signals of the step circuit after the two hashes
+    if r1cs_shape_primary.num_io != 2 + c.num_public_signals() || r1cs_shape_secondary.num_io != 2 {
+      return Err(NovaError::InvalidStepCircuitIO);
+    }
+
+    // the augmented circuits always have constraints, but an empty shape would yield empty
+    // commitment keys, so it is rejected here rather than in the commitment engine
+    if r1cs_shape_primary.num_cons == 0 || r1cs_shape_secondary.num_cons == 0 {
+      return Err(NovaError::EmptyCircuit);
+    }
+
+    Ok((r1cs_shape_primary, r1cs_shape_secondary))
+  }
+
+  /// Returns the step circuit of the secondary circuit, which absorbs `data_commitment` if the
+  /// data chain is enabled
+  fn data_chain_circuit(&self, data_commitment: Option<Commitment<E1>>) -> DataChainCircuit<E1> {
+    if self.options.data_chain {
+      DataChainCircuit::new(self.ro_consts_circuit_secondary.clone(), data_commitment)
+    } else {
+      DataChainCircuit::disabled()
+    }
+  }
+
+  /// Creates a new `PublicParams` for another circuit `C2` that copies the commitment keys of `self`
+  /// instead of generating new ones, e.g., to set up several related circuits of similar size from a
+  /// single over-provisioned key.
+  ///
+  /// The shapes and the digest are computed for the new circuit, and the derived parameters keep
+  /// the other settings of `self`. Returns `NovaError::InvalidCommitmentKeyLength` if the shared keys
+  /// are too small for the shapes of the new circuit. The keys are only checked against the shapes,
+  /// so a SNARK that requires a larger key, as given by its `ck_floor`, should be accounted for when
+  /// setting up `self`.
+  pub fn derive_for<C2: StepCircuit<E1::Scalar>>(
+    &self,
+    c: &C2,
+  ) -> Result<PublicParams<E1, E2, C2>, NovaError> {
+    let (r1cs_shape_primary, r1cs_shape_secondary) = PublicParams::<E1, E2, C2>::circuit_shapes(
+      c,
+      &self.ro_consts_circuit_primary,
+      &self.ro_consts_circuit_secondary,
+      &self.options,
+      None,
+    )?;
+
+    if self.ck_primary.length() < r1cs_shape_primary.min_commitment_key_size()
+      || self.ck_secondary.length() < r1cs_shape_secondary.min_commitment_key_size()
+    {
+      return Err(NovaError::InvalidCommitmentKeyLength);
+    }
+
+    let pp = PublicParams {
+      F_arity: c.arity(),
+
+      ro_consts_primary: self.ro_consts_primary.clone(),
+      ro_consts_circuit_primary: self.ro_consts_circuit_primary.clone(),
+
+      ro_consts_secondary: self.ro_consts_secondary.clone(),
+      ro_consts_circuit_secondary: self.ro_consts_circuit_secondary.clone(),
+
+      ck_primary: self.ck_primary.clone(),
+      r1cs_shape_primary,
+
+      ck_secondary: self.ck_secondary.clone(),
+      r1cs_shape_secondary,
+
+      ck_seed_commitment: self.ck_seed_commitment,
+
+      options: self.options.clone(),
+
+      shape_format_version: SHAPE_FORMAT_VERSION,
+
       digest: OnceCell::new(),
+      digest_fingerprint: OnceCell::new(),
       _p: Default::default(),
     };
 
@@ -177,11 +781,80 @@ where
 
   /// Retrieve the digest of the public parameters.
   pub fn digest(&self) -> E1::Scalar {
-    self
+    let digest = self
       .digest
       .get_or_try_init(|| DigestComputer::new(self).digest())
       .cloned()
-      .expect("Failure in retrieving digest")
+      .expect("Failure in retrieving digest");
+    self
+      .digest_fingerprint
+      .get_or_init(|| self.fingerprint(&digest));
+    digest
+  }
+
+  // hashes `digest` with the arity, the sizes of the shapes and the commitment keys, and the
+  // remaining fields absorbed by `write_bytes`, which is cheap compared to hashing the keys
+  fn fingerprint(&self, digest: &E1::Scalar) -> [u8; 32] {
+    let bytes = bincode::serialize(&(
+      digest,
+      self.F_arity,
+      (
+        self.r1cs_shape_primary.num_cons,
+        self.r1cs_shape_primary.num_vars,
+        self.r1cs_shape_primary.num_io,
+      ),
+      (
+        self.r1cs_shape_secondary.num_cons,
+        self.r1cs_shape_secondary.num_vars,
+        self.r1cs_shape_secondary.num_io,
+      ),
+      (self.ck_primary.length(), self.ck_secondary.length()),
+      &self.ck_seed_commitment,
+      &self.options,
+      self.shape_format_version,
+    ))
+    .expect("serializing the fingerprint cannot fail");
+    Sha3_256::digest(bytes).into()
+  }
+
+  /// Checks that the cached digest, if any, was computed by `digest` and that the parts of the
+  /// public parameters that are cheap to hash have not changed since, and returns
+  /// `NovaError::StalePublicParamsDigest` otherwise.
+  ///
+  /// Unlike `check_digest`, this does not hash the commitment keys, so it is cheap enough to run
+  /// whenever a proof starts, but it does not detect a change to the generators of a key that
+  /// keeps its length.
+  fn check_digest_fingerprint(&self) -> Result<(), NovaError> {
+    match (self.digest.get(), self.digest_fingerprint.get()) {
+      (Some(digest), Some(fingerprint)) if self.fingerprint(digest) != *fingerprint => {
+        Err(NovaError::StalePublicParamsDigest)
+      }
+      (Some(_), None) => Err(NovaError::StalePublicParamsDigest),
+      _ => Ok(()),
+    }
+  }
+
+  /// Returns the version of the semantics of circuit synthesis that produced the shapes, which is
+  /// bound into the digest, as given by `frontend::SHAPE_FORMAT_VERSION` at setup
+  pub const fn shape_format_version(&self) -> u32 {
+    self.shape_format_version
+  }
+
+  /// Returns the application-defined context bound into the digest, which is empty unless the
+  /// parameters were created with `setup_with_context`
+  pub fn context(&self) -> &[E1::Scalar] {
+    &self.options.context
+  }
+
+  /// Returns the maximum number of steps of the primary circuit executed per fold, which is one
+  /// unless the parameters were created with `setup_with_steps_per_fold`
+  pub const fn steps_per_fold(&self) -> usize {
+    self.options.steps_per_fold
+  }
+
+  /// Returns the options the public parameters were set up with
+  pub const fn options(&self) -> &SetupOptions<E1> {
+    &self.options
   }
 
   /// Returns the number of constraints in the primary and secondary circuits
@@ -192,13 +865,267 @@ where
     )
   }
 
-  /// Returns the number of variables in the primary and secondary circuits
-  pub const fn num_variables(&self) -> (usize, usize) {
-    (
-      self.r1cs_shape_primary.num_vars,
-      self.r1cs_shape_secondary.num_vars,
+  /// Returns a digest of the shapes of the primary and secondary circuits and the arity of the
+  /// step circuit, which identifies the circuits independently of the commitment keys and RO
+  /// constants, unlike `digest`.
+  ///
+  /// Two `PublicParams` for the same circuit with commitment keys of different sizes share a
+  /// shape digest, but not a digest.
+  pub fn shape_digest(&self) -> E1::Scalar {
+    let shape_params = ShapeParams {
+      F_arity: self.F_arity,
+      r1cs_shape_primary: &self.r1cs_shape_primary,
+      r1cs_shape_secondary: &self.r1cs_shape_secondary,
+      trivial_secondary: self.options.trivial_secondary,
+    };
+    DigestComputer::new(&shape_params)
+      .digest()
+      .expect("Failure in computing the shape digest")
+  }
+
+  // the number of public signals of the step circuit, which the primary circuit outputs after the
+  // two hashes
+  fn num_public_signals(&self) -> usize {
+    self.r1cs_shape_primary.num_io.saturating_sub(2)
+  }
+
+  /// Returns the number of generators in the primary and secondary commitment keys, e.g., to check
+  /// that they are at least as large as requested by the `ck_floor` of a SNARK
+  pub fn commitment_key_sizes(&self) -> (usize, usize) {
+    (self.ck_primary.length(), self.ck_secondary.length())
+  }
+
+  /// Grows the primary and secondary commitment keys to the keys that `setup` samples for
+  /// `new_primary` and `new_secondary` generators, keeping the existing generators rather than
+  /// sampling the keys again, e.g., when a data-dependent computation outgrows them, and recomputes
+  /// the digest. A key that already has enough generators is left unchanged.
+  ///
+  /// Since the digest changes, prover and verifier keys set up from `self` must be set up again.
+  /// Returns `NovaError::UnsupportedCommitmentKeyExtension` if the keys were derived from a seed by
+  /// `setup_with_ck_seed`, which the public parameters only hold a commitment to, or if the
+  /// commitment engine of either engine cannot extend its key, as is the case for HyperKZG, in
+  /// which case `self` is left unchanged.
+  pub fn grow_keys(&mut self, new_primary: usize, new_secondary: usize) -> Result<(), NovaError> {
+    if self.ck_seed_commitment.is_some() {
+      return Err(NovaError::UnsupportedCommitmentKeyExtension);
+    }
+
+    // a key with enough generators is left unchanged, so this checks that both keys can be
+    // extended before growing either of them in place
+    E1::CE::extend(&mut self.ck_primary, b"ck", 0)?;
+    E2::CE::extend(&mut self.ck_secondary, b"ck", 0)?;
+
+    E1::CE::extend(&mut self.ck_primary, b"ck", new_primary)?;
+    E2::CE::extend(&mut self.ck_secondary, b"ck", new_secondary)?;
+
+    self.digest = OnceCell::new();
+    self.digest_fingerprint = OnceCell::new();
+    let _ = self.digest();
+    Ok(())
+  }
+
+  /// Returns the number of variables in the primary and secondary circuits
+  pub const fn num_variables(&self) -> (usize, usize) {
+    (
+      self.r1cs_shape_primary.num_vars,
+      self.r1cs_shape_secondary.num_vars,
+    )
+  }
+
+  /// Returns the constants of the ROs used to hash the running instances of the primary and
+  /// secondary circuits, e.g., to replicate the hash chain checked by `RecursiveSNARK::verify`.
+  ///
+  /// The prover and the verifier must use the same constants, or proofs will not verify.
+  pub const fn ro_constants(&self) -> (&ROConstants<E1>, &ROConstants<E2>) {
+    (&self.ro_consts_primary, &self.ro_consts_secondary)
+  }
+
+  /// Checks the internal consistency of the public parameters, e.g., after deserializing them
+  /// from an untrusted source.
+  ///
+  /// This checks that the arity and the number of steps per fold are nonzero, that a trivial
+  /// secondary circuit does not maintain a data chain, that both shapes
+  /// have two public outputs and were synthesized with the current `frontend::SHAPE_FORMAT_VERSION`,
+  /// that the commitment keys are large enough for the shapes, that the RO constants used outside
+  /// the augmented circuits match the ones used inside them, and that a cached digest matches a
+  /// fresh recomputation.
+  /// Returns `NovaError::InvalidPublicParams` describing the first failed check.
+  pub fn self_check(&self) -> Result<(), NovaError> {
+    let invalid = |reason: &str| NovaError::InvalidPublicParams {
+      reason: reason.to_string(),
+    };
+
+    if self.F_arity == 0 {
+      return Err(invalid("arity of the step circuit is zero"));
+    }
+
+    if self.options.steps_per_fold == 0 {
+      return Err(invalid("number of steps per fold is zero"));
+    }
+
+    if self.options.trivial_secondary && self.options.data_chain {
+      return Err(invalid(
+        "a trivial secondary circuit cannot maintain a data chain",
+      ));
+    }
+
+    if self.r1cs_shape_primary.num_io < 2 || self.r1cs_shape_secondary.num_io != 2 {
+      return Err(invalid("R1CS shapes do not have two public outputs"));
+    }
+
+    if self.shape_format_version != SHAPE_FORMAT_VERSION {
+      return Err(invalid(
+        "R1CS shapes were synthesized with a different shape format version",
+      ));
+    }
+
+    if self.ck_primary.length()
+      < max(
+        self.r1cs_shape_primary.num_cons,
+        self.r1cs_shape_primary.num_vars,
+      )
+      || self.ck_secondary.length()
+        < max(
+          self.r1cs_shape_secondary.num_cons,
+          self.r1cs_shape_secondary.num_vars,
+        )
+    {
+      return Err(invalid("commitment keys are too small for the R1CS shapes"));
+    }
+
+    // the constants do not implement equality, so they are compared through their serialized form;
+    // each circuit verifies the folding of the other one's instances, and hence uses its constants
+    fn is_same<T: Serialize, U: Serialize>(consts: &T, circuit_consts: &U) -> bool {
+      bincode::serialize(consts).ok() == bincode::serialize(circuit_consts).ok()
+    }
+    if !is_same(&self.ro_consts_primary, &self.ro_consts_circuit_secondary)
+      || !is_same(&self.ro_consts_secondary, &self.ro_consts_circuit_primary)
+    {
+      return Err(invalid(
+        "RO constants differ from the ones of the augmented circuits",
+      ));
+    }
+
+    match self.check_digest() {
+      Err(NovaError::StalePublicParamsDigest) => Err(invalid(
+        "cached digest does not match the public parameters",
+      )),
+      res => res,
+    }
+  }
+
+  /// Checks that the cached digest, if any, matches a fresh recomputation over the contents of the
+  /// public parameters, and returns `NovaError::StalePublicParamsDigest` otherwise.
+  ///
+  /// A stale digest would be baked into the hashes of every proof, which would then not verify
+  /// against parameters with the correct digest. This serializes and hashes the public parameters,
+  /// including both commitment keys, so `RecursiveSNARK::new` only compares a cheap fingerprint of
+  /// the parameters recorded along with the digest.
+  pub fn check_digest(&self) -> Result<(), NovaError> {
+    if let Some(digest) = self.digest.get() {
+      let fresh: E1::Scalar = DigestComputer::new(self)
+        .digest()
+        .map_err(|_| NovaError::DigestError)?;
+      if *digest != fresh {
+        return Err(NovaError::StalePublicParamsDigest);
+      }
+    }
+    Ok(())
+  }
+
+  /// Checks that the step circuit `c` is compatible with the public parameters, i.e., that it can
+  /// be folded with the primary and secondary circuits they were set up for. This is meant as a
+  /// design-time check that catches a step circuit whose arity or shape differs from the one used
+  /// in `setup`, which would otherwise only surface as failures when proving or verifying.
+  ///
+  /// This synthesizes the step circuit on its own to check that it returns as many outputs as its
+  /// arity, and then synthesizes both augmented circuits to compare them with the stored shapes.
+  /// Returns `NovaError::IncompatibleCircuit` describing the first mismatch.
+  pub fn check_circuit_compatibility(&self, c: &C) -> Result<(), NovaError> {
+    let incompatible = |reason: String| NovaError::IncompatibleCircuit { reason };
+
+    if c.arity() != self.F_arity {
+      return Err(incompatible(format!(
+        "step circuit has arity {}, but the public parameters expect arity {}",
+        c.arity(),
+        self.F_arity
+      )));
+    }
+
+    let mut cs: ShapeCS<E1> = ShapeCS::new();
+    let z = (0..c.arity())
+      .map(|i| AllocatedNum::alloc(cs.namespace(|| format!("z{i}")), || Ok(E1::Scalar::ZERO)))
+      .collect::<Result<Vec<_>, _>>()?;
+    let outputs = c.synthesize(&mut cs, &z)?;
+    if outputs.len() != c.arity() + c.num_public_signals() {
+      return Err(incompatible(format!(
+        "step circuit returns {} outputs, but has arity {} and {} public signals",
+        outputs.len(),
+        c.arity(),
+        c.num_public_signals()
+      )));
+    }
+
+    let (r1cs_shape_primary, r1cs_shape_secondary) = Self::circuit_shapes(
+      c,
+      &self.ro_consts_circuit_primary,
+      &self.ro_consts_circuit_secondary,
+      &self.options,
+      None,
     )
+    .map_err(|e| incompatible(format!("augmented circuits cannot be synthesized: {e}")))?;
+    if r1cs_shape_primary != self.r1cs_shape_primary {
+      return Err(incompatible(format!(
+        "primary circuit has {} constraints and {} variables, but the public parameters expect {} constraints and {} variables",
+        r1cs_shape_primary.num_cons,
+        r1cs_shape_primary.num_vars,
+        self.r1cs_shape_primary.num_cons,
+        self.r1cs_shape_primary.num_vars
+      )));
+    }
+    if r1cs_shape_secondary != self.r1cs_shape_secondary {
+      return Err(incompatible(
+        "secondary circuit differs from the one the public parameters were set up for".to_string(),
+      ));
+    }
+
+    Ok(())
+  }
+}
+
+// reads the state of the secondary circuit, which is empty if it is specialized for a trivial step
+fn secondary_state<F: PrimeField>(zi: &[AllocatedNum<F>]) -> Result<F, SynthesisError> {
+  zi.first().map_or(Ok(F::ZERO), |z| {
+    z.get_value().ok_or(SynthesisError::AssignmentMissing)
+  })
+}
+
+/// The cost of a step of the primary circuit as estimated by `RecursiveSNARK::dry_run_step`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepCostReport {
+  /// The number of constraints of the primary circuit, which the synthesized witness satisfies
+  pub num_constraints: usize,
+  /// The number of entries of the synthesized witness
+  pub num_vars: usize,
+  /// The number of nonzero entries of the synthesized witness
+  pub nonzero_witness_entries: usize,
+  /// An estimate of the number of nonzero scalars in the multi-scalar multiplications of the step
+  /// on the primary curve, i.e., the nonzero witness entries plus the entries of the cross term
+  pub msm_size: usize,
+}
+
+// fails to serialize steps queued by `RecursiveSNARK::queue_primary_steps`, which would otherwise be
+// lost, and serializes nothing if there are none
+fn serialize_no_pending_steps<S: Serializer, C>(
+  pending_steps: &[C],
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  if !pending_steps.is_empty() {
+    return Err(S::Error::custom(
+      "cannot serialize a RecursiveSNARK with queued steps",
+    ));
   }
+  serializer.serialize_unit()
 }
 
 /// A SNARK that proves the correct execution of an incremental computation
@@ -227,6 +1154,17 @@ where
 
   zi: Vec<E1::Scalar>,
 
+  // the public signals of the last step, which follow its outputs
+  public_signals: Vec<E1::Scalar>,
+
+  // the state of the secondary circuit, which is the hash chain of the data commitments, if any
+  data_commitment_chain: E2::Scalar,
+
+  // the steps queued by `queue_primary_steps` that are not yet folded, which are not part of the
+  // proof, so it cannot be serialized while there are any
+  #[serde(serialize_with = "serialize_no_pending_steps", skip_deserializing)]
+  pending_steps: Vec<C>,
+
   _p: PhantomData<C>,
 }
 
@@ -236,17 +1174,48 @@ where
   E2: Engine<Base = <E1 as Engine>::Scalar>,
   C: StepCircuit<E1::Scalar>,
 {
+  /// Create new instance of recursive SNARK whose initial input is the encoding of the
+  /// application state `s0`. Returns `NovaError::InvalidInitialInputLength` if the arity of the
+  /// state does not match the arity of the step circuit.
+  pub fn new_typed<S: IvcState<E1::Scalar>>(
+    pp: &PublicParams<E1, E2, C>,
+    c: &C,
+    s0: &S,
+  ) -> Result<Self, NovaError> {
+    let z0 = s0.to_field_vec();
+    if S::arity() != pp.F_arity || z0.len() != S::arity() {
+      return Err(NovaError::InvalidInitialInputLength);
+    }
+    Self::new(pp, c, &z0)
+  }
+
   /// Create new instance of recursive SNARK
+  ///
+  /// Only the initial input `z0` of the step circuit is supplied: the secondary circuit is not
+  /// chosen by the caller, and `new` initializes its state itself, with the correct arity for
+  /// whether it is trivial or maintains a data commitment chain.
+  ///
+  /// Returns `NovaError::InvalidInitialState` if `z0` violates a precondition of the step circuit,
+  /// as checked by `StepCircuit::validate_initial`, before synthesizing the first step, and
+  /// `NovaError::StalePublicParamsDigest` if the cached digest of `pp` was not computed over its
+  /// current contents, as far as a cheap fingerprint of them can tell; `PublicParams::check_digest`
+  /// performs the full check.
   pub fn new(pp: &PublicParams<E1, E2, C>, c: &C, z0: &[E1::Scalar]) -> Result<Self, NovaError> {
+    pp.check_digest_fingerprint()?;
     if z0.len() != pp.F_arity {
       return Err(NovaError::InvalidInitialInputLength);
     }
+    c.validate_initial(z0)
+      .map_err(|reason| NovaError::InvalidInitialState { reason })?;
 
     let ri_primary = E1::Scalar::random(&mut OsRng);
     let ri_secondary = E2::Scalar::random(&mut OsRng);
 
     // base case for the primary
-    let mut cs_primary = SatisfyingAssignment::<E1>::new();
+    let mut cs_primary = SatisfyingAssignment::<E1>::with_capacity(
+      pp.r1cs_shape_primary.num_vars,
+      pp.r1cs_shape_primary.num_io + 1,
+    );
     let inputs_primary: NovaAugmentedCircuitInputs<E2> = NovaAugmentedCircuitInputs::new(
       scalar_as_base::<E1>(pp.digest()),
       E1::Scalar::ZERO,
@@ -264,13 +1233,23 @@ where
       Some(inputs_primary),
       c,
       pp.ro_consts_circuit_primary.clone(),
-    );
+    )
+    .with_output_hash(pp.options.output_hash)
+    .with_steps_per_fold(pp.options.steps_per_fold, &[]);
     let zi_primary = circuit_primary.synthesize(&mut cs_primary)?;
     let (u_primary, w_primary) =
       cs_primary.r1cs_instance_and_witness(&pp.r1cs_shape_primary, &pp.ck_primary)?;
+    if cfg!(debug_assertions) {
+      pp.r1cs_shape_primary
+        .is_sat_constraints(&u_primary, &w_primary)
+        .map_err(|_| NovaError::StepCircuitUnsatisfiable { step: 1 })?;
+    }
 
     // base case for the secondary
-    let mut cs_secondary = SatisfyingAssignment::<E2>::new();
+    let mut cs_secondary = SatisfyingAssignment::<E2>::with_capacity(
+      pp.r1cs_shape_secondary.num_vars,
+      pp.r1cs_shape_secondary.num_io + 1,
+    );
     let inputs_secondary: NovaAugmentedCircuitInputs<E1> = NovaAugmentedCircuitInputs::new(
       pp.digest(),
       E2::Scalar::ZERO,
@@ -282,14 +1261,20 @@ where
       Some(u_primary.clone()),
       None,
     );
-    let tc = TrivialCircuit::<E2::Scalar>::default();
+    // the step executed here absorbs no data commitment
+    let tc = pp.data_chain_circuit(None);
     let circuit_secondary: NovaAugmentedCircuit<'_, E1, _> = NovaAugmentedCircuit::new(
       false,
       Some(inputs_secondary),
       &tc,
       pp.ro_consts_circuit_secondary.clone(),
-    );
-    let _ = circuit_secondary.synthesize(&mut cs_secondary)?;
+    )
+    .with_trivial_step(pp.options.trivial_secondary)
+    .with_output_hash(pp.options.output_hash)
+    .with_folded_public_signals(pp.num_public_signals())
+    .with_steps_per_fold(pp.options.steps_per_fold, &[]);
+    let zi_secondary = circuit_secondary.synthesize(&mut cs_secondary)?;
+    let data_commitment_chain = secondary_state(&zi_secondary)?;
     let (u_secondary, w_secondary) =
       cs_secondary.r1cs_instance_and_witness(&pp.r1cs_shape_secondary, &pp.ck_secondary)?;
 
@@ -307,14 +1292,15 @@ where
     let r_U_secondary =
       RelaxedR1CSInstance::<E2>::default(&pp.ck_secondary, &pp.r1cs_shape_secondary);
 
-    if zi_primary.len() != pp.F_arity {
+    if zi_primary.len() != pp.F_arity + c.num_public_signals() {
       return Err(NovaError::InvalidStepOutputLength);
     }
 
-    let zi_primary = zi_primary
+    let mut zi_primary = zi_primary
       .iter()
       .map(|v| v.get_value().ok_or(SynthesisError::AssignmentMissing))
       .collect::<Result<Vec<<E1 as Engine>::Scalar>, _>>()?;
+    let public_signals = zi_primary.split_off(pp.F_arity);
 
     Ok(Self {
       z0: z0.to_vec(),
@@ -334,17 +1320,161 @@ where
 
       zi: zi_primary,
 
+      public_signals,
+
+      data_commitment_chain,
+
+      pending_steps: Vec::new(),
+
       _p: Default::default(),
     })
   }
 
-  /// Updates the provided `RecursiveSNARK` by executing a step of the incremental computation
-  pub fn prove_step(&mut self, pp: &PublicParams<E1, E2, C>, c: &C) -> Result<(), NovaError> {
+  /// Updates the provided `RecursiveSNARK` by executing a step of the incremental computation,
+  /// and returns the number of steps executed thus far, as later passed to `verify`.
+  ///
+  /// In debug builds, returns `NovaError::StepCircuitUnsatisfiable` with the number of the failing
+  /// step, as it would have been returned, if the step circuit is not satisfied by its advice,
+  /// leaving `self` unchanged. This checks every constraint of the primary circuit once more, so
+  /// release builds skip it, and a step that is not satisfied then only fails `verify`;
+  /// `prove_step_checked` always performs the check.
+  ///
+  /// # Base case
+  ///
+  /// The first step is executed by `new`, so the first call to `prove_step` after `new` does not
+  /// synthesize anything and only records that step, returning `1`. Each subsequent call executes
+  /// one more step. After `n` calls, the `RecursiveSNARK` verifies with `num_steps = n`.
+  ///
+  /// # Example
+  ///
+  /// ```rust
+  /// # use nova_snark::nova::{PublicParams, RecursiveSNARK};
+  /// # use nova_snark::provider::{PallasEngine, VestaEngine};
+  /// # use nova_snark::traits::{circuit::TrivialCircuit, snark::default_ck_hint, Engine};
+  /// # use ff::Field;
+  /// type E1 = PallasEngine;
+  /// type E2 = VestaEngine;
+  ///
+  /// let circuit = TrivialCircuit::<<E1 as Engine>::Scalar>::default();
+  /// let pp = PublicParams::<E1, E2, _>::setup(&circuit, &*default_ck_hint(), &*default_ck_hint())?;
+  /// let z0 = [<E1 as Engine>::Scalar::ZERO];
+  /// let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0)?;
+  ///
+  /// // the first call records the step executed by `new`
+  /// assert_eq!(recursive_snark.prove_step(&pp, &circuit)?, 1);
+  /// assert_eq!(recursive_snark.prove_step(&pp, &circuit)?, 2);
+  /// recursive_snark.verify(&pp, 2, &z0)?;
+  /// # Ok::<(), nova_snark::errors::NovaError>(())
+  /// ```
+  pub fn prove_step(&mut self, pp: &PublicParams<E1, E2, C>, c: &C) -> Result<usize, NovaError> {
+    self.prove_step_with_data(pp, c, None)
+  }
+
+  /// Executes a step of the incremental computation as in `prove_step`, and absorbs the commitment
+  /// `data_commitment` to external data into the hash chain returned by `data_commitment_chain`.
+  /// A step without data, as executed by `prove_step`, absorbs the identity point.
+  ///
+  /// Requires public parameters created with `PublicParams::setup_with_data_chain`. The step
+  /// executed by `new`, which the first call records, absorbs no data, so the first call takes
+  /// `None`. Returns `NovaError::InvalidDataCommitment` if a commitment is supplied otherwise.
+  /// Returns `NovaError::PendingPrimarySteps` if steps queued by `queue_primary_steps` are not
+  /// folded yet by `sync_secondary`.
+  pub fn prove_step_with_data(
+    &mut self,
+    pp: &PublicParams<E1, E2, C>,
+    c: &C,
+    data_commitment: Option<Commitment<E1>>,
+  ) -> Result<usize, NovaError> {
+    self.prove_step_inner(pp, c, data_commitment, cfg!(debug_assertions))
+  }
+
+  // executes a step as in `prove_step_with_data`, checking that the step circuit is satisfied by its
+  // advice before folding if `check_sat` is set
+  fn prove_step_inner(
+    &mut self,
+    pp: &PublicParams<E1, E2, C>,
+    c: &C,
+    data_commitment: Option<Commitment<E1>>,
+    check_sat: bool,
+  ) -> Result<usize, NovaError> {
+    if data_commitment.is_some() && (!pp.options.data_chain || self.i == 0) {
+      return Err(NovaError::InvalidDataCommitment);
+    }
+    if !self.pending_steps.is_empty() {
+      return Err(NovaError::PendingPrimarySteps);
+    }
+
     // first step was already done in the constructor
     if self.i == 0 {
       self.i = 1;
-      return Ok(());
+      return Ok(self.i);
+    }
+
+    self.fold_steps(pp, c, &[], data_commitment, check_sat)
+  }
+
+  /// Queues `n` steps of `c` to be executed by the next call to `sync_secondary`, which folds them
+  /// with a single fold of each circuit, and returns the number of steps executed thus far,
+  /// counting the queued ones, as later passed to `verify` once they are folded.
+  ///
+  /// This requires public parameters created with `PublicParams::setup_with_steps_per_fold`, and
+  /// returns `NovaError::InvalidNumSteps` if more than `steps_per_fold` steps would be queued, in
+  /// which case `self` is left unchanged. As with `prove_step`, the first call records the step
+  /// executed by `new`, which counts towards `n`.
+  ///
+  /// This only queues the steps: they are not part of the proof until they are folded, and are not
+  /// checked until then, when a step circuit that is not satisfied by its advice fails
+  /// `sync_secondary` instead, in debug builds. A `RecursiveSNARK` with queued steps cannot be serialized.
+  pub fn queue_primary_steps(
+    &mut self,
+    pp: &PublicParams<E1, E2, C>,
+    c: &C,
+    n: usize,
+  ) -> Result<usize, NovaError> {
+    // the first step was already done in the constructor, but has not been recorded yet
+    let recorded = usize::from(self.i == 0);
+    let n_queued = n.saturating_sub(recorded);
+    if n == 0 || self.pending_steps.len() + n_queued > pp.options.steps_per_fold {
+      return Err(NovaError::InvalidNumSteps);
+    }
+
+    self.i += recorded;
+    self.pending_steps.extend((0..n_queued).map(|_| c.clone()));
+    Ok(self.i + self.pending_steps.len())
+  }
+
+  /// Folds the steps queued by `queue_primary_steps` with a single fold of each circuit, so that
+  /// the secondary circuit catches up with the primary one, and returns the number of steps executed
+  /// thus far, as later passed to `verify`. Does nothing if no step is queued.
+  ///
+  /// The secondary circuit is fixed by the public parameters, so unlike the primary steps, it takes
+  /// no step circuit. In debug builds, returns `NovaError::StepCircuitUnsatisfiable` with the number
+  /// of the first queued step if the fold is not satisfied, leaving `self` unchanged, as in
+  /// `prove_step`.
+  pub fn sync_secondary(&mut self, pp: &PublicParams<E1, E2, C>) -> Result<usize, NovaError> {
+    let pending_steps = std::mem::take(&mut self.pending_steps);
+    let Some((c, further_steps)) = pending_steps.split_first() else {
+      return Ok(self.i);
+    };
+
+    let res = self.fold_steps(pp, c, further_steps, None, cfg!(debug_assertions));
+    if res.is_err() {
+      self.pending_steps = pending_steps;
     }
+    res
+  }
+
+  // executes the step of `c` followed by those of `further_steps` with a single fold of each circuit,
+  // checking that the primary circuit is satisfied before folding if `check_sat` is set
+  fn fold_steps(
+    &mut self,
+    pp: &PublicParams<E1, E2, C>,
+    c: &C,
+    further_steps: &[C],
+    data_commitment: Option<Commitment<E1>>,
+    check_sat: bool,
+  ) -> Result<usize, NovaError> {
+    let num_steps = 1 + further_steps.len();
 
     // fold the secondary circuit's instance
     let (nifs_secondary, (r_U_secondary, r_W_secondary)) = NIFS::prove(
@@ -360,7 +1490,10 @@ where
 
     let r_next_primary = E1::Scalar::random(&mut OsRng);
 
-    let mut cs_primary = SatisfyingAssignment::<E1>::new();
+    let mut cs_primary = SatisfyingAssignment::<E1>::with_capacity(
+      pp.r1cs_shape_primary.num_vars,
+      pp.r1cs_shape_primary.num_io + 1,
+    );
     let inputs_primary: NovaAugmentedCircuitInputs<E2> = NovaAugmentedCircuitInputs::new(
       scalar_as_base::<E1>(pp.digest()),
       E1::Scalar::from(self.i as u64),
@@ -371,19 +1504,30 @@ where
       r_next_primary,
       Some(self.l_u_secondary.clone()),
       Some(nifs_secondary.comm_T),
-    );
+    )
+    .with_public_signals(self.public_signals.clone())
+    .with_num_steps(num_steps);
 
     let circuit_primary: NovaAugmentedCircuit<'_, E2, C> = NovaAugmentedCircuit::new(
       true,
       Some(inputs_primary),
       c,
       pp.ro_consts_circuit_primary.clone(),
-    );
+    )
+    .with_output_hash(pp.options.output_hash)
+    .with_steps_per_fold(pp.options.steps_per_fold, further_steps);
     let zi_primary = circuit_primary.synthesize(&mut cs_primary)?;
 
     let (l_u_primary, l_w_primary) =
       cs_primary.r1cs_instance_and_witness(&pp.r1cs_shape_primary, &pp.ck_primary)?;
 
+    // stop before folding if the step circuit is not satisfied by its advice
+    if check_sat {
+      pp.r1cs_shape_primary
+        .is_sat_constraints(&l_u_primary, &l_w_primary)
+        .map_err(|_| NovaError::StepCircuitUnsatisfiable { step: self.i + 1 })?;
+    }
+
     // fold the primary circuit's instance
     let (nifs_primary, (r_U_primary, r_W_primary)) = NIFS::prove(
       &pp.ck_primary,
@@ -398,27 +1542,36 @@ where
 
     let r_next_secondary = E2::Scalar::random(&mut OsRng);
 
-    let mut cs_secondary = SatisfyingAssignment::<E2>::new();
+    let mut cs_secondary = SatisfyingAssignment::<E2>::with_capacity(
+      pp.r1cs_shape_secondary.num_vars,
+      pp.r1cs_shape_secondary.num_io + 1,
+    );
     let inputs_secondary: NovaAugmentedCircuitInputs<E1> = NovaAugmentedCircuitInputs::new(
       pp.digest(),
       E2::Scalar::from(self.i as u64),
       vec![E2::Scalar::ZERO],
-      Some(vec![E2::Scalar::ZERO]),
+      Some(vec![self.data_commitment_chain]),
       Some(self.r_U_primary.clone()),
       Some(self.ri_secondary),
       r_next_secondary,
       Some(l_u_primary),
       Some(nifs_primary.comm_T),
-    );
+    )
+    .with_num_steps(num_steps);
 
-    let tc = TrivialCircuit::<E2::Scalar>::default();
+    let tc = pp.data_chain_circuit(data_commitment);
     let circuit_secondary: NovaAugmentedCircuit<'_, E1, _> = NovaAugmentedCircuit::new(
       false,
       Some(inputs_secondary),
       &tc,
       pp.ro_consts_circuit_secondary.clone(),
-    );
-    let _ = circuit_secondary.synthesize(&mut cs_secondary)?;
+    )
+    .with_trivial_step(pp.options.trivial_secondary)
+    .with_output_hash(pp.options.output_hash)
+    .with_folded_public_signals(pp.num_public_signals())
+    .with_steps_per_fold(pp.options.steps_per_fold, &[]);
+    let zi_secondary = circuit_secondary.synthesize(&mut cs_secondary)?;
+    let data_commitment_chain = secondary_state(&zi_secondary)?;
 
     let (l_u_secondary, l_w_secondary) = cs_secondary
       .r1cs_instance_and_witness(&pp.r1cs_shape_secondary, &pp.ck_secondary)
@@ -427,18 +1580,21 @@ where
       })?;
 
     // update the running instances and witnesses
-    self.zi = zi_primary
+    let mut zi_primary = zi_primary
       .iter()
       .map(|v| v.get_value().ok_or(SynthesisError::AssignmentMissing))
       .collect::<Result<Vec<<E1 as Engine>::Scalar>, _>>()?;
+    self.public_signals = zi_primary.split_off(pp.F_arity);
+    self.zi = zi_primary;
 
     self.l_u_secondary = l_u_secondary;
     self.l_w_secondary = l_w_secondary;
+    self.data_commitment_chain = data_commitment_chain;
 
     self.r_U_primary = r_U_primary;
     self.r_W_primary = r_W_primary;
 
-    self.i += 1;
+    self.i += num_steps;
 
     self.r_U_secondary = r_U_secondary;
     self.r_W_secondary = r_W_secondary;
@@ -446,75 +1602,211 @@ where
     self.ri_primary = r_next_primary;
     self.ri_secondary = r_next_secondary;
 
+    Ok(self.i)
+  }
+
+  /// Executes a step of the incremental computation as in `prove_step`, and then verifies the
+  /// whole computation thus far as in `verify`, which catches a folding bug at the step that
+  /// introduces it. This doubles the cost of a step, so it is meant for tests.
+  ///
+  /// Unlike `prove_step` in release builds, this always checks that the step circuit is satisfied
+  /// by its advice before folding, and returns `NovaError::StepCircuitUnsatisfiable` otherwise,
+  /// leaving `self` unchanged. Returns the error of `prove_step` or `verify`, if either fails.
+  pub fn prove_step_checked(
+    &mut self,
+    pp: &PublicParams<E1, E2, C>,
+    c: &C,
+  ) -> Result<(), NovaError> {
+    self.prove_step_inner(pp, c, None, true)?;
+    // the first call only records the step executed by `new`, after which `self.i` is one
+    let z0 = self.z0.clone();
+    self.verify(pp, self.i, &z0)?;
     Ok(())
   }
 
-  /// Verify the correctness of the `RecursiveSNARK`
-  pub fn verify(
-    &self,
+  /// Pads the incremental computation with steps of `c_noop` until `target_steps` steps have been
+  /// executed, so that the `RecursiveSNARK` verifies with `num_steps = target_steps` and the same
+  /// outputs as before padding. Returns the number of steps executed thus far.
+  ///
+  /// `c_noop` must be an instance of the step circuit that returns its input unchanged, e.g., the
+  /// circuit with a no-op flag set in its advice, so that it has the same shape as `C`.
+  /// Returns `NovaError::InvalidNumSteps` if more than `target_steps` steps were already executed,
+  /// and `NovaError::InvalidPaddingStep` if a step of `c_noop` changes the outputs, in which case
+  /// `self` is left unchanged.
+  pub fn pad_to(
+    &mut self,
     pp: &PublicParams<E1, E2, C>,
-    num_steps: usize,
-    z0: &[E1::Scalar],
-  ) -> Result<Vec<E1::Scalar>, NovaError> {
-    // number of steps cannot be zero
-    let is_num_steps_zero = num_steps == 0;
+    target_steps: usize,
+    c_noop: &C,
+  ) -> Result<usize, NovaError> {
+    if self.i > target_steps {
+      return Err(NovaError::InvalidNumSteps);
+    }
 
-    // check if the provided proof has executed num_steps
-    let is_num_steps_not_match = self.i != num_steps;
+    let snapshot = self.clone();
+    let zn = self.zi.clone();
+    while self.i < target_steps {
+      let res = self.prove_step(pp, c_noop);
+      if res.is_err() || self.zi != zn {
+        *self = snapshot;
+        return Err(res.err().unwrap_or(NovaError::InvalidPaddingStep));
+      }
+    }
 
-    // check if the initial inputs match
-    let is_inputs_not_match = self.z0 != z0;
+    Ok(self.i)
+  }
 
-    // check if the (relaxed) R1CS instances have two public outputs
-    let is_instance_has_two_outputs = self.l_u_secondary.X.len() != 2
-      || self.r_U_primary.X.len() != 2
-      || self.r_U_secondary.X.len() != 2;
+  /// Returns a checkpoint of the current step, to which `rewind_to` can later restore `self`.
+  ///
+  /// The checkpoint retains the running instances and their witnesses, so it is as large as `self`.
+  pub fn checkpoint(&self) -> RecursiveSNARKCheckpoint<E1, E2, C> {
+    RecursiveSNARKCheckpoint(self.clone())
+  }
 
-    if is_num_steps_zero
-      || is_num_steps_not_match
-      || is_inputs_not_match
-      || is_instance_has_two_outputs
-    {
-      return Err(NovaError::ProofVerifyError {
-        reason: "Invalid number of steps or inputs".to_string(),
-      });
+  /// Discards the steps executed after `checkpoint` was taken, so that subsequent calls to
+  /// `prove_step` extend the incremental computation from the checkpointed step.
+  ///
+  /// Returns `NovaError::InvalidCheckpoint` if the checkpoint is ahead of the current step, was
+  /// taken from a computation with a different initial input, or its running instances are not
+  /// the ones bound to its outputs by the hash chain, in which case `self` is left unchanged.
+  /// As in `verify_trusting_instances`, the satisfiability of the instances is not checked.
+  pub fn rewind_to(
+    &mut self,
+    pp: &PublicParams<E1, E2, C>,
+    checkpoint: &RecursiveSNARKCheckpoint<E1, E2, C>,
+  ) -> Result<(), NovaError> {
+    let invalid = |reason: String| NovaError::InvalidCheckpoint { reason };
+
+    let mut snapshot = checkpoint.0.clone();
+    if snapshot.i > self.i {
+      return Err(invalid(format!(
+        "checkpoint is at step {}, ahead of step {}",
+        snapshot.i, self.i
+      )));
+    }
+    if snapshot.z0 != self.z0 {
+      return Err(invalid(
+        "checkpoint has a different initial input".to_string(),
+      ));
     }
 
-    // check if the output hashes in R1CS instances point to the right running instances
-    let (hash_primary, hash_secondary) = {
-      let mut hasher = <E2 as Engine>::RO::new(pp.ro_consts_secondary.clone());
-      hasher.absorb(pp.digest());
-      hasher.absorb(E1::Scalar::from(num_steps as u64));
-      for e in z0 {
-        hasher.absorb(*e);
-      }
-      for e in &self.zi {
-        hasher.absorb(*e);
-      }
-      self.r_U_secondary.absorb_in_ro(&mut hasher);
-      hasher.absorb(self.ri_primary);
+    // the first call to `prove_step` only records the step executed by `new`, so a checkpoint
+    // taken before it holds the instances of the first step
+    let i = snapshot.i;
+    snapshot.i = i.max(1);
+    snapshot
+      .verify_trusting_instances(pp, snapshot.i, &self.z0)
+      .map_err(|e| invalid(format!("checkpoint is inconsistent: {e}")))?;
+    snapshot.i = i;
 
-      let mut hasher2 = <E1 as Engine>::RO::new(pp.ro_consts_primary.clone());
-      hasher2.absorb(scalar_as_base::<E1>(pp.digest()));
-      hasher2.absorb(E2::Scalar::from(num_steps as u64));
-      hasher2.absorb(E2::Scalar::ZERO);
-      hasher2.absorb(E2::Scalar::ZERO);
-      self.r_U_primary.absorb_in_ro(&mut hasher2);
-      hasher2.absorb(self.ri_secondary);
+    *self = snapshot;
+    Ok(())
+  }
 
-      (
-        hasher.squeeze(NUM_HASH_BITS),
-        hasher2.squeeze(NUM_HASH_BITS),
-      )
-    };
+  /// Synthesizes the primary circuit of the step that `prove_step` would execute next, and reports
+  /// its cost without folding it or otherwise changing `self`. This is useful to estimate the cost
+  /// of a step whose step circuit does a data-dependent amount of work before executing it.
+  ///
+  /// Before the first call to `prove_step`, this reports the cost of the base case step executed
+  /// by `new`. With public parameters created with `PublicParams::setup_with_steps_per_fold`, the
+  /// report is that of a fold of a single step, as executed by `prove_step`, in which the further
+  /// steps of the fold are synthesized but not executed.
+  ///
+  /// Returns `NovaError::StepCircuitUnsatisfiable` if the step circuit is not satisfied by its
+  /// advice, as `prove_step_checked` would, and `NovaError::PendingPrimarySteps` if steps queued by
+  /// `queue_primary_steps` are not folded yet, as `prove_step` would.
+  pub fn dry_run_step(
+    &self,
+    pp: &PublicParams<E1, E2, C>,
+    c: &C,
+  ) -> Result<StepCostReport, NovaError> {
+    if !self.pending_steps.is_empty() {
+      return Err(NovaError::PendingPrimarySteps);
+    }
 
-    if hash_primary != scalar_as_base::<E2>(self.l_u_secondary.X[0])
-      || hash_secondary != self.l_u_secondary.X[1]
-    {
-      return Err(NovaError::ProofVerifyError {
-        reason: "Invalid output hash in R1CS instances".to_string(),
-      });
+    let inputs_primary: NovaAugmentedCircuitInputs<E2> = if self.i == 0 {
+      NovaAugmentedCircuitInputs::new(
+        scalar_as_base::<E1>(pp.digest()),
+        E1::Scalar::ZERO,
+        self.z0.to_vec(),
+        None,
+        None,
+        None,
+        self.ri_primary,
+        None,
+        None,
+      )
+    } else {
+      // the cross term of the secondary folding is an input to the primary circuit
+      let (nifs_secondary, _) = NIFS::prove(
+        &pp.ck_secondary,
+        &pp.ro_consts_secondary,
+        &scalar_as_base::<E1>(pp.digest()),
+        &pp.r1cs_shape_secondary,
+        &self.r_U_secondary,
+        &self.r_W_secondary,
+        &self.l_u_secondary,
+        &self.l_w_secondary,
+      )?;
+
+      NovaAugmentedCircuitInputs::new(
+        scalar_as_base::<E1>(pp.digest()),
+        E1::Scalar::from(self.i as u64),
+        self.z0.to_vec(),
+        Some(self.zi.clone()),
+        Some(self.r_U_secondary.clone()),
+        Some(self.ri_primary),
+        E1::Scalar::random(&mut OsRng),
+        Some(self.l_u_secondary.clone()),
+        Some(nifs_secondary.comm_T),
+      )
+      .with_public_signals(self.public_signals.clone())
     }
+    // a fold of a single step, as executed by `prove_step`
+    .with_num_steps(1);
+
+    let mut cs_primary = SatisfyingAssignment::<E1>::with_capacity(
+      pp.r1cs_shape_primary.num_vars,
+      pp.r1cs_shape_primary.num_io + 1,
+    );
+    let circuit_primary: NovaAugmentedCircuit<'_, E2, C> = NovaAugmentedCircuit::new(
+      true,
+      Some(inputs_primary),
+      c,
+      pp.ro_consts_circuit_primary.clone(),
+    )
+    .with_output_hash(pp.options.output_hash)
+    .with_steps_per_fold(pp.options.steps_per_fold, &[]);
+    circuit_primary.synthesize(&mut cs_primary)?;
+
+    // check the witness against the constraints without committing to it
+    let S = &pp.r1cs_shape_primary;
+    let W = R1CSWitness::<E1>::new(S, cs_primary.aux_assignment())?;
+    let U = R1CSInstance::<E1>::new(
+      S,
+      &Commitment::<E1>::default(),
+      &cs_primary.input_assignment()[1..],
+    )?;
+    S.is_sat_constraints(&U, &W)
+      .map_err(|_| NovaError::StepCircuitUnsatisfiable { step: self.i + 1 })?;
+
+    let nonzero_witness_entries = W.W.iter().filter(|w| !bool::from(w.is_zero())).count();
+    Ok(StepCostReport {
+      num_constraints: S.num_cons,
+      num_vars: S.num_vars,
+      nonzero_witness_entries,
+      msm_size: nonzero_witness_entries + S.num_cons,
+    })
+  }
+
+  /// Verify the correctness of the `RecursiveSNARK`
+  pub fn verify(
+    &self,
+    pp: &PublicParams<E1, E2, C>,
+    num_steps: usize,
+    z0: &[E1::Scalar],
+  ) -> Result<Vec<E1::Scalar>, NovaError> {
+    self.verify_trusting_instances(pp, num_steps, z0)?;
 
     // check the satisfiability of the provided instances
     let (res_r_primary, (res_r_secondary, res_l_secondary)) = rayon::join(
@@ -550,15 +1842,414 @@ where
     Ok(self.zi.clone())
   }
 
-  /// Get the outputs after the last step of computation.
-  pub fn outputs(&self) -> &[E1::Scalar] {
-    &self.zi
-  }
+  /// Performs the checks of `verify` except for the satisfiability of the running instances and
+  /// of the last secondary instance, which dominate its cost, and returns these instances, i.e.,
+  /// the primary running instance, the secondary running instance, and the last secondary instance.
+  ///
+  /// # Trust assumption
+  ///
+  /// This only checks that the returned instances are the ones bound to `z0`, the outputs, and
+  /// `num_steps` by the hash chain. It does not check that they are satisfiable, so a successful
+  /// result proves nothing about the computation unless the instances are trusted, e.g., because
+  /// they are signed by a trusted prover, or until their satisfiability is checked separately.
+  pub fn verify_trusting_instances(
+    &self,
+    pp: &PublicParams<E1, E2, C>,
+    num_steps: usize,
+    z0: &[E1::Scalar],
+  ) -> Result<
+    (
+      RelaxedR1CSInstance<E1>,
+      RelaxedR1CSInstance<E2>,
+      R1CSInstance<E2>,
+    ),
+    NovaError,
+  > {
+    // number of steps cannot be zero
+    let is_num_steps_zero = num_steps == 0;
 
-  /// The number of steps which have been executed thus far.
+    // check if the provided proof has executed num_steps
+    let is_num_steps_not_match = self.i != num_steps;
+
+    // check if the initial inputs match, in constant time as the hashes below
+    let is_inputs_not_match = !bool::from(self.z0.as_slice().ct_eq(z0));
+
+    // check if the (relaxed) R1CS instances have two public outputs, followed by the public
+    // signals for those of the primary circuit
+    let is_instance_has_two_outputs = self.l_u_secondary.X.len() != 2
+      || self.r_U_primary.X.len() != pp.r1cs_shape_primary.num_io
+      || self.r_U_primary.X.len() != 2 + self.public_signals.len()
+      || self.r_U_secondary.X.len() != 2;
+
+    if is_num_steps_zero
+      || is_num_steps_not_match
+      || is_inputs_not_match
+      || is_instance_has_two_outputs
+    {
+      return Err(NovaError::ProofVerifyError {
+        reason: "Invalid number of steps or inputs".to_string(),
+      });
+    }
+
+    // check if the output hashes in R1CS instances point to the right running instances
+    let (hash_primary, hash_secondary) = {
+      let (inputs, inputs2) = self.hash_inputs(pp, num_steps);
+
+      let mut hasher = <E2 as Engine>::RO::new(pp.ro_consts_secondary.clone());
+      hasher.absorb_batch(&inputs);
+
+      let mut hasher2 = <E1 as Engine>::RO::new(pp.ro_consts_primary.clone());
+      hasher2.absorb_batch(&inputs2);
+
+      (
+        hasher.squeeze(NUM_HASH_BITS),
+        hasher2.squeeze(NUM_HASH_BITS),
+      )
+    };
+
+    // compare both hashes in constant time, so that the timing does not reveal which one differs
+    let is_hash_match = hash_primary.ct_eq(&scalar_as_base::<E2>(self.l_u_secondary.X[0]))
+      & hash_secondary.ct_eq(&self.l_u_secondary.X[1]);
+    if !bool::from(is_hash_match) {
+      return Err(NovaError::ProofVerifyError {
+        reason: "Invalid output hash in R1CS instances".to_string(),
+      });
+    }
+
+    Ok((
+      self.r_U_primary.clone(),
+      self.r_U_secondary.clone(),
+      self.l_u_secondary.clone(),
+    ))
+  }
+
+  // the elements absorbed by the two hashes that `verify` checks against the last secondary instance
+  fn hash_inputs(
+    &self,
+    pp: &PublicParams<E1, E2, C>,
+    num_steps: usize,
+  ) -> (Vec<E1::Scalar>, Vec<E2::Scalar>) {
+    let mut inputs = vec![pp.digest(), E1::Scalar::from(num_steps as u64)];
+    inputs.extend_from_slice(&self.z0);
+    if pp.options.output_hash {
+      inputs.push(state_hash::<E2>(&pp.ro_consts_secondary, &self.zi));
+    } else {
+      inputs.extend_from_slice(&self.zi);
+    }
+    inputs.extend_from_slice(&self.public_signals);
+    inputs.extend(self.r_U_secondary.ro_elements());
+    inputs.push(self.ri_primary);
+
+    let mut inputs2 = vec![
+      scalar_as_base::<E1>(pp.digest()),
+      E2::Scalar::from(num_steps as u64),
+    ];
+    if !pp.options.trivial_secondary {
+      inputs2.push(E2::Scalar::ZERO);
+      if pp.options.output_hash {
+        inputs2.push(state_hash::<E1>(
+          &pp.ro_consts_primary,
+          &[self.data_commitment_chain],
+        ));
+      } else {
+        inputs2.push(self.data_commitment_chain);
+      }
+    }
+    inputs2.extend(self.r_U_primary.ro_elements());
+    inputs2.push(self.ri_secondary);
+
+    (inputs, inputs2)
+  }
+
+  /// Returns the canonical byte encoding of every element that `verify` absorbs into its hashes,
+  /// in the order it absorbs them: first the elements of the hash over the primary running
+  /// instance's side, then those of the hash over the secondary side.
+  ///
+  /// This is meant for debugging hash mismatches across versions or platforms, since two
+  /// verifiers that disagree on the result of `verify` necessarily disagree on these bytes.
+  pub fn transcript_bytes(&self, pp: &PublicParams<E1, E2, C>) -> Vec<u8> {
+    let (inputs, inputs2) = self.hash_inputs(pp, self.i);
+    inputs
+      .iter()
+      .flat_map(|e| e.to_repr().as_ref().to_vec())
+      .chain(inputs2.iter().flat_map(|e| e.to_repr().as_ref().to_vec()))
+      .collect()
+  }
+
+  /// Produces a human-readable report of the checks that `verify` performs on the `RecursiveSNARK`
+  /// against its own number of steps and initial inputs, stating whether each check passes instead
+  /// of stopping at the first failure. This is meant for investigating a proof that unexpectedly
+  /// fails to verify.
+  ///
+  /// The report contains the number of steps, the inputs and outputs, hashes of the commitments of
+  /// the instances, and the expected and actual values of the hash chain, but no witness values.
+  pub fn diagnostic_report(&self, pp: &PublicParams<E1, E2, C>) -> String {
+    fn hex(bytes: &[u8]) -> String {
+      bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+    fn scalar_hex<F: PrimeField>(s: &F) -> String {
+      format!("0x{}", hex(s.to_repr().as_ref()))
+    }
+    fn comm_hash<E: Engine>(comm: &Commitment<E>) -> String {
+      let (x, y, is_infinity) = comm.to_coordinates();
+      let mut hasher = Sha3_256::new();
+      hasher.update(x.to_repr().as_ref());
+      hasher.update(y.to_repr().as_ref());
+      hasher.update([is_infinity as u8]);
+      format!("0x{}", hex(&hasher.finalize()))
+    }
+    fn check(name: &str, res: Result<(), String>) -> String {
+      match res {
+        Ok(()) => format!("check {name}: pass\n"),
+        Err(reason) => format!("check {name}: FAIL ({reason})\n"),
+      }
+    }
+    let scalars_hex = |z: &[E1::Scalar]| {
+      let z = z.iter().map(scalar_hex).collect::<Vec<_>>();
+      format!("[{}]", z.join(", "))
+    };
+
+    let mut report = String::from("RecursiveSNARK diagnostic report\n");
+    report += &format!("steps: {}\n", self.i);
+    report += &format!("z0: {}\n", scalars_hex(&self.z0));
+    report += &format!("zi: {}\n", scalars_hex(&self.zi));
+    report += &format!(
+      "primary running instance: comm_W {}, comm_E {}\n",
+      comm_hash::<E1>(&self.r_U_primary.comm_W),
+      comm_hash::<E1>(&self.r_U_primary.comm_E)
+    );
+    report += &format!(
+      "secondary running instance: comm_W {}, comm_E {}\n",
+      comm_hash::<E2>(&self.r_U_secondary.comm_W),
+      comm_hash::<E2>(&self.r_U_secondary.comm_E)
+    );
+    report += &format!(
+      "last secondary instance: comm_W {}\n",
+      comm_hash::<E2>(&self.l_u_secondary.comm_W)
+    );
+
+    report += &check(
+      "number of steps",
+      (self.i > 0)
+        .then_some(())
+        .ok_or_else(|| "no step was executed".to_string()),
+    );
+    let has_two_outputs = self.l_u_secondary.X.len() == 2
+      && self.r_U_primary.X.len() == 2 + self.public_signals.len()
+      && self.r_U_secondary.X.len() == 2;
+    report += &check(
+      "instance outputs",
+      has_two_outputs
+        .then_some(())
+        .ok_or_else(|| "an instance does not have two public outputs".to_string()),
+    );
+
+    // the hash chain can only be compared if the last secondary instance has the outputs to compare
+    if has_two_outputs {
+      let (inputs, inputs2) = self.hash_inputs(pp, self.i);
+      let mut hasher = <E2 as Engine>::RO::new(pp.ro_consts_secondary.clone());
+      hasher.absorb_batch(&inputs);
+      let mut hasher2 = <E1 as Engine>::RO::new(pp.ro_consts_primary.clone());
+      hasher2.absorb_batch(&inputs2);
+
+      let chain = |name: &str, expected: String, actual: String| {
+        let status = if expected == actual { "pass" } else { "FAIL" };
+        format!("check {name}: {status} (expected {expected}, actual {actual})\n")
+      };
+      report += &chain(
+        "primary hash chain",
+        scalar_hex(&hasher.squeeze(NUM_HASH_BITS)),
+        scalar_hex(&scalar_as_base::<E2>(self.l_u_secondary.X[0])),
+      );
+      report += &chain(
+        "secondary hash chain",
+        scalar_hex(&hasher2.squeeze(NUM_HASH_BITS)),
+        scalar_hex(&self.l_u_secondary.X[1]),
+      );
+    }
+
+    let unsat = |e: NovaError| e.to_string();
+    report += &check(
+      "primary running instance satisfiable",
+      pp.r1cs_shape_primary
+        .is_sat_relaxed(&pp.ck_primary, &self.r_U_primary, &self.r_W_primary)
+        .map_err(unsat),
+    );
+    report += &check(
+      "secondary running instance satisfiable",
+      pp.r1cs_shape_secondary
+        .is_sat_relaxed(&pp.ck_secondary, &self.r_U_secondary, &self.r_W_secondary)
+        .map_err(unsat),
+    );
+    report += &check(
+      "last secondary instance satisfiable",
+      pp.r1cs_shape_secondary
+        .is_sat(&pp.ck_secondary, &self.l_u_secondary, &self.l_w_secondary)
+        .map_err(unsat),
+    );
+
+    report
+  }
+
+  /// Get the outputs after the last step of computation.
+  pub fn outputs(&self) -> &[E1::Scalar] {
+    &self.zi
+  }
+
+  /// Get the public signals exposed by the last step of computation, as declared by
+  /// `StepCircuit::num_public_signals`. They are public inputs of the primary circuit, and like the
+  /// outputs, they are bound by `verify` through the hash of the running instances.
+  pub fn public_signals(&self) -> &[E1::Scalar] {
+    &self.public_signals
+  }
+
+  /// Get the outputs after the last step of computation, decoded as an application state.
+  pub fn typed_outputs<S: IvcState<E1::Scalar>>(&self) -> Result<S, StateError> {
+    S::from_field_vec(&self.zi)
+  }
+
+  /// The number of steps which have been executed thus far.
   pub fn num_steps(&self) -> usize {
     self.i
   }
+
+  /// Returns the hash chain of the data commitments absorbed by `prove_step_with_data` thus far,
+  /// which `verify` checks against the running instances. It is zero unless the public parameters
+  /// were created with `PublicParams::setup_with_data_chain`.
+  pub fn data_commitment_chain(&self) -> E2::Scalar {
+    self.data_commitment_chain
+  }
+}
+
+impl<E1, E2> RecursiveSNARK<E1, E2, TransitionCircuit<E1::Scalar>>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+{
+  /// Creates a recursive SNARK for `num_steps` steps of the transition function built with the
+  /// expressions of `gadgets::dsl`, starting from `z0`.
+  pub fn from_transition(
+    pp: &PublicParams<E1, E2, TransitionCircuit<E1::Scalar>>,
+    transition: &TransitionCircuit<E1::Scalar>,
+    z0: &[E1::Scalar],
+    num_steps: usize,
+  ) -> Result<Self, NovaError> {
+    let mut recursive_snark = Self::new(pp, transition, z0)?;
+    for _i in 0..num_steps {
+      recursive_snark.prove_step(pp, transition)?;
+    }
+    Ok(recursive_snark)
+  }
+}
+
+/// A snapshot of a `RecursiveSNARK` at some step, produced by `RecursiveSNARK::checkpoint`
+/// and consumed by `RecursiveSNARK::rewind_to`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct RecursiveSNARKCheckpoint<E1, E2, C>(RecursiveSNARK<E1, E2, C>)
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>;
+
+impl<E1, E2, C> RecursiveSNARKCheckpoint<E1, E2, C>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+{
+  /// The number of steps which had been executed when the checkpoint was taken
+  pub fn num_steps(&self) -> usize {
+    self.0.i
+  }
+
+  /// The outputs of the last step executed when the checkpoint was taken
+  pub fn outputs(&self) -> &[E1::Scalar] {
+    &self.0.zi
+  }
+}
+
+/// A compact binary encoding of a `RecursiveSNARK`.
+///
+/// Converting it back into a `RecursiveSNARK` with `try_from` validates the structure of the
+/// decoded proof, so that a corrupted or maliciously-crafted encoding is rejected at the type boundary
+/// rather than at `verify`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecursiveSNARKBytes(Vec<u8>);
+
+impl RecursiveSNARKBytes {
+  /// Wraps the provided bytes, which are validated only when converted into a `RecursiveSNARK`
+  pub fn new(bytes: Vec<u8>) -> Self {
+    Self(bytes)
+  }
+
+  /// Returns the underlying bytes
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.0
+  }
+}
+
+impl<E1, E2, C> From<&RecursiveSNARK<E1, E2, C>> for RecursiveSNARKBytes
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+{
+  fn from(recursive_snark: &RecursiveSNARK<E1, E2, C>) -> Self {
+    let bytes = bincode::DefaultOptions::new()
+      .with_little_endian()
+      .with_fixint_encoding()
+      .serialize(recursive_snark)
+      .expect("Failure in serializing RecursiveSNARK");
+    Self(bytes)
+  }
+}
+
+impl<E1, E2, C> TryFrom<RecursiveSNARKBytes> for RecursiveSNARK<E1, E2, C>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+{
+  type Error = NovaError;
+
+  fn try_from(bytes: RecursiveSNARKBytes) -> Result<Self, Self::Error> {
+    let invalid = |reason: &str| NovaError::InvalidRecursiveSNARK {
+      reason: reason.to_string(),
+    };
+
+    let recursive_snark: Self = bincode::DefaultOptions::new()
+      .with_little_endian()
+      .with_fixint_encoding()
+      .with_limit(bytes.0.len() as u64)
+      .deserialize(&bytes.0)
+      .map_err(|e| NovaError::InvalidRecursiveSNARK {
+        reason: e.to_string(),
+      })?;
+
+    if recursive_snark.zi.len() != recursive_snark.z0.len() {
+      return Err(invalid("zi and z0 have different lengths"));
+    }
+
+    if recursive_snark.l_u_secondary.X.len() != 2
+      || recursive_snark.r_U_primary.X.len() != 2 + recursive_snark.public_signals.len()
+      || recursive_snark.r_U_secondary.X.len() != 2
+    {
+      return Err(invalid("R1CS instances do not have two public outputs"));
+    }
+
+    // the primary running witness is set in the base case, while the secondary one
+    // starts accumulating only once the second step is proven
+    let is_default = |W: &[E1::Scalar]| W.iter().all(|w| bool::from(w.is_zero()));
+    let is_default_secondary = |W: &[E2::Scalar]| W.iter().all(|w| bool::from(w.is_zero()));
+    if (recursive_snark.i > 0 && is_default(&recursive_snark.r_W_primary.W))
+      || (recursive_snark.i > 1 && is_default_secondary(&recursive_snark.r_W_secondary.W))
+    {
+      return Err(invalid("running witnesses are not set after the base case"));
+    }
+
+    Ok(recursive_snark)
+  }
 }
 
 /// A type that holds the prover key for `CompressedSNARK`
@@ -596,9 +2287,58 @@ where
   vk_secondary: S2::VerifierKey,
   dk_primary: DerandKey<E1>,
   dk_secondary: DerandKey<E2>,
+  #[serde(default)]
+  trivial_secondary: bool,
+  #[serde(default)]
+  output_hash: bool,
   _p: PhantomData<C>,
 }
 
+impl<E1, E2, C, S1, S2> VerifierKey<E1, E2, C, S1, S2>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<E2>,
+{
+  /// Returns the constants of the ROs used to hash the running instances of the primary and
+  /// secondary circuits, which match those of the `PublicParams` the key was produced from.
+  ///
+  /// The prover and the verifier must use the same constants, or proofs will not verify.
+  pub const fn ro_constants(&self) -> (&ROConstants<E1>, &ROConstants<E2>) {
+    (&self.ro_consts_primary, &self.ro_consts_secondary)
+  }
+
+  /// Precomputes tables for the multi-scalar multiplications with the fixed bases of the
+  /// verifier's commitment keys, which `CompressedSNARK::verify` then uses. This speeds up the
+  /// verification of many proofs with the same key, without changing its result.
+  ///
+  /// The tables take several times the memory of the commitment keys, and are not serialized.
+  pub fn precompute_msm_tables(&mut self) {
+    S1::precompute_msm_tables(&mut self.vk_primary);
+    S2::precompute_msm_tables(&mut self.vk_secondary);
+  }
+}
+
+// (de)serializes a field that is only `None` in values decoded from an older layout, which cannot
+// be serialized in the current layout
+fn serialize_required<S: Serializer, T: Serialize>(
+  value: &Option<T>,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  value
+    .as_ref()
+    .ok_or_else(|| S::Error::custom("a required field is missing"))?
+    .serialize(serializer)
+}
+
+fn deserialize_required<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+  deserializer: D,
+) -> Result<Option<T>, D::Error> {
+  T::deserialize(deserializer).map(Some)
+}
+
 /// A SNARK that proves the knowledge of a valid `RecursiveSNARK`
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(bound = "")]
@@ -633,6 +2373,18 @@ where
 
   zn: Vec<E1::Scalar>,
 
+  // fields absent from older layouts, see `deserialize_compat`; the commitment to `z0` is only
+  // `None` in a proof decoded from such a layout, and is required in the current one
+  #[serde(
+    serialize_with = "serialize_required",
+    deserialize_with = "deserialize_required"
+  )]
+  z0_commitment: Option<E1::Scalar>,
+  #[serde(default)]
+  data_commitment_chain: E2::Scalar,
+  #[serde(default)]
+  public_signals: Vec<E1::Scalar>,
+
   _p: PhantomData<C>,
 }
 
@@ -666,6 +2418,8 @@ where
       vk_secondary,
       dk_primary: E1::CE::derand_key(&pp.ck_primary),
       dk_secondary: E2::CE::derand_key(&pp.ck_secondary),
+      trivial_secondary: pp.options.trivial_secondary,
+      output_hash: pp.options.output_hash,
       _p: Default::default(),
     };
 
@@ -677,11 +2431,40 @@ where
     pp: &PublicParams<E1, E2, C>,
     pk: &ProverKey<E1, E2, C, S1, S2>,
     recursive_snark: &RecursiveSNARK<E1, E2, C>,
+  ) -> Result<Self, NovaError> {
+    Self::prove_with_rng(pp, pk, recursive_snark, &mut OsRng)
+  }
+
+  /// Create a new `CompressedSNARK` whose randomness (the sampled random instances
+  /// and all blinds) is derived from `seed`, so that the same inputs and seed
+  /// always produce byte-identical proofs.
+  ///
+  /// This is intended for reproducible tests and fixtures: anyone who knows the seed
+  /// can recover the blinds, so such a proof must not be relied on for zero-knowledge.
+  pub fn prove_deterministic(
+    pp: &PublicParams<E1, E2, C>,
+    pk: &ProverKey<E1, E2, C, S1, S2>,
+    recursive_snark: &RecursiveSNARK<E1, E2, C>,
+    seed: [u8; 32],
+  ) -> Result<Self, NovaError> {
+    Self::prove_with_rng(
+      pp,
+      pk,
+      recursive_snark,
+      &mut rand_chacha::ChaCha20Rng::from_seed(seed),
+    )
+  }
+
+  fn prove_with_rng(
+    pp: &PublicParams<E1, E2, C>,
+    pk: &ProverKey<E1, E2, C, S1, S2>,
+    recursive_snark: &RecursiveSNARK<E1, E2, C>,
+    rng: &mut impl RngCore,
   ) -> Result<Self, NovaError> {
     // prove three foldings
 
     // fold secondary U/W with secondary u/w to get Uf/Wf
-    let (nifs_Uf_secondary, (r_Uf_secondary, r_Wf_secondary)) = NIFS::prove(
+    let (nifs_Uf_secondary, (r_Uf_secondary, r_Wf_secondary)) = NIFS::prove_with_rng(
       &pp.ck_secondary,
       &pp.ro_consts_secondary,
       &scalar_as_base::<E1>(pp.digest()),
@@ -690,12 +2473,13 @@ where
       &recursive_snark.r_W_secondary,
       &recursive_snark.l_u_secondary,
       &recursive_snark.l_w_secondary,
+      &mut *rng,
     )?;
 
     // fold Uf/Wf with random inst/wit to get U1/W1
     let (l_ur_secondary, l_wr_secondary) = pp
       .r1cs_shape_secondary
-      .sample_random_instance_witness(&pp.ck_secondary)?;
+      .sample_random_instance_witness_with_rng(&pp.ck_secondary, &mut *rng)?;
 
     let (nifs_Un_secondary, (r_Un_secondary, r_Wn_secondary)) = NIFSRelaxed::prove(
       &pp.ck_secondary,
@@ -706,12 +2490,13 @@ where
       &r_Wf_secondary,
       &l_ur_secondary,
       &l_wr_secondary,
+      &mut *rng,
     )?;
 
     // fold primary U/W with random inst/wit to get U2/W2
     let (l_ur_primary, l_wr_primary) = pp
       .r1cs_shape_primary
-      .sample_random_instance_witness(&pp.ck_primary)?;
+      .sample_random_instance_witness_with_rng(&pp.ck_primary, &mut *rng)?;
 
     let (nifs_Un_primary, (r_Un_primary, r_Wn_primary)) = NIFSRelaxed::prove(
       &pp.ck_primary,
@@ -722,6 +2507,7 @@ where
       &recursive_snark.r_W_primary,
       &l_ur_primary,
       &l_wr_primary,
+      &mut *rng,
     )?;
 
     // derandomize/unblind commitments
@@ -786,18 +2572,158 @@ where
       snark_secondary: snark_secondary?,
 
       zn: recursive_snark.zi.clone(),
+      z0_commitment: Some(Self::commit_z0(
+        &pp.ro_consts_secondary,
+        pp.digest(),
+        &recursive_snark.z0,
+      )),
+      data_commitment_chain: recursive_snark.data_commitment_chain,
+      public_signals: recursive_snark.public_signals.clone(),
 
       _p: Default::default(),
     })
   }
 
+  /// Hashes the initial input `z0`, prefixed with its length, together with the digest of the
+  /// public parameters
+  fn commit_z0(
+    ro_consts: &ROConstants<E2>,
+    pp_digest: E1::Scalar,
+    z0: &[E1::Scalar],
+  ) -> E1::Scalar {
+    let mut hasher = <E2 as Engine>::RO::new(ro_consts.clone());
+    hasher.absorb(pp_digest);
+    hasher.absorb(E1::Scalar::from(z0.len() as u64));
+    for e in z0 {
+      hasher.absorb(*e);
+    }
+    hasher.squeeze(NUM_HASH_BITS)
+  }
+
+  /// Returns the commitment to the initial input `z0` carried by the proof, which is a hash of the
+  /// digest of the public parameters and `z0`, or `None` for a proof decoded by `deserialize_compat`
+  /// from a layout without it.
+  ///
+  /// The commitment is supplied by the prover, and `verify` rejects the proof unless it is the
+  /// commitment to the `z0` supplied by the verifier, so a verified proof is bound to it. A proof
+  /// in the current layout cannot be decoded or serialized without the commitment.
+  pub fn z0_commitment(&self) -> Option<E1::Scalar> {
+    self.z0_commitment
+  }
+
+  /// Returns true if `self` compresses `recursive_snark`, i.e., if it embeds the same running
+  /// instances, last secondary instance, and outputs.
+  ///
+  /// This only compares the two proofs, e.g., to detect a mismatch before publishing one of them,
+  /// and does not verify either of them.
+  pub fn attests(&self, recursive_snark: &RecursiveSNARK<E1, E2, C>) -> bool {
+    self.r_U_primary == recursive_snark.r_U_primary
+      && self.r_U_secondary == recursive_snark.r_U_secondary
+      && self.l_u_secondary == recursive_snark.l_u_secondary
+      && self.zn == recursive_snark.zi
+      && self.public_signals == recursive_snark.public_signals
+  }
+
+  /// Returns the public signals of the last step attested by the proof,
+  /// as in `RecursiveSNARK::public_signals`, which are bound by `verify`
+  pub fn public_signals(&self) -> &[E1::Scalar] {
+    &self.public_signals
+  }
+
+  /// Returns the hash chain of the data commitments attested by the proof,
+  /// as in `RecursiveSNARK::data_commitment_chain`
+  pub fn data_commitment_chain(&self) -> E2::Scalar {
+    self.data_commitment_chain
+  }
+
+  /// Checks that the supplied `z0` matches the one committed to in the proof,
+  /// without running the (more expensive) verification of the proof itself.
+  ///
+  /// Returns `NovaError::InputMismatch` if `z0` does not match, and
+  /// `NovaError::InvalidCompressedSNARK` if the proof carries no commitment, i.e., if it was
+  /// decoded by `deserialize_compat` from a layout without it. The `z0` of such a proof is only
+  /// checked by `verify`, through the hashes of the running instances.
+  pub fn check_z0(
+    &self,
+    vk: &VerifierKey<E1, E2, C, S1, S2>,
+    z0: &[E1::Scalar],
+  ) -> Result<(), NovaError> {
+    let comm = self
+      .z0_commitment
+      .ok_or_else(|| NovaError::InvalidCompressedSNARK {
+        reason: "proof carries no commitment to z0".to_string(),
+      })?;
+    if !bool::from(Self::commit_z0(&vk.ro_consts_secondary, vk.pp_digest, z0).ct_eq(&comm)) {
+      return Err(NovaError::InputMismatch);
+    }
+    Ok(())
+  }
+
   /// Verify the correctness of the `CompressedSNARK` (provides zero-knowledge)
+  ///
+  /// Returns `NovaError::InputMismatch` early if `z0` does not match the one the prover committed to.
   pub fn verify(
     &self,
     vk: &VerifierKey<E1, E2, C, S1, S2>,
     num_steps: usize,
     z0: &[E1::Scalar],
   ) -> Result<Vec<E1::Scalar>, NovaError> {
+    // the circuits absorb either the outputs, or their hashes
+    if vk.output_hash {
+      let (hash_primary, hash_secondary) = self.output_hash(vk);
+      self.verify_outputs(vk, num_steps, z0, &[hash_primary], hash_secondary)?;
+    } else {
+      self.verify_outputs(vk, num_steps, z0, &self.zn, self.data_commitment_chain)?;
+    }
+
+    Ok(self.zn.clone())
+  }
+
+  /// Returns the hashes of the outputs of the primary and secondary circuits, which the augmented
+  /// circuits absorb in place of the outputs when the public parameters are set up with
+  /// `SetupOptions::with_output_hash`. The hashes are computed with the RO constants of `vk`.
+  ///
+  /// The output of the secondary circuit is the chain of data commitments, see
+  /// `data_commitment_chain`.
+  pub fn output_hash(&self, vk: &VerifierKey<E1, E2, C, S1, S2>) -> (E1::Scalar, E2::Scalar) {
+    (
+      state_hash::<E2>(&vk.ro_consts_secondary, &self.zn),
+      state_hash::<E1>(&vk.ro_consts_primary, &[self.data_commitment_chain]),
+    )
+  }
+
+  /// Verify the correctness of the `CompressedSNARK` as in `verify`, against the claimed hashes
+  /// `output_hash` of the outputs of the primary and secondary circuits rather than the outputs
+  /// carried by the proof, so that the verifier does not need the outputs.
+  ///
+  /// The verifier key must be produced from public parameters set up with
+  /// `SetupOptions::with_output_hash`, whose augmented circuits absorb the hashes.
+  pub fn verify_against_output_hash(
+    &self,
+    vk: &VerifierKey<E1, E2, C, S1, S2>,
+    num_steps: usize,
+    z0: &[E1::Scalar],
+    output_hash: (E1::Scalar, E2::Scalar),
+  ) -> Result<(), NovaError> {
+    if !vk.output_hash {
+      return Err(NovaError::ProofVerifyError {
+        reason: "the circuits do not absorb the hashes of their outputs".to_string(),
+      });
+    }
+
+    self.verify_outputs(vk, num_steps, z0, &[output_hash.0], output_hash.1)
+  }
+
+  // verifies the proof given the elements the circuits absorb for their outputs, i.e., the
+  // outputs `zn` and the data commitment chain, or their hashes
+  fn verify_outputs(
+    &self,
+    vk: &VerifierKey<E1, E2, C, S1, S2>,
+    num_steps: usize,
+    z0: &[E1::Scalar],
+    zn: &[E1::Scalar],
+    data_commitment_chain: E2::Scalar,
+  ) -> Result<(), NovaError> {
     // the number of steps cannot be zero
     if num_steps == 0 {
       return Err(NovaError::ProofVerifyError {
@@ -805,11 +2731,19 @@ where
       });
     }
 
-    // check if the (relaxed) R1CS instances have two public outputs
+    // check that the initial input is the one committed to by the prover, unless the proof was
+    // decoded from a layout without the commitment
+    if self.z0_commitment.is_some() {
+      self.check_z0(vk, z0)?;
+    }
+
+    // check if the (relaxed) R1CS instances have two public outputs, followed by the public
+    // signals for those of the primary circuit
+    let num_io_primary = 2 + self.public_signals.len();
     if self.l_u_secondary.X.len() != 2
-      || self.r_U_primary.X.len() != 2
+      || self.r_U_primary.X.len() != num_io_primary
       || self.r_U_secondary.X.len() != 2
-      || self.l_ur_primary.X.len() != 2
+      || self.l_ur_primary.X.len() != num_io_primary
       || self.l_ur_secondary.X.len() != 2
     {
       return Err(NovaError::ProofVerifyError {
@@ -822,20 +2756,19 @@ where
       let mut hasher = <E2 as Engine>::RO::new(vk.ro_consts_secondary.clone());
       hasher.absorb(vk.pp_digest);
       hasher.absorb(E1::Scalar::from(num_steps as u64));
-      for e in z0 {
-        hasher.absorb(*e);
-      }
-      for e in &self.zn {
-        hasher.absorb(*e);
-      }
+      hasher.absorb_batch(z0);
+      hasher.absorb_batch(zn);
+      hasher.absorb_batch(&self.public_signals);
       self.r_U_secondary.absorb_in_ro(&mut hasher);
       hasher.absorb(self.ri_primary);
 
       let mut hasher2 = <E1 as Engine>::RO::new(vk.ro_consts_primary.clone());
       hasher2.absorb(scalar_as_base::<E1>(vk.pp_digest));
       hasher2.absorb(E2::Scalar::from(num_steps as u64));
-      hasher2.absorb(E2::Scalar::ZERO);
-      hasher2.absorb(E2::Scalar::ZERO);
+      if !vk.trivial_secondary {
+        hasher2.absorb(E2::Scalar::ZERO);
+        hasher2.absorb(data_commitment_chain);
+      }
       self.r_U_primary.absorb_in_ro(&mut hasher2);
       hasher2.absorb(self.ri_secondary);
 
@@ -845,9 +2778,10 @@ where
       )
     };
 
-    if hash_primary != base_as_scalar::<E1>(self.l_u_secondary.X[0])
-      || hash_secondary != self.l_u_secondary.X[1]
-    {
+    // compare both hashes in constant time, as in `RecursiveSNARK::verify`
+    let is_hash_match = hash_primary.ct_eq(&base_as_scalar::<E1>(self.l_u_secondary.X[0]))
+      & hash_secondary.ct_eq(&self.l_u_secondary.X[1]);
+    if !bool::from(is_hash_match) {
       return Err(NovaError::ProofVerifyError {
         reason: "Invalid output hash in R1CS instances".to_string(),
       });
@@ -907,24 +2841,66 @@ where
     res_primary?;
     res_secondary?;
 
-    Ok(self.zn.clone())
+    Ok(())
   }
-}
 
-#[cfg(test)]
-mod tests {
-  use super::*;
-  use crate::{
-    frontend::{num::AllocatedNum, ConstraintSystem, SynthesisError},
-    provider::{
-      pedersen::CommitmentKeyExtTrait, traits::DlogGroup, Bn256EngineIPA, Bn256EngineKZG,
-      GrumpkinEngine, PallasEngine, Secp256k1Engine, Secq256k1Engine, VestaEngine,
-    },
-    traits::{circuit::TrivialCircuit, evaluation::EvaluationEngineTrait, snark::default_ck_hint},
-  };
-  use core::{fmt::Write, marker::PhantomData};
-  use expect_test::{expect, Expect};
-  use ff::PrimeField;
+  /// Verifies a batch of independent proofs against the same verifier key in parallel, where each
+  /// entry of `proofs` holds a proof along with the number of steps and the initial input to verify
+  /// it with as in `verify`.
+  ///
+  /// Returns the result of `verify` for each proof, in the order of `proofs`, so that an invalid
+  /// proof does not prevent the others from being verified.
+  pub fn verify_batch(
+    vk: &VerifierKey<E1, E2, C, S1, S2>,
+    proofs: &[(Self, usize, Vec<E1::Scalar>)],
+  ) -> Vec<Result<Vec<E1::Scalar>, NovaError>> {
+    proofs
+      .par_iter()
+      .map(|(proof, num_steps, z0)| proof.verify(vk, *num_steps, z0))
+      .collect()
+  }
+
+  /// Verify the correctness of the `CompressedSNARK` as in `verify`, after checking that the digest
+  /// of the public parameters the verifier key was produced from is included in a published
+  /// set of digests, committed to by the Merkle `root`.
+  ///
+  /// The leaf is the canonical byte representation of the digest, as returned by `to_repr`.
+  pub fn verify_with_inclusion(
+    &self,
+    vk: &VerifierKey<E1, E2, C, S1, S2>,
+    num_steps: usize,
+    z0: &[E1::Scalar],
+    merkle_proof: &MerkleProof,
+    root: &[u8; 32],
+  ) -> Result<Vec<E1::Scalar>, NovaError> {
+    if !merkle_proof.verify(vk.pp_digest.to_repr().as_ref(), root) {
+      return Err(NovaError::ProofVerifyError {
+        reason: "Digest of public parameters is not included under the Merkle root".to_string(),
+      });
+    }
+
+    self.verify(vk, num_steps, z0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    frontend::{num::AllocatedNum, AllocatedBit, Boolean, ConstraintSystem, SynthesisError},
+    gadgets::merkle::merkle_root,
+    provider::{
+      pedersen::CommitmentKeyExtTrait,
+      poseidon::{PoseidonConstantsCircuit, PoseidonRO},
+      traits::DlogGroup,
+      Bn256EngineIPA, Bn256EngineKZG, GrumpkinEngine, Keccak256Transcript, PallasEngine,
+      PedersenCommitmentEngine, Secp256k1Engine, Secq256k1Engine, VestaEngine,
+    },
+    traits::{circuit::TrivialCircuit, evaluation::EvaluationEngineTrait, snark::default_ck_hint},
+  };
+  use core::{fmt::Write, marker::PhantomData};
+  use expect_test::{expect, Expect};
+  use ff::{PrimeField, PrimeFieldBits};
 
   type EE<E> = crate::provider::ipa_pc::EvaluationEngine<E>;
   type EEPrime<E> = crate::provider::hyperkzg::EvaluationEngine<E>;
@@ -1062,6 +3038,171 @@ mod tests {
     test_ivc_trivial_with::<Secp256k1Engine, Secq256k1Engine>();
   }
 
+  // an insecure RO that hashes the absorbed elements to their sum offset by its constant, which
+  // is only meant to check that the RO of an engine can be swapped
+  #[derive(Clone, Default, Serialize, Deserialize)]
+  struct SumROConstants(u64);
+
+  struct SumRO<Base: PrimeField> {
+    state: Vec<Base>,
+    constants: SumROConstants,
+  }
+
+  impl<Base: PrimeFieldBits> ROTrait<Base> for SumRO<Base> {
+    type CircuitRO = SumROCircuit<Base>;
+    type Constants = SumROConstants;
+
+    fn new(constants: SumROConstants) -> Self {
+      Self {
+        state: Vec::new(),
+        constants,
+      }
+    }
+
+    fn absorb(&mut self, e: Base) {
+      self.state.push(e);
+    }
+
+    fn squeeze(&mut self, num_bits: usize) -> Base {
+      let hash = self.state.iter().sum::<Base>() + Base::from(self.constants.0);
+      self.state = vec![hash];
+      let bits = hash.to_le_bits();
+      bits[..num_bits].iter().rev().fold(Base::ZERO, |acc, bit| {
+        acc.double() + Base::from(*bit as u64)
+      })
+    }
+  }
+
+  struct SumROCircuit<Base: PrimeField> {
+    state: Vec<AllocatedNum<Base>>,
+    constants: SumROConstants,
+  }
+
+  impl<Base: PrimeFieldBits> ROCircuitTrait<Base> for SumROCircuit<Base> {
+    type NativeRO = SumRO<Base>;
+    type Constants = SumROConstants;
+
+    fn new(constants: SumROConstants) -> Self {
+      Self {
+        state: Vec::new(),
+        constants,
+      }
+    }
+
+    fn absorb(&mut self, e: &AllocatedNum<Base>) {
+      self.state.push(e.clone());
+    }
+
+    fn squeeze<CS: ConstraintSystem<Base>>(
+      &mut self,
+      mut cs: CS,
+      num_bits: usize,
+    ) -> Result<Vec<AllocatedBit>, SynthesisError> {
+      let offset = Base::from(self.constants.0);
+      let hash = AllocatedNum::alloc(cs.namespace(|| "hash"), || {
+        self.state.iter().try_fold(offset, |acc, e| {
+          e.get_value()
+            .map(|e| acc + e)
+            .ok_or(SynthesisError::AssignmentMissing)
+        })
+      })?;
+      cs.enforce(
+        || "hash = offset + sum of state",
+        |lc| {
+          self
+            .state
+            .iter()
+            .fold(lc + (offset, CS::one()), |lc, e| lc + e.get_variable())
+        },
+        |lc| lc + CS::one(),
+        |lc| lc + hash.get_variable(),
+      );
+      self.state = vec![hash.clone()];
+
+      let bits = hash
+        .to_bits_le_strict(cs.namespace(|| "hash to bits"))?
+        .into_iter()
+        .map(|bit| match bit {
+          Boolean::Is(bit) => bit,
+          _ => unreachable!("strict decomposition allocates its bits"),
+        })
+        .collect::<Vec<_>>();
+      Ok(bits[..num_bits].to_vec())
+    }
+  }
+
+  // the Pasta engines with their RO replaced by `SumRO`
+  #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+  struct SumROPallasEngine;
+
+  #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+  struct SumROVestaEngine;
+
+  impl Engine for SumROPallasEngine {
+    type Base = <PallasEngine as Engine>::Base;
+    type Scalar = <PallasEngine as Engine>::Scalar;
+    type GE = <PallasEngine as Engine>::GE;
+    type RO = SumRO<Self::Base>;
+    type ROCircuit = SumROCircuit<Self::Base>;
+    type RO2 = <PallasEngine as Engine>::RO2;
+    type RO2Circuit = <PallasEngine as Engine>::RO2Circuit;
+    type TE = Keccak256Transcript<Self>;
+    type CE = PedersenCommitmentEngine<Self>;
+  }
+
+  impl Engine for SumROVestaEngine {
+    type Base = <VestaEngine as Engine>::Base;
+    type Scalar = <VestaEngine as Engine>::Scalar;
+    type GE = <VestaEngine as Engine>::GE;
+    type RO = SumRO<Self::Base>;
+    type ROCircuit = SumROCircuit<Self::Base>;
+    type RO2 = <VestaEngine as Engine>::RO2;
+    type RO2Circuit = <VestaEngine as Engine>::RO2Circuit;
+    type TE = Keccak256Transcript<Self>;
+    type CE = PedersenCommitmentEngine<Self>;
+  }
+
+  #[test]
+  fn test_ivc_trivial_with_alternate_ro() {
+    type E1 = SumROPallasEngine;
+    type E2 = SumROVestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    test_ivc_trivial_with::<E1, E2>();
+
+    // non-default constants are used throughout, and bound into the digest
+    let circuit = TrivialCircuit::<F>::default();
+    let pp =
+      PublicParams::<E1, E2, _>::setup(&circuit, &*default_ck_hint(), &*default_ck_hint()).unwrap();
+    let pp_custom = PublicParams::<E1, E2, _>::setup_with_ro_constants(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+      SumROConstants(7),
+      SumROConstants(11),
+    )
+    .unwrap();
+    assert_ne!(pp.digest(), pp_custom.digest());
+    assert_eq!(pp_custom.ro_constants().0 .0, 7);
+    assert_eq!(pp_custom.self_check(), Ok(()));
+
+    let mut recursive_snark = RecursiveSNARK::new(&pp_custom, &circuit, &[F::ZERO]).unwrap();
+    for _ in 0..3 {
+      recursive_snark.prove_step(&pp_custom, &circuit).unwrap();
+    }
+    assert!(recursive_snark.verify(&pp_custom, 3, &[F::ZERO]).is_ok());
+
+    // constants that differ from the ones of the augmented circuits are rejected
+    let mut pp_mismatched = pp_custom;
+    pp_mismatched.ro_consts_circuit_secondary = SumROConstants(8);
+    assert_eq!(
+      pp_mismatched.self_check(),
+      Err(NovaError::InvalidPublicParams {
+        reason: "RO constants differ from the ones of the augmented circuits".to_string()
+      })
+    );
+  }
+
   fn test_ivc_nontrivial_with<E1, E2>()
   where
     E1: Engine<Base = <E2 as Engine>::Scalar>,
@@ -1118,6 +3259,52 @@ mod tests {
     test_ivc_nontrivial_with::<Secp256k1Engine, Secq256k1Engine>();
   }
 
+  #[test]
+  fn test_run_ivc() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+
+    // the harness reproduces the outputs of `test_ivc_nontrivial`
+    let circuit = CubicCircuit::default();
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let zn = testing::run_ivc::<E1, E2, _>(&circuit, &z0, 3).unwrap();
+    assert_eq!(zn, vec![<E1 as Engine>::Scalar::from(2460515u64)]);
+
+    let zn = testing::run_ivc_with_compression::<E1, E2, _, S<E1, EE<E1>>, S<E2, EE<E2>>>(
+      &circuit, &z0, 3,
+    )
+    .unwrap();
+    assert_eq!(zn, vec![<E1 as Engine>::Scalar::from(2460515u64)]);
+
+    assert_eq!(
+      testing::run_ivc::<E1, E2, _>(&circuit, &z0, 0),
+      Err(NovaError::InvalidNumSteps)
+    );
+  }
+
+  #[test]
+  fn test_assert_symmetric_ivc() {
+    // the cubic circuit reaches the outputs of `test_ivc_nontrivial` on both orderings of each cycle
+    let zn = testing::assert_symmetric_ivc::<PallasEngine, VestaEngine, _, _>(
+      &CubicCircuit::default(),
+      &CubicCircuit::default(),
+      &[0],
+      3,
+    );
+    assert_eq!(zn, vec![<PallasEngine as Engine>::Scalar::from(2460515u64)]);
+
+    let zn = testing::assert_symmetric_ivc::<Bn256EngineKZG, GrumpkinEngine, _, _>(
+      &CubicCircuit::default(),
+      &CubicCircuit::default(),
+      &[0],
+      3,
+    );
+    assert_eq!(
+      zn,
+      vec![<Bn256EngineKZG as Engine>::Scalar::from(2460515u64)]
+    );
+  }
+
   fn test_ivc_nontrivial_with_compression_with<E1, E2, EE1, EE2>()
   where
     E1: Engine<Base = <E2 as Engine>::Scalar>,
@@ -1177,6 +3364,41 @@ mod tests {
     assert!(res.is_ok());
   }
 
+  #[test]
+  fn test_verify_batch() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let (pk, vk) = CompressedSNARK::<_, _, _, S<E1, EE<E1>>, S<E2, EE<E2>>>::setup(&pp).unwrap();
+
+    // compress the computation after each of three steps
+    let z0 = vec![F::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    let mut proofs = Vec::new();
+    for num_steps in 1..=3 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+      let compressed_snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+      proofs.push((compressed_snark, num_steps, z0.clone()));
+    }
+
+    // corrupt the second proof
+    proofs[1].0.zn[0] += F::ONE;
+
+    let results = CompressedSNARK::verify_batch(&vk, &proofs);
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0], Ok(circuit.output(&z0)));
+    assert!(results[1].is_err());
+    assert_eq!(results[2], Ok(recursive_snark.verify(&pp, 3, &z0).unwrap()));
+  }
+
   #[test]
   fn test_ivc_nontrivial_with_compression() {
     test_ivc_nontrivial_with_compression_with::<PallasEngine, VestaEngine, EE<_>, EE<_>>();
@@ -1255,6 +3477,41 @@ mod tests {
     // verify the compressed SNARK
     let res = compressed_snark.verify(&vk, num_steps, &[<E1 as Engine>::Scalar::ZERO]);
     assert!(res.is_ok());
+
+    // the public parameters are large enough for both backends, which must agree
+    assert_backend_equivalence::<_, _, _, EE1, EE2>(
+      &pp,
+      &recursive_snark,
+      num_steps,
+      &[<E1 as Engine>::Scalar::ZERO],
+    );
+  }
+
+  /// Compresses `recursive_snark` with both the Spartan and the preprocessing Spartan backends,
+  /// and checks that both proofs verify against the same statement and yield the same outputs.
+  /// The public parameters must be set up with the `ck_floor` of the preprocessing backend.
+  fn assert_backend_equivalence<E1, E2, C, EE1, EE2>(
+    pp: &PublicParams<E1, E2, C>,
+    recursive_snark: &RecursiveSNARK<E1, E2, C>,
+    num_steps: usize,
+    z0: &[<E1 as Engine>::Scalar],
+  ) where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+    C: StepCircuit<E1::Scalar>,
+    EE1: EvaluationEngineTrait<E1>,
+    EE2: EvaluationEngineTrait<E2>,
+  {
+    let (pk, vk) = CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::setup(pp).unwrap();
+    let compressed_snark = CompressedSNARK::prove(pp, &pk, recursive_snark).unwrap();
+    let zn = compressed_snark.verify(&vk, num_steps, z0).unwrap();
+
+    let (pk, vk) = CompressedSNARK::<_, _, _, SPrime<E1, EE1>, SPrime<E2, EE2>>::setup(pp).unwrap();
+    let compressed_snark = CompressedSNARK::prove(pp, &pk, recursive_snark).unwrap();
+    let zn_prime = compressed_snark.verify(&vk, num_steps, z0).unwrap();
+
+    assert_eq!(zn, zn_prime);
+    assert_eq!(zn, recursive_snark.verify(pp, num_steps, z0).unwrap());
   }
 
   #[test]
@@ -1270,170 +3527,2438 @@ mod tests {
     );
   }
 
-  fn test_ivc_nondet_with_compression_with<E1, E2, EE1, EE2>()
+  fn test_verify_with_inclusion_with<E1, E2, EE1, EE2>()
   where
     E1: Engine<Base = <E2 as Engine>::Scalar>,
     E2: Engine<Base = <E1 as Engine>::Scalar>,
     EE1: EvaluationEngineTrait<E1>,
     EE2: EvaluationEngineTrait<E2>,
   {
-    // y is a non-deterministic advice representing the fifth root of the input at a step.
-    #[derive(Clone, Debug)]
-    struct FifthRootCheckingCircuit<F: PrimeField> {
-      y: F,
-    }
-
-    impl<F: PrimeField> FifthRootCheckingCircuit<F> {
-      fn new(num_steps: usize) -> (Vec<F>, Vec<Self>) {
-        let mut powers = Vec::new();
-        let rng = &mut rand::rngs::OsRng;
-        let mut seed = F::random(rng);
-        for _i in 0..num_steps + 1 {
-          seed *= seed.clone().square().square();
-
-          powers.push(Self { y: seed });
-        }
-
-        // reverse the powers to get roots
-        let roots = powers.into_iter().rev().collect::<Vec<Self>>();
-        (vec![roots[0].y], roots[1..].to_vec())
-      }
-    }
-
-    impl<F> StepCircuit<F> for FifthRootCheckingCircuit<F>
-    where
-      F: PrimeField,
-    {
-      fn arity(&self) -> usize {
-        1
-      }
-
-      fn synthesize<CS: ConstraintSystem<F>>(
-        &self,
-        cs: &mut CS,
-        z: &[AllocatedNum<F>],
-      ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
-        let x = &z[0];
-
-        // we allocate a variable and set it to the provided non-deterministic advice.
-        let y = AllocatedNum::alloc_infallible(cs.namespace(|| "y"), || self.y);
-
-        // We now check if y = x^{1/5} by checking if y^5 = x
-        let y_sq = y.square(cs.namespace(|| "y_sq"))?;
-        let y_quad = y_sq.square(cs.namespace(|| "y_quad"))?;
-        let y_pow_5 = y_quad.mul(cs.namespace(|| "y_fifth"), &y)?;
-
-        cs.enforce(
-          || "y^5 = x",
-          |lc| lc + y_pow_5.get_variable(),
-          |lc| lc + CS::one(),
-          |lc| lc + x.get_variable(),
-        );
-
-        Ok(vec![y])
-      }
-    }
-
-    let circuit = FifthRootCheckingCircuit {
-      y: <E1 as Engine>::Scalar::ZERO,
-    };
+    let circuit = CubicCircuit::default();
 
     // produce public parameters
-    let pp = PublicParams::<E1, E2, FifthRootCheckingCircuit<<E1 as Engine>::Scalar>>::setup(
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
       &circuit,
       &*default_ck_hint(),
       &*default_ck_hint(),
     )
     .unwrap();
 
-    let num_steps = 3;
+    // produce and compress a recursive SNARK
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    recursive_snark.prove_step(&pp, &circuit).unwrap();
+    let (pk, vk) = CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::setup(&pp).unwrap();
+    let compressed_snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+
+    // publish the digest of the public parameters alongside the digests of two other versions
+    let leaves = [
+      <E1 as Engine>::Scalar::from(1u64).to_repr(),
+      pp.digest().to_repr(),
+      <E1 as Engine>::Scalar::from(2u64).to_repr(),
+    ];
+    let root = merkle_root(&leaves).unwrap();
+
+    // a valid inclusion proof passes
+    let merkle_proof = MerkleProof::new(&leaves, 1).unwrap();
+    let res = compressed_snark.verify_with_inclusion(&vk, 1, &z0, &merkle_proof, &root);
+    assert_eq!(res, compressed_snark.verify(&vk, 1, &z0));
+    assert!(res.is_ok());
 
-    // produce non-deterministic advice
-    let (z0, roots) = FifthRootCheckingCircuit::new(num_steps);
+    // a tampered path or an inclusion proof for another leaf fails
+    let mut tampered = merkle_proof.clone();
+    tampered.siblings[0][0] ^= 1;
+    let res = compressed_snark.verify_with_inclusion(&vk, 1, &z0, &tampered, &root);
+    assert!(res.is_err());
 
-    // produce a recursive SNARK
-    let mut recursive_snark: RecursiveSNARK<
-      E1,
-      E2,
-      FifthRootCheckingCircuit<<E1 as Engine>::Scalar>,
-    > = RecursiveSNARK::<E1, E2, FifthRootCheckingCircuit<<E1 as Engine>::Scalar>>::new(
-      &pp, &roots[0], &z0,
-    )
-    .unwrap();
+    let other_proof = MerkleProof::new(&leaves, 0).unwrap();
+    let res = compressed_snark.verify_with_inclusion(&vk, 1, &z0, &other_proof, &root);
+    assert!(res.is_err());
+  }
 
-    for circuit in roots.iter().take(num_steps) {
-      let res = recursive_snark.prove_step(&pp, circuit);
-      assert!(res.is_ok());
-    }
+  #[test]
+  fn test_verify_with_inclusion() {
+    test_verify_with_inclusion_with::<PallasEngine, VestaEngine, EE<_>, EE<_>>();
+  }
 
-    // verify the recursive SNARK
-    let res = recursive_snark.verify(&pp, num_steps, &z0);
-    assert!(res.is_ok());
+  fn test_output_hash_with<E1, E2, EE1, EE2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+    EE1: EvaluationEngineTrait<E1>,
+    EE2: EvaluationEngineTrait<E2>,
+  {
+    let circuit = CubicCircuit::default();
+    let setup = |options: SetupOptions<E1>| {
+      PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup_with_options(
+        &circuit,
+        &*default_ck_hint(),
+        &*default_ck_hint(),
+        options,
+      )
+    };
+    let options = SetupOptions::default().with_output_hash(true);
+    let pp = setup(options.clone()).unwrap();
+    assert_ne!(
+      pp.digest(),
+      setup(SetupOptions::default()).unwrap().digest()
+    );
+    assert!(setup(options.with_trivial_secondary(true)).is_err());
 
-    // produce the prover and verifier keys for compressed snark
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _i in 0..3 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+    let zn = recursive_snark.verify(&pp, 3, &z0).unwrap();
     let (pk, vk) = CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::setup(&pp).unwrap();
+    let compressed_snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+    assert_eq!(compressed_snark.verify(&vk, 3, &z0).unwrap(), zn);
 
-    // produce a compressed SNARK
-    let res = CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::prove(&pp, &pk, &recursive_snark);
-    assert!(res.is_ok());
-    let compressed_snark = res.unwrap();
-
-    // verify the compressed SNARK
-    let res = compressed_snark.verify(&vk, num_steps, &z0);
-    assert!(res.is_ok());
+    // the output hash of the primary circuit is a native Poseidon hash of the outputs
+    let mut hasher = PoseidonRO::new(PoseidonConstantsCircuit::default());
+    for e in &zn {
+      hasher.absorb(*e);
+    }
+    let output_hash = compressed_snark.output_hash(&vk);
+    assert_eq!(output_hash.0, hasher.squeeze(NUM_HASH_BITS));
+
+    // the proof verifies against the hashes alone, which the circuits bind
+    assert!(compressed_snark
+      .verify_against_output_hash(&vk, 3, &z0, output_hash)
+      .is_ok());
+    let (h1, h2) = output_hash;
+    assert!(compressed_snark
+      .verify_against_output_hash(&vk, 3, &z0, (h1 + <E1 as Engine>::Scalar::ONE, h2))
+      .is_err());
+    assert!(compressed_snark
+      .verify_against_output_hash(&vk, 3, &z0, (h1, h2 + <E2 as Engine>::Scalar::ONE))
+      .is_err());
+
+    // without the option, the circuits absorb the outputs rather than their hashes
+    let pp = setup(SetupOptions::default()).unwrap();
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    recursive_snark.prove_step(&pp, &circuit).unwrap();
+    let (pk, vk) = CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::setup(&pp).unwrap();
+    let compressed_snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+    assert!(compressed_snark.verify(&vk, 1, &z0).is_ok());
+    let output_hash = compressed_snark.output_hash(&vk);
+    assert!(compressed_snark
+      .verify_against_output_hash(&vk, 1, &z0, output_hash)
+      .is_err());
   }
 
   #[test]
-  fn test_ivc_nondet_with_compression() {
-    test_ivc_nondet_with_compression_with::<PallasEngine, VestaEngine, EE<_>, EE<_>>();
-    test_ivc_nondet_with_compression_with::<Bn256EngineKZG, GrumpkinEngine, EEPrime<_>, EE<_>>();
-    test_ivc_nondet_with_compression_with::<Secp256k1Engine, Secq256k1Engine, EE<_>, EE<_>>();
+  fn test_output_hash() {
+    test_output_hash_with::<PallasEngine, VestaEngine, EE<_>, EE<_>>();
   }
 
-  fn test_ivc_base_with<E1, E2>()
+  fn test_prove_deterministic_with<E1, E2, EE1, EE2>()
   where
     E1: Engine<Base = <E2 as Engine>::Scalar>,
     E2: Engine<Base = <E1 as Engine>::Scalar>,
+    EE1: EvaluationEngineTrait<E1>,
+    EE2: EvaluationEngineTrait<E2>,
   {
-    let test_circuit1 = CubicCircuit::<<E1 as Engine>::Scalar>::default();
-
-    // produce public parameters
+    let circuit = CubicCircuit::default();
     let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
-      &test_circuit1,
+      &circuit,
       &*default_ck_hint(),
       &*default_ck_hint(),
     )
     .unwrap();
 
-    let num_steps = 1;
-
-    // produce a recursive SNARK
-    let mut recursive_snark = RecursiveSNARK::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::new(
-      &pp,
-      &test_circuit1,
-      &[<E1 as Engine>::Scalar::ZERO],
-    )
-    .unwrap();
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _i in 0..2 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+    let (pk, vk) = CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::setup(&pp).unwrap();
+
+    let prove = |seed| {
+      let snark = CompressedSNARK::prove_deterministic(&pp, &pk, &recursive_snark, seed).unwrap();
+      (bincode::serialize(&snark).unwrap(), snark)
+    };
+    let (bytes1, compressed_snark) = prove([7u8; 32]);
+    let (bytes2, _) = prove([7u8; 32]);
+    let (bytes3, _) = prove([8u8; 32]);
+
+    // the same seed yields the same proof, a different seed a different one
+    assert_eq!(bytes1, bytes2);
+    assert_ne!(bytes1, bytes3);
+    assert!(compressed_snark.verify(&vk, 2, &z0).is_ok());
+  }
+
+  #[test]
+  fn test_prove_deterministic() {
+    test_prove_deterministic_with::<PallasEngine, VestaEngine, EE<_>, EE<_>>();
+  }
+
+  fn test_data_commitment_chain_with<E1, E2, EE1, EE2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+    EE1: EvaluationEngineTrait<E1>,
+    EE2: EvaluationEngineTrait<E2>,
+  {
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup_with_data_chain(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let commit = |v: u64| {
+      E1::CE::commit(
+        &pp.ck_primary,
+        &[<E1 as Engine>::Scalar::from(v)],
+        &<E1 as Engine>::Scalar::ZERO,
+      )
+    };
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let run = |data: &[Commitment<E1>]| {
+      let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+      recursive_snark
+        .prove_step_with_data(&pp, &circuit, None)
+        .unwrap();
+      for d in data {
+        recursive_snark
+          .prove_step_with_data(&pp, &circuit, Some(*d))
+          .unwrap();
+      }
+      recursive_snark
+    };
+
+    let recursive_snark = run(&[commit(1), commit(2), commit(3)]);
+    assert!(recursive_snark.verify(&pp, 4, &z0).is_ok());
+
+    // changing the data commitment of one step changes the chain
+    let mut other_snark = run(&[commit(1), commit(5), commit(3)]);
+    assert!(other_snark.verify(&pp, 4, &z0).is_ok());
+    assert_ne!(
+      recursive_snark.data_commitment_chain(),
+      other_snark.data_commitment_chain()
+    );
+
+    // and the proof does not verify against the chain of the original data
+    other_snark.data_commitment_chain = recursive_snark.data_commitment_chain();
+    assert!(other_snark.verify(&pp, 4, &z0).is_err());
+
+    // the compressed proof attests to the same chain
+    let (pk, vk) = CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::setup(&pp).unwrap();
+    let mut compressed_snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+    assert_eq!(
+      compressed_snark.data_commitment_chain(),
+      recursive_snark.data_commitment_chain()
+    );
+    assert!(compressed_snark.verify(&vk, 4, &z0).is_ok());
+    compressed_snark.data_commitment_chain += <E2 as Engine>::Scalar::ONE;
+    assert!(compressed_snark.verify(&vk, 4, &z0).is_err());
+
+    // the step executed by `new` cannot absorb data
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    assert_eq!(
+      recursive_snark.prove_step_with_data(&pp, &circuit, Some(commit(1))),
+      Err(NovaError::InvalidDataCommitment)
+    );
+  }
+
+  #[test]
+  fn test_data_commitment_chain() {
+    test_data_commitment_chain_with::<PallasEngine, VestaEngine, EE<_>, EE<_>>();
+  }
+
+  fn test_attests_with<E1, E2, EE1, EE2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+    EE1: EvaluationEngineTrait<E1>,
+    EE2: EvaluationEngineTrait<E2>,
+  {
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _i in 0..2 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+    let (pk, _vk) = CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::setup(&pp).unwrap();
+    let compressed_snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+    assert!(compressed_snark.attests(&recursive_snark));
+
+    // a different recursive SNARK over the same circuit is not attested
+    let mut other_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _i in 0..3 {
+      other_snark.prove_step(&pp, &circuit).unwrap();
+    }
+    assert!(!compressed_snark.attests(&other_snark));
+
+    // nor is the original one extended by a step
+    recursive_snark.prove_step(&pp, &circuit).unwrap();
+    assert!(!compressed_snark.attests(&recursive_snark));
+  }
+
+  #[test]
+  fn test_attests() {
+    test_attests_with::<PallasEngine, VestaEngine, EE<_>, EE<_>>();
+  }
+
+  fn test_setup_with_context_with<E1, E2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+  {
+    let circuit = CubicCircuit::default();
+    let setup = |context: &[<E1 as Engine>::Scalar]| {
+      PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup_with_context(
+        &circuit,
+        &*default_ck_hint(),
+        &*default_ck_hint(),
+        context,
+      )
+      .unwrap()
+    };
+    let context = [
+      <E1 as Engine>::Scalar::from(1u64),
+      <E1 as Engine>::Scalar::from(7u64),
+    ];
+    let pp = setup(&context);
+    let other_pp = setup(&[
+      <E1 as Engine>::Scalar::from(2u64),
+      <E1 as Engine>::Scalar::from(7u64),
+    ]);
+    assert_eq!(pp.context(), &context);
+
+    // an empty context yields the same digest as `setup`
+    let plain_pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    assert_eq!(setup(&[]).digest(), plain_pp.digest());
+    assert_ne!(pp.digest(), other_pp.digest());
+    assert_ne!(pp.digest(), plain_pp.digest());
+
+    // a proof under one context does not verify under another
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _i in 0..2 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+    assert!(recursive_snark.verify(&pp, 2, &z0).is_ok());
+    assert!(recursive_snark.verify(&other_pp, 2, &z0).is_err());
+  }
+
+  #[test]
+  fn test_setup_with_context() {
+    test_setup_with_context_with::<PallasEngine, VestaEngine>();
+  }
+
+  fn test_transcript_bytes_with<E1, E2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+  {
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _i in 0..2 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+    recursive_snark.verify(&pp, 2, &z0).unwrap();
+
+    // the transcript is a deterministic function of the proof
+    let transcript = recursive_snark.transcript_bytes(&pp);
+    assert!(!transcript.is_empty());
+    assert_eq!(transcript, recursive_snark.transcript_bytes(&pp));
+    let decoded: RecursiveSNARK<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>> =
+      bincode::deserialize(&bincode::serialize(&recursive_snark).unwrap()).unwrap();
+    assert_eq!(transcript, decoded.transcript_bytes(&pp));
+
+    // and changes with it
+    recursive_snark.prove_step(&pp, &circuit).unwrap();
+    assert_ne!(transcript, recursive_snark.transcript_bytes(&pp));
+  }
+
+  #[test]
+  fn test_transcript_bytes() {
+    test_transcript_bytes_with::<PallasEngine, VestaEngine>();
+  }
+
+  fn test_dry_run_step_with<E1, E2>(steps_per_fold: usize)
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+  {
+    let circuit = CubicCircuit::default();
+    let pp =
+      PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup_with_steps_per_fold(
+        &circuit,
+        &*default_ck_hint(),
+        &*default_ck_hint(),
+        steps_per_fold,
+      )
+      .unwrap();
+
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for i in 0..3 {
+      let report = recursive_snark.dry_run_step(&pp, &circuit).unwrap();
+      assert_eq!(recursive_snark.num_steps(), i);
+      assert_eq!(report.num_constraints, pp.r1cs_shape_primary.num_cons);
+      assert_eq!(report.num_vars, pp.r1cs_shape_primary.num_vars);
+      assert!(report.nonzero_witness_entries <= report.num_vars);
+      assert!(report.nonzero_witness_entries > 0);
+
+      // a real step produces a witness and an error vector of the reported sizes
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+      assert_eq!(report.num_vars, recursive_snark.r_W_primary.W.len());
+      assert_eq!(report.num_constraints, recursive_snark.r_W_primary.E.len());
+    }
+    recursive_snark.verify(&pp, 3, &z0).unwrap();
+
+    // as with `prove_step`, queued steps must be folded first
+    if steps_per_fold > 1 {
+      recursive_snark
+        .queue_primary_steps(&pp, &circuit, steps_per_fold)
+        .unwrap();
+      assert_eq!(
+        recursive_snark.dry_run_step(&pp, &circuit).err(),
+        Some(NovaError::PendingPrimarySteps)
+      );
+    }
+  }
+
+  #[test]
+  fn test_dry_run_step() {
+    test_dry_run_step_with::<PallasEngine, VestaEngine>(1);
+    test_dry_run_step_with::<PallasEngine, VestaEngine>(3);
+  }
+
+  #[test]
+  fn test_precompute_msm_tables() {
+    use crate::provider::ipa_pc::tests::TABLE_MSM_COUNT;
+    use std::sync::atomic::Ordering;
+
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type S1 = S<E1, EE<E1>>;
+    type S2 = S<E2, EE<E2>>;
+
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let prove = |num_steps: usize| {
+      let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+      for _i in 0..num_steps {
+        recursive_snark.prove_step(&pp, &circuit).unwrap();
+      }
+      recursive_snark
+    };
+    let (pk, vk) = CompressedSNARK::<_, _, _, S1, S2>::setup(&pp).unwrap();
+    let (_, mut vk_tables) = CompressedSNARK::<_, _, _, S1, S2>::setup(&pp).unwrap();
+    vk_tables.precompute_msm_tables();
+
+    // a valid proof, and one whose primary SNARK is taken from a proof of another computation
+    let valid = CompressedSNARK::prove(&pp, &pk, &prove(3)).unwrap();
+    let mut forged = CompressedSNARK::prove(&pp, &pk, &prove(3)).unwrap();
+    forged.snark_primary = CompressedSNARK::prove(&pp, &pk, &prove(5))
+      .unwrap()
+      .snark_primary;
+
+    let count = TABLE_MSM_COUNT.load(Ordering::Relaxed);
+    let num_verifications = 4;
+    for _i in 0..num_verifications {
+      assert_eq!(
+        valid.verify(&vk_tables, 3, &z0).unwrap(),
+        valid.verify(&vk, 3, &z0).unwrap()
+      );
+      assert!(forged.verify(&vk, 3, &z0).is_err());
+      assert!(forged.verify(&vk_tables, 3, &z0).is_err());
+    }
+
+    // every verification of the valid proof used the tables of both curves
+    assert!(TABLE_MSM_COUNT.load(Ordering::Relaxed) >= count + 2 * num_verifications);
+  }
+
+  // an application state with two counters, the second of which sums the values of the first
+  #[derive(Clone, Debug, PartialEq, Eq)]
+  struct Counters {
+    steps: u64,
+    sum: u64,
+  }
+
+  impl<F: PrimeField> IvcState<F> for Counters {
+    fn to_field_vec(&self) -> Vec<F> {
+      vec![F::from(self.steps), F::from(self.sum)]
+    }
+
+    fn from_field_vec(v: &[F]) -> Result<Self, StateError> {
+      if v.len() != <Self as IvcState<F>>::arity() {
+        return Err(StateError::InvalidLength {
+          expected: <Self as IvcState<F>>::arity(),
+          actual: v.len(),
+        });
+      }
+      let to_u64 = |e: &F| {
+        let repr = e.to_repr();
+        let (low, high) = repr.as_ref().split_at(8);
+        if high.iter().any(|b| *b != 0) {
+          return Err(StateError::InvalidValue {
+            reason: "counter does not fit in a u64".to_string(),
+          });
+        }
+        Ok(u64::from_le_bytes(low.try_into().unwrap()))
+      };
+      Ok(Self {
+        steps: to_u64(&v[0])?,
+        sum: to_u64(&v[1])?,
+      })
+    }
+
+    fn arity() -> usize {
+      2
+    }
+  }
+
+  #[derive(Clone, Debug, Default)]
+  struct CountersCircuit<F: PrimeField> {
+    _p: PhantomData<F>,
+  }
+
+  impl<F: PrimeField> StepCircuit<F> for CountersCircuit<F> {
+    fn arity(&self) -> usize {
+      2
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+      &self,
+      cs: &mut CS,
+      z: &[AllocatedNum<F>],
+    ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+      let steps = AllocatedNum::alloc(cs.namespace(|| "steps"), || {
+        Ok(z[0].get_value().unwrap() + F::ONE)
+      })?;
+      cs.enforce(
+        || "steps = z[0] + 1",
+        |lc| lc + z[0].get_variable() + CS::one(),
+        |lc| lc + CS::one(),
+        |lc| lc + steps.get_variable(),
+      );
+      let sum = z[1].add(cs.namespace(|| "sum"), &steps)?;
+
+      Ok(vec![steps, sum])
+    }
+  }
+
+  #[test]
+  fn test_new_typed() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    // the state round-trips through its encoding
+    let s0 = Counters { steps: 0, sum: 10 };
+    let z0 = s0.to_field_vec();
+    assert_eq!(z0, vec![F::ZERO, F::from(10u64)]);
+    assert_eq!(Counters::from_field_vec(&z0), Ok(s0.clone()));
+    assert_eq!(
+      Counters::from_field_vec(&z0[..1]),
+      Err(StateError::InvalidLength {
+        expected: 2,
+        actual: 1
+      })
+    );
+    assert!(matches!(
+      Counters::from_field_vec(&[F::ZERO, -F::ONE]),
+      Err(StateError::InvalidValue { .. })
+    ));
+
+    let circuit = CountersCircuit::default();
+    let pp = PublicParams::<E1, E2, CountersCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let num_steps = 4;
+    let mut recursive_snark = RecursiveSNARK::new_typed(&pp, &circuit, &s0).unwrap();
+    for _i in 0..num_steps {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+    recursive_snark.verify(&pp, num_steps, &z0).unwrap();
+    assert_eq!(
+      recursive_snark.typed_outputs::<Counters>(),
+      Ok(Counters {
+        steps: 4,
+        sum: 10 + 1 + 2 + 3 + 4
+      })
+    );
+
+    // a state of the wrong arity is rejected
+    let cubic = CubicCircuit::default();
+    let pp_cubic = PublicParams::<E1, E2, CubicCircuit<F>>::setup(
+      &cubic,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    assert!(matches!(
+      RecursiveSNARK::new_typed(&pp_cubic, &cubic, &s0),
+      Err(NovaError::InvalidInitialInputLength)
+    ));
+  }
+
+  fn test_z0_commitment_with<E1, E2, EE1, EE2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+    EE1: EvaluationEngineTrait<E1>,
+    EE2: EvaluationEngineTrait<E2>,
+  {
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    recursive_snark.prove_step(&pp, &circuit).unwrap();
+    let (pk, vk) = CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::setup(&pp).unwrap();
+    let mut compressed_snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+
+    assert_eq!(compressed_snark.check_z0(&vk, &z0), Ok(()));
+    assert!(compressed_snark.verify(&vk, 1, &z0).is_ok());
+
+    // the proof carries the commitment to z0
+    let z0_commitment = CompressedSNARK::<
+      E1,
+      E2,
+      CubicCircuit<<E1 as Engine>::Scalar>,
+      S<E1, EE1>,
+      S<E2, EE2>,
+    >::commit_z0(&vk.ro_consts_secondary, vk.pp_digest, &z0);
+    assert_eq!(compressed_snark.z0_commitment(), Some(z0_commitment));
+
+    // a mismatched z0 is caught by the commitment check
+    let wrong_z0 = [<E1 as Engine>::Scalar::ONE];
+    assert_eq!(
+      compressed_snark.check_z0(&vk, &wrong_z0),
+      Err(NovaError::InputMismatch)
+    );
+    assert_eq!(
+      compressed_snark.verify(&vk, 1, &wrong_z0),
+      Err(NovaError::InputMismatch)
+    );
+
+    // z0 is committed to along with its length
+    assert_eq!(
+      compressed_snark.check_z0(&vk, &[z0[0], z0[0]]),
+      Err(NovaError::InputMismatch)
+    );
+
+    // a commitment other than the one to z0 fails verification
+    compressed_snark.z0_commitment = Some(<E1 as Engine>::Scalar::ZERO);
+    assert_eq!(
+      compressed_snark.verify(&vk, 1, &z0),
+      Err(NovaError::InputMismatch)
+    );
+
+    // the commitment cannot be stripped from a proof in the current layout
+    compressed_snark.z0_commitment = None;
+    assert!(bincode::serialize(&compressed_snark).is_err());
+    assert!(matches!(
+      compressed_snark.check_z0(&vk, &z0),
+      Err(NovaError::InvalidCompressedSNARK { .. })
+    ));
+  }
+
+  #[test]
+  fn test_z0_commitment() {
+    test_z0_commitment_with::<PallasEngine, VestaEngine, EE<_>, EE<_>>();
+  }
+
+  fn test_ivc_nondet_with_compression_with<E1, E2, EE1, EE2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+    EE1: EvaluationEngineTrait<E1>,
+    EE2: EvaluationEngineTrait<E2>,
+  {
+    // y is a non-deterministic advice representing the fifth root of the input at a step.
+    #[derive(Clone, Debug)]
+    struct FifthRootCheckingCircuit<F: PrimeField> {
+      y: F,
+    }
+
+    impl<F: PrimeField> FifthRootCheckingCircuit<F> {
+      fn new(num_steps: usize) -> (Vec<F>, Vec<Self>) {
+        let mut powers = Vec::new();
+        let rng = &mut rand::rngs::OsRng;
+        let mut seed = F::random(rng);
+        for _i in 0..num_steps + 1 {
+          seed *= seed.clone().square().square();
+
+          powers.push(Self { y: seed });
+        }
+
+        // reverse the powers to get roots
+        let roots = powers.into_iter().rev().collect::<Vec<Self>>();
+        (vec![roots[0].y], roots[1..].to_vec())
+      }
+    }
+
+    impl<F> StepCircuit<F> for FifthRootCheckingCircuit<F>
+    where
+      F: PrimeField,
+    {
+      fn arity(&self) -> usize {
+        1
+      }
+
+      fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<F>],
+      ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+        let x = &z[0];
+
+        // we allocate a variable and set it to the provided non-deterministic advice.
+        let y = AllocatedNum::alloc_infallible(cs.namespace(|| "y"), || self.y);
+
+        // We now check if y = x^{1/5} by checking if y^5 = x
+        let y_sq = y.square(cs.namespace(|| "y_sq"))?;
+        let y_quad = y_sq.square(cs.namespace(|| "y_quad"))?;
+        let y_pow_5 = y_quad.mul(cs.namespace(|| "y_fifth"), &y)?;
+
+        cs.enforce(
+          || "y^5 = x",
+          |lc| lc + y_pow_5.get_variable(),
+          |lc| lc + CS::one(),
+          |lc| lc + x.get_variable(),
+        );
+
+        Ok(vec![y])
+      }
+    }
+
+    let circuit = FifthRootCheckingCircuit {
+      y: <E1 as Engine>::Scalar::ZERO,
+    };
+
+    // produce public parameters
+    let pp = PublicParams::<E1, E2, FifthRootCheckingCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let num_steps = 3;
+
+    // produce non-deterministic advice
+    let (z0, roots) = FifthRootCheckingCircuit::new(num_steps);
+
+    // produce a recursive SNARK
+    let mut recursive_snark: RecursiveSNARK<
+      E1,
+      E2,
+      FifthRootCheckingCircuit<<E1 as Engine>::Scalar>,
+    > = RecursiveSNARK::<E1, E2, FifthRootCheckingCircuit<<E1 as Engine>::Scalar>>::new(
+      &pp, &roots[0], &z0,
+    )
+    .unwrap();
+
+    for circuit in roots.iter().take(num_steps) {
+      let res = recursive_snark.prove_step(&pp, circuit);
+      assert!(res.is_ok());
+    }
+
+    // verify the recursive SNARK
+    let res = recursive_snark.verify(&pp, num_steps, &z0);
+    assert!(res.is_ok());
+
+    // produce the prover and verifier keys for compressed snark
+    let (pk, vk) = CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::setup(&pp).unwrap();
+
+    // produce a compressed SNARK
+    let res = CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::prove(&pp, &pk, &recursive_snark);
+    assert!(res.is_ok());
+    let compressed_snark = res.unwrap();
+
+    // verify the compressed SNARK
+    let res = compressed_snark.verify(&vk, num_steps, &z0);
+    assert!(res.is_ok());
+  }
+
+  #[test]
+  fn test_ivc_nondet_with_compression() {
+    test_ivc_nondet_with_compression_with::<PallasEngine, VestaEngine, EE<_>, EE<_>>();
+    test_ivc_nondet_with_compression_with::<Bn256EngineKZG, GrumpkinEngine, EEPrime<_>, EE<_>>();
+    test_ivc_nondet_with_compression_with::<Secp256k1Engine, Secq256k1Engine, EE<_>, EE<_>>();
+  }
+
+  fn test_ivc_base_with<E1, E2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+  {
+    let test_circuit1 = CubicCircuit::<<E1 as Engine>::Scalar>::default();
+
+    // produce public parameters
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &test_circuit1,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let num_steps = 1;
+
+    // produce a recursive SNARK
+    let mut recursive_snark = RecursiveSNARK::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::new(
+      &pp,
+      &test_circuit1,
+      &[<E1 as Engine>::Scalar::ZERO],
+    )
+    .unwrap();
 
     // produce a recursive SNARK
     let res = recursive_snark.prove_step(&pp, &test_circuit1);
 
-    assert!(res.is_ok());
+    assert!(res.is_ok());
+
+    // the first call only records the base case executed by the constructor
+    assert_eq!(res.unwrap(), num_steps);
+    assert_eq!(recursive_snark.num_steps(), num_steps);
+
+    // verify the recursive SNARK
+    let res = recursive_snark.verify(&pp, num_steps, &[<E1 as Engine>::Scalar::ZERO]);
+    assert!(res.is_ok());
+
+    let zn = res.unwrap();
+
+    assert_eq!(zn, vec![<E1 as Engine>::Scalar::from(5u64)]);
+
+    // subsequent calls each execute one more step
+    for expected in num_steps + 1..num_steps + 3 {
+      assert_eq!(
+        recursive_snark.prove_step(&pp, &test_circuit1),
+        Ok(expected)
+      );
+    }
+    let res = recursive_snark.verify(&pp, num_steps + 2, &[<E1 as Engine>::Scalar::ZERO]);
+    assert_eq!(res, Ok(vec![<E1 as Engine>::Scalar::from(2460515u64)]));
+  }
+
+  #[test]
+  fn test_ivc_base() {
+    test_ivc_base_with::<PallasEngine, VestaEngine>();
+    test_ivc_base_with::<Bn256EngineKZG, GrumpkinEngine>();
+    test_ivc_base_with::<Secp256k1Engine, Secq256k1Engine>();
+  }
+
+  fn test_recursive_snark_bytes_with<E1, E2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+  {
+    let circuit = CubicCircuit::<<E1 as Engine>::Scalar>::default();
+
+    // produce public parameters
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let num_steps = 3;
+
+    // produce a recursive SNARK
+    let mut recursive_snark = RecursiveSNARK::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::new(
+      &pp,
+      &circuit,
+      &[<E1 as Engine>::Scalar::ZERO],
+    )
+    .unwrap();
+
+    for _i in 0..num_steps {
+      let res = recursive_snark.prove_step(&pp, &circuit);
+      assert!(res.is_ok());
+    }
+
+    // a round-trip through the byte form preserves the proof
+    let bytes = RecursiveSNARKBytes::from(&recursive_snark);
+    let decoded = RecursiveSNARK::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::try_from(bytes);
+    assert!(decoded.is_ok());
+    let res = decoded
+      .unwrap()
+      .verify(&pp, num_steps, &[<E1 as Engine>::Scalar::ZERO]);
+    assert_eq!(
+      res,
+      recursive_snark.verify(&pp, num_steps, &[<E1 as Engine>::Scalar::ZERO])
+    );
+
+    // a truncated byte form cannot be decoded
+    let mut bytes = RecursiveSNARKBytes::from(&recursive_snark)
+      .as_bytes()
+      .to_vec();
+    bytes.truncate(bytes.len() / 2);
+    let res = RecursiveSNARK::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::try_from(
+      RecursiveSNARKBytes::new(bytes),
+    );
+    assert!(matches!(
+      res.err(),
+      Some(NovaError::InvalidRecursiveSNARK { .. })
+    ));
+
+    // a byte form with an inconsistent output length is rejected
+    let mut corrupted = recursive_snark.clone();
+    corrupted.zi.push(<E1 as Engine>::Scalar::ZERO);
+    let res = RecursiveSNARK::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::try_from(
+      RecursiveSNARKBytes::from(&corrupted),
+    );
+    assert_eq!(
+      res.err(),
+      Some(NovaError::InvalidRecursiveSNARK {
+        reason: "zi and z0 have different lengths".to_string()
+      })
+    );
+
+    // a byte form with cleared running witnesses is rejected
+    let mut corrupted = recursive_snark.clone();
+    corrupted.r_W_secondary = RelaxedR1CSWitness::default(&pp.r1cs_shape_secondary);
+    let res = RecursiveSNARK::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::try_from(
+      RecursiveSNARKBytes::from(&corrupted),
+    );
+    assert_eq!(
+      res.err(),
+      Some(NovaError::InvalidRecursiveSNARK {
+        reason: "running witnesses are not set after the base case".to_string()
+      })
+    );
+  }
+
+  // computes `x^3 + x + 5` as `CubicCircuit`, or passes `x` through when `noop` is set
+  #[derive(Clone, Debug, Default)]
+  struct PaddableCubicCircuit<F: PrimeField> {
+    noop: bool,
+    _p: PhantomData<F>,
+  }
+
+  impl<F: PrimeField> StepCircuit<F> for PaddableCubicCircuit<F> {
+    fn arity(&self) -> usize {
+      1
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+      &self,
+      cs: &mut CS,
+      z: &[AllocatedNum<F>],
+    ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+      let x = &z[0];
+      let noop = AllocatedNum::alloc(cs.namespace(|| "noop"), || {
+        Ok(if self.noop { F::ONE } else { F::ZERO })
+      })?;
+      cs.enforce(
+        || "noop is a bit",
+        |lc| lc + noop.get_variable(),
+        |lc| lc + CS::one() - noop.get_variable(),
+        |lc| lc,
+      );
+
+      let x_sq = x.square(cs.namespace(|| "x_sq"))?;
+      let x_cu = x_sq.mul(cs.namespace(|| "x_cu"), x)?;
+
+      // t = (1 - noop) * (x^3 + 5)
+      let t = AllocatedNum::alloc(cs.namespace(|| "t"), || {
+        if self.noop {
+          Ok(F::ZERO)
+        } else {
+          Ok(x_cu.get_value().unwrap() + F::from(5u64))
+        }
+      })?;
+      cs.enforce(
+        || "t = (1 - noop) * (x^3 + 5)",
+        |lc| lc + CS::one() - noop.get_variable(),
+        |lc| lc + x_cu.get_variable() + CS::one() + CS::one() + CS::one() + CS::one() + CS::one(),
+        |lc| lc + t.get_variable(),
+      );
+
+      let y = AllocatedNum::alloc(cs.namespace(|| "y"), || {
+        Ok(x.get_value().unwrap() + t.get_value().unwrap())
+      })?;
+      cs.enforce(
+        || "y = x + t",
+        |lc| lc + x.get_variable() + t.get_variable(),
+        |lc| lc + CS::one(),
+        |lc| lc + y.get_variable(),
+      );
+
+      Ok(vec![y])
+    }
+  }
+
+  #[test]
+  fn test_prove_step_checked() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _i in 0..3 {
+      recursive_snark.prove_step_checked(&pp, &circuit).unwrap();
+    }
+    assert_eq!(recursive_snark.num_steps(), 3);
+
+    // a corrupted intermediate state is caught by the step that folds it, which is not recorded
+    recursive_snark.zi[0] += <E1 as Engine>::Scalar::ONE;
+    assert!(recursive_snark.prove_step_checked(&pp, &circuit).is_err());
+    assert_eq!(recursive_snark.num_steps(), 3);
+  }
+
+  #[test]
+  fn test_diagnostic_report() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _i in 0..3 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+
+    // every check passes for a valid proof
+    let report = recursive_snark.diagnostic_report(&pp);
+    assert!(report.contains("steps: 3\n"));
+    assert!(report.contains("check primary hash chain: pass"));
+    assert!(report.contains("check last secondary instance satisfiable: pass"));
+    assert!(!report.contains("FAIL"));
+
+    // tampered outputs break the hash chain, and only it
+    let mut tampered = recursive_snark.clone();
+    tampered.zi[0] += <E1 as Engine>::Scalar::ONE;
+    let report = tampered.diagnostic_report(&pp);
+    let failures = report
+      .lines()
+      .filter(|l| l.contains("FAIL"))
+      .collect::<Vec<_>>();
+    assert_eq!(failures.len(), 1);
+    assert!(failures[0].starts_with("check primary hash chain: FAIL"));
+
+    // a tampered witness makes its instance unsatisfiable, and the witness is not reported
+    let mut tampered = recursive_snark.clone();
+    tampered.r_W_primary.W[0] += <E1 as Engine>::Scalar::ONE;
+    let report = tampered.diagnostic_report(&pp);
+    let failures = report
+      .lines()
+      .filter(|l| l.contains("FAIL"))
+      .collect::<Vec<_>>();
+    assert_eq!(failures.len(), 1);
+    assert!(failures[0].starts_with("check primary running instance satisfiable: FAIL"));
+    let w0 = tampered.r_W_primary.W[0].to_repr();
+    let w0_hex = w0
+      .as_ref()
+      .iter()
+      .map(|b| format!("{b:02x}"))
+      .collect::<String>();
+    assert!(!report.contains(&w0_hex));
+  }
+
+  #[test]
+  fn test_validate_initial() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    // a counter that must start at zero
+    #[derive(Clone, Debug, Default)]
+    struct CounterCircuit<F: PrimeField> {
+      _p: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> StepCircuit<F> for CounterCircuit<F> {
+      fn arity(&self) -> usize {
+        1
+      }
+
+      fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<F>],
+      ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+        let next = AllocatedNum::alloc(cs.namespace(|| "next"), || {
+          z[0]
+            .get_value()
+            .map(|v| v + F::ONE)
+            .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce(
+          || "next = z + 1",
+          |lc| lc + z[0].get_variable() + CS::one(),
+          |lc| lc + CS::one(),
+          |lc| lc + next.get_variable(),
+        );
+        Ok(vec![next])
+      }
+
+      fn validate_initial(&self, z0: &[F]) -> Result<(), String> {
+        if z0[0] != F::ZERO {
+          return Err("counter must start at 0".to_string());
+        }
+        Ok(())
+      }
+    }
+
+    let circuit = CounterCircuit::default();
+    let pp = PublicParams::<E1, E2, CounterCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    assert_eq!(
+      RecursiveSNARK::new(&pp, &circuit, &[F::ONE]).err(),
+      Some(NovaError::InvalidInitialState {
+        reason: "counter must start at 0".to_string()
+      })
+    );
+
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &[F::ZERO]).unwrap();
+    recursive_snark.prove_step(&pp, &circuit).unwrap();
+    recursive_snark.prove_step(&pp, &circuit).unwrap();
+    assert_eq!(
+      recursive_snark.verify(&pp, 2, &[F::ZERO]).unwrap(),
+      vec![F::from(2u64)]
+    );
+  }
+
+  #[test]
+  fn test_verify_constant_time() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    // the verifiers compare the hashes and the inputs with `ConstantTimeEq`
+    fn assert_constant_time_eq<T: ConstantTimeEq + ?Sized>() {}
+    assert_constant_time_eq::<F>();
+    assert_constant_time_eq::<<E2 as Engine>::Scalar>();
+    assert_constant_time_eq::<[F]>();
+
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let z0 = [F::ONE];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _ in 0..2 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+
+    // valid proofs still verify, and invalid ones still fail
+    assert!(recursive_snark.verify(&pp, 2, &z0).is_ok());
+    assert!(recursive_snark.verify(&pp, 2, &[F::ZERO]).is_err());
+    let mut tampered = recursive_snark.clone();
+    tampered.zi[0] += F::ONE;
+    assert!(tampered.verify(&pp, 2, &z0).is_err());
+
+    let (pk, vk) = CompressedSNARK::<_, _, _, S<E1, EE<E1>>, S<E2, EE<E2>>>::setup(&pp).unwrap();
+    let mut compressed_snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+    assert!(compressed_snark.verify(&vk, 2, &z0).is_ok());
+    assert_eq!(
+      compressed_snark.verify(&vk, 2, &[F::ZERO]).err(),
+      Some(NovaError::InputMismatch)
+    );
+    compressed_snark.zn[0] += F::ONE;
+    assert!(compressed_snark.verify(&vk, 2, &z0).is_err());
+  }
+
+  #[test]
+  fn test_public_signals() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    // a counter that also exposes the square of its input as a public signal
+    #[derive(Clone, Debug, Default)]
+    struct SquareSignalCircuit<F: PrimeField> {
+      _p: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> StepCircuit<F> for SquareSignalCircuit<F> {
+      fn arity(&self) -> usize {
+        1
+      }
+
+      fn num_public_signals(&self) -> usize {
+        1
+      }
+
+      fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<F>],
+      ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+        let next = AllocatedNum::alloc(cs.namespace(|| "next"), || {
+          z[0]
+            .get_value()
+            .map(|v| v + F::ONE)
+            .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce(
+          || "next = z + 1",
+          |lc| lc + z[0].get_variable() + CS::one(),
+          |lc| lc + CS::one(),
+          |lc| lc + next.get_variable(),
+        );
+        let square = z[0].square(cs.namespace(|| "square"))?;
+        Ok(vec![next, square])
+      }
+    }
+
+    let circuit = SquareSignalCircuit::default();
+    let pp = PublicParams::<E1, E2, SquareSignalCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    pp.check_circuit_compatibility(&circuit).unwrap();
+
+    // the primary circuit outputs the signal after its two hashes
+    assert_eq!(pp.r1cs_shape_primary.num_io, 3);
+    assert_eq!(pp.r1cs_shape_secondary.num_io, 2);
+
+    let z0 = [F::from(2u64)];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _ in 0..3 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+
+    // the signal of the last step, which squares 4, follows the outputs without feeding the next step
+    assert_eq!(
+      recursive_snark.verify(&pp, 3, &z0).unwrap(),
+      vec![F::from(5u64)]
+    );
+    assert_eq!(recursive_snark.public_signals(), &[F::from(16u64)]);
+    assert_eq!(recursive_snark.r_U_primary.X.len(), 3);
+
+    // the signals are bound by the proof
+    let mut tampered = recursive_snark.clone();
+    tampered.public_signals = vec![F::from(17u64)];
+    assert!(tampered.verify(&pp, 3, &z0).is_err());
+
+    let (pk, vk) = CompressedSNARK::<_, _, _, S<E1, EE<E1>>, S<E2, EE<E2>>>::setup(&pp).unwrap();
+    let mut compressed_snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark).unwrap();
+    assert!(compressed_snark.attests(&recursive_snark));
+    assert_eq!(compressed_snark.public_signals(), &[F::from(16u64)]);
+    assert!(compressed_snark.verify(&vk, 3, &z0).is_ok());
+
+    compressed_snark.public_signals = vec![F::from(17u64)];
+    assert!(compressed_snark.verify(&vk, 3, &z0).is_err());
+  }
+
+  fn test_pad_to_with<E1, E2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+  {
+    let circuit = PaddableCubicCircuit::default();
+    let circuit_noop = PaddableCubicCircuit {
+      noop: true,
+      _p: PhantomData,
+    };
+    let pp = PublicParams::<E1, E2, PaddableCubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _i in 0..3 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+    let zn = recursive_snark.verify(&pp, 3, &z0).unwrap();
+
+    assert_eq!(recursive_snark.pad_to(&pp, 8, &circuit_noop), Ok(8));
+    assert_eq!(recursive_snark.verify(&pp, 8, &z0), Ok(zn.clone()));
+
+    // padding cannot reduce the number of steps
+    assert_eq!(
+      recursive_snark.pad_to(&pp, 5, &circuit_noop),
+      Err(NovaError::InvalidNumSteps)
+    );
+
+    // a padding step that changes the outputs is rejected and leaves the proof unchanged
+    assert_eq!(
+      recursive_snark.pad_to(&pp, 9, &circuit),
+      Err(NovaError::InvalidPaddingStep)
+    );
+    assert_eq!(recursive_snark.verify(&pp, 8, &z0), Ok(zn));
+  }
+
+  #[test]
+  fn test_pad_to() {
+    test_pad_to_with::<PallasEngine, VestaEngine>();
+  }
+
+  fn test_rewind_to_with<E1, E2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+  {
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    let checkpoint_0 = recursive_snark.checkpoint();
+    let mut checkpoint_3 = None;
+    for i in 0..5 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+      if i == 2 {
+        checkpoint_3 = Some(recursive_snark.checkpoint());
+      }
+    }
+    let checkpoint_3 = checkpoint_3.unwrap();
+    assert_eq!(checkpoint_3.num_steps(), 3);
+    let zn = recursive_snark.verify(&pp, 5, &z0).unwrap();
+
+    // rewinding to step 3 and re-proving to step 5 yields the same final state
+    recursive_snark.rewind_to(&pp, &checkpoint_3).unwrap();
+    assert_eq!(recursive_snark.num_steps(), 3);
+    assert_eq!(recursive_snark.outputs(), checkpoint_3.outputs());
+    recursive_snark.verify(&pp, 3, &z0).unwrap();
+    for _i in 0..2 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+    assert_eq!(recursive_snark.verify(&pp, 5, &z0), Ok(zn.clone()));
+
+    // a checkpoint taken before the first step can be rewound to as well
+    let mut rewound = recursive_snark.clone();
+    rewound.rewind_to(&pp, &checkpoint_0).unwrap();
+    for _i in 0..5 {
+      rewound.prove_step(&pp, &circuit).unwrap();
+    }
+    assert_eq!(rewound.verify(&pp, 5, &z0), Ok(zn));
+
+    // a checkpoint ahead of the current step is rejected
+    let mut short = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    short.prove_step(&pp, &circuit).unwrap();
+    assert!(matches!(
+      short.rewind_to(&pp, &checkpoint_3),
+      Err(NovaError::InvalidCheckpoint { .. })
+    ));
+
+    // a checkpoint of a computation with a different initial input is rejected
+    let z0_other = [<E1 as Engine>::Scalar::ONE];
+    let mut other = RecursiveSNARK::new(&pp, &circuit, &z0_other).unwrap();
+    for _i in 0..5 {
+      other.prove_step(&pp, &circuit).unwrap();
+    }
+    assert!(matches!(
+      other.rewind_to(&pp, &checkpoint_3),
+      Err(NovaError::InvalidCheckpoint { .. })
+    ));
+
+    // a checkpoint whose instances are not bound to its outputs is rejected
+    let mut tampered = checkpoint_3.clone();
+    tampered.0.zi[0] += <E1 as Engine>::Scalar::ONE;
+    assert!(matches!(
+      recursive_snark.rewind_to(&pp, &tampered),
+      Err(NovaError::InvalidCheckpoint { .. })
+    ));
+    assert_eq!(recursive_snark.num_steps(), 5);
+  }
+
+  #[test]
+  fn test_rewind_to() {
+    test_rewind_to_with::<PallasEngine, VestaEngine>();
+  }
+
+  #[test]
+  fn test_from_transition() {
+    use crate::gadgets::dsl::{add, constant, input, mul};
+
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    // the cubic transition `x^3 + x + 5` of `CubicCircuit`
+    let transition = TransitionCircuit::new(vec![add(
+      add(mul(mul(input(0), input(0)), input(0)), input(0)),
+      constant(F::from(5u64)),
+    )])
+    .unwrap();
+    let cubic = CubicCircuit::default();
+
+    let pp = PublicParams::<E1, E2, TransitionCircuit<F>>::setup(
+      &transition,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let pp_cubic = PublicParams::<E1, E2, CubicCircuit<F>>::setup(
+      &cubic,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    assert_eq!(pp.num_constraints(), pp_cubic.num_constraints());
+    assert_eq!(pp.num_variables(), pp_cubic.num_variables());
+
+    let num_steps = 3;
+    let z0 = [F::from(2u64)];
+    let recursive_snark =
+      RecursiveSNARK::from_transition(&pp, &transition, &z0, num_steps).unwrap();
+    let zn = recursive_snark.verify(&pp, num_steps, &z0).unwrap();
+
+    let mut zn_cubic = z0.to_vec();
+    for _i in 0..num_steps {
+      assert_eq!(transition.output(&zn_cubic), cubic.output(&zn_cubic));
+      zn_cubic = cubic.output(&zn_cubic);
+    }
+    assert_eq!(zn, zn_cubic);
+  }
+
+  #[test]
+  fn test_check_circuit_compatibility() {
+    use crate::gadgets::dsl::{add, constant, input, mul};
+
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    // the standard cubic circuit, with the trivial circuit on the secondary curve, is compatible
+    let cubic = CubicCircuit::default();
+    let pp_cubic = PublicParams::<E1, E2, CubicCircuit<F>>::setup(
+      &cubic,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    assert_eq!(pp_cubic.check_circuit_compatibility(&cubic), Ok(()));
+
+    let transition =
+      TransitionCircuit::new(vec![add(mul(input(0), input(0)), constant(F::from(5u64)))]).unwrap();
+    let pp = PublicParams::<E1, E2, TransitionCircuit<F>>::setup(
+      &transition,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    assert_eq!(pp.check_circuit_compatibility(&transition), Ok(()));
+
+    // a circuit of a different arity is rejected with a message naming both arities
+    let wide = TransitionCircuit::new(vec![input(1), input(0)]).unwrap();
+    match pp.check_circuit_compatibility(&wide) {
+      Err(NovaError::IncompatibleCircuit { reason }) => {
+        assert_eq!(
+          reason,
+          "step circuit has arity 2, but the public parameters expect arity 1"
+        );
+      }
+      res => panic!("unexpected result {res:?}"),
+    }
+
+    // a circuit of the same arity but a different shape is rejected
+    let cubed = TransitionCircuit::new(vec![mul(mul(input(0), input(0)), input(0))]).unwrap();
+    assert!(matches!(
+      pp.check_circuit_compatibility(&cubed),
+      Err(NovaError::IncompatibleCircuit { reason }) if reason.starts_with("primary circuit")
+    ));
+  }
+
+  fn test_commitment_key_sizes_with<E1, E2, EE1, EE2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+    EE1: EvaluationEngineTrait<E1>,
+    EE2: EvaluationEngineTrait<E2>,
+  {
+    let circuit = TrivialCircuit::<<E1 as Engine>::Scalar>::default();
+    let pp = PublicParams::<E1, E2, TrivialCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let pp_floor = PublicParams::<E1, E2, TrivialCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*SPrime::<E1, EE1>::ck_floor(),
+      &*SPrime::<E2, EE2>::ck_floor(),
+    )
+    .unwrap();
+
+    let (ck_size_primary, ck_size_secondary) = pp.commitment_key_sizes();
+    let (ck_floor_size_primary, ck_floor_size_secondary) = pp_floor.commitment_key_sizes();
+    assert!(ck_size_primary >= pp.num_constraints().0);
+    assert!(ck_floor_size_primary > ck_size_primary);
+    assert!(ck_floor_size_secondary > ck_size_secondary);
+  }
+
+  #[test]
+  fn test_commitment_key_sizes() {
+    test_commitment_key_sizes_with::<PallasEngine, VestaEngine, EE<_>, EE<_>>();
+    test_commitment_key_sizes_with::<Bn256EngineKZG, GrumpkinEngine, EEPrime<_>, EE<_>>();
+  }
+
+  #[test]
+  fn test_grow_keys() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    let circuit = CubicCircuit::default();
+    let mut pp = PublicParams::<E1, E2, CubicCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let (n_primary, n_secondary) = pp.commitment_key_sizes();
+    let digest = pp.digest();
+
+    // the grown keys are those that setup samples for the new sizes
+    pp.grow_keys(2 * n_primary, n_secondary).unwrap();
+    assert_eq!(pp.commitment_key_sizes(), (2 * n_primary, n_secondary));
+    assert_eq!(
+      pp.ck_primary,
+      <E1 as Engine>::CE::setup(b"ck", 2 * n_primary)
+    );
+
+    // the digest is recomputed, and the grown parameters still prove and verify
+    assert_ne!(pp.digest(), digest);
+    assert_eq!(pp.check_digest(), Ok(()));
+    let z0 = [F::ONE];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    recursive_snark.prove_step(&pp, &circuit).unwrap();
+    recursive_snark.prove_step(&pp, &circuit).unwrap();
+    assert!(recursive_snark.verify(&pp, 2, &z0).is_ok());
+
+    // keys derived from a seed cannot be grown without it
+    let mut pp = PublicParams::<E1, E2, CubicCircuit<F>>::setup_with_ck_seed(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+      [1u8; 32],
+    )
+    .unwrap();
+    assert_eq!(
+      pp.grow_keys(2 * n_primary, n_secondary),
+      Err(NovaError::UnsupportedCommitmentKeyExtension)
+    );
+    assert_eq!(pp.commitment_key_sizes(), (n_primary, n_secondary));
+  }
+
+  #[test]
+  fn test_grow_keys_hyperkzg() {
+    type E1 = Bn256EngineKZG;
+    type E2 = GrumpkinEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    let circuit = CubicCircuit::default();
+    let mut pp = PublicParams::<E1, E2, CubicCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let sizes = pp.commitment_key_sizes();
+    let digest = pp.digest();
+
+    // the powers of tau of HyperKZG cannot be extended, so neither key is grown
+    assert_eq!(
+      pp.grow_keys(2 * sizes.0, 2 * sizes.1),
+      Err(NovaError::UnsupportedCommitmentKeyExtension)
+    );
+    assert_eq!(pp.commitment_key_sizes(), sizes);
+    assert_eq!(pp.digest(), digest);
+  }
+
+  fn test_verify_trusting_instances_with<E1, E2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+  {
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _i in 0..3 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+    assert!(recursive_snark.verify(&pp, 3, &z0).is_ok());
+
+    let (r_U_primary, r_U_secondary, l_u_secondary) = recursive_snark
+      .verify_trusting_instances(&pp, 3, &z0)
+      .unwrap();
+    assert_eq!(r_U_primary, recursive_snark.r_U_primary);
+    assert_eq!(r_U_secondary, recursive_snark.r_U_secondary);
+    assert_eq!(l_u_secondary, recursive_snark.l_u_secondary);
+
+    // the checks on the hash chain agree with verify
+    let wrong_z0 = [<E1 as Engine>::Scalar::ONE];
+    assert_eq!(
+      recursive_snark
+        .verify_trusting_instances(&pp, 3, &wrong_z0)
+        .err(),
+      recursive_snark.verify(&pp, 3, &wrong_z0).err()
+    );
+    assert_eq!(
+      recursive_snark.verify_trusting_instances(&pp, 4, &z0).err(),
+      recursive_snark.verify(&pp, 4, &z0).err()
+    );
+
+    // an unsatisfying witness is only caught by verify
+    let mut corrupted = recursive_snark.clone();
+    corrupted.r_W_primary = RelaxedR1CSWitness::default(&pp.r1cs_shape_primary);
+    assert!(corrupted.verify_trusting_instances(&pp, 3, &z0).is_ok());
+    assert!(corrupted.verify(&pp, 3, &z0).is_err());
+  }
+
+  #[test]
+  fn test_verify_trusting_instances() {
+    test_verify_trusting_instances_with::<PallasEngine, VestaEngine>();
+  }
+
+  fn test_shape_digest_with<E1, E2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+  {
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let pp_larger_ck = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &|_| 1 << 15,
+      &|_| 1 << 15,
+    )
+    .unwrap();
+    assert_eq!(pp.shape_digest(), pp_larger_ck.shape_digest());
+    assert_ne!(pp.digest(), pp_larger_ck.digest());
+
+    let other_circuit = TrivialCircuit::default();
+    let pp_other = PublicParams::<E1, E2, TrivialCircuit<<E1 as Engine>::Scalar>>::setup(
+      &other_circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    assert_ne!(pp.shape_digest(), pp_other.shape_digest());
+  }
+
+  #[test]
+  fn test_shape_digest() {
+    test_shape_digest_with::<PallasEngine, VestaEngine>();
+  }
+
+  // computes `x^3 + x + 5` as `CubicCircuit`, but allocates the output from advice that is off by
+  // one when `wrong_advice` is set, which leaves the constraints unsatisfied
+  #[derive(Clone, Debug, Default)]
+  struct AdviceCubicCircuit<F: PrimeField> {
+    wrong_advice: bool,
+    _p: PhantomData<F>,
+  }
+
+  impl<F: PrimeField> StepCircuit<F> for AdviceCubicCircuit<F> {
+    fn arity(&self) -> usize {
+      1
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+      &self,
+      cs: &mut CS,
+      z: &[AllocatedNum<F>],
+    ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+      let x = &z[0];
+      let x_sq = x.square(cs.namespace(|| "x_sq"))?;
+      let x_cu = x_sq.mul(cs.namespace(|| "x_cu"), x)?;
+      let y = AllocatedNum::alloc(cs.namespace(|| "y"), || {
+        let y = x_cu.get_value().unwrap() + x.get_value().unwrap() + F::from(5u64);
+        Ok(if self.wrong_advice { y + F::ONE } else { y })
+      })?;
+
+      cs.enforce(
+        || "y = x^3 + x + 5",
+        |lc| {
+          lc + x_cu.get_variable()
+            + x.get_variable()
+            + CS::one()
+            + CS::one()
+            + CS::one()
+            + CS::one()
+            + CS::one()
+        },
+        |lc| lc + CS::one(),
+        |lc| lc + y.get_variable(),
+      );
+
+      Ok(vec![y])
+    }
+  }
+
+  fn test_prove_step_unsatisfiable_with<E1, E2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+  {
+    let circuit = AdviceCubicCircuit::default();
+    let circuit_wrong = AdviceCubicCircuit {
+      wrong_advice: true,
+      _p: PhantomData,
+    };
+    let pp = PublicParams::<E1, E2, AdviceCubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    // `new` and `prove_step` only check the step circuit in debug builds
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    if cfg!(debug_assertions) {
+      assert_eq!(
+        RecursiveSNARK::new(&pp, &circuit_wrong, &z0).err(),
+        Some(NovaError::StepCircuitUnsatisfiable { step: 1 })
+      );
+    }
+
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    assert_eq!(recursive_snark.prove_step(&pp, &circuit), Ok(1));
+    if cfg!(debug_assertions) {
+      assert_eq!(
+        recursive_snark.prove_step(&pp, &circuit_wrong),
+        Err(NovaError::StepCircuitUnsatisfiable { step: 2 })
+      );
+    }
+
+    // while `prove_step_checked` always does
+    assert_eq!(
+      recursive_snark.prove_step_checked(&pp, &circuit_wrong),
+      Err(NovaError::StepCircuitUnsatisfiable { step: 2 })
+    );
+
+    // the failed step leaves the proof unchanged
+    assert_eq!(recursive_snark.num_steps(), 1);
+    assert_eq!(recursive_snark.prove_step(&pp, &circuit), Ok(2));
+    assert!(recursive_snark.verify(&pp, 2, &z0).is_ok());
+  }
+
+  #[test]
+  fn test_prove_step_unsatisfiable() {
+    test_prove_step_unsatisfiable_with::<PallasEngine, VestaEngine>();
+  }
+
+  fn test_self_check_with<E1, E2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+  {
+    let circuit = CubicCircuit::default();
+    let mut pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    assert_eq!(pp.self_check(), Ok(()));
+
+    // a shape with the wrong number of public outputs is rejected
+    pp.r1cs_shape_secondary.num_io = 3;
+    assert_eq!(
+      pp.self_check(),
+      Err(NovaError::InvalidPublicParams {
+        reason: "R1CS shapes do not have two public outputs".to_string()
+      })
+    );
+    pp.r1cs_shape_secondary.num_io = 2;
+
+    // a stale cached digest is rejected
+    pp.F_arity = 2;
+    assert_eq!(
+      pp.self_check(),
+      Err(NovaError::InvalidPublicParams {
+        reason: "cached digest does not match the public parameters".to_string()
+      })
+    );
+  }
+
+  #[test]
+  fn test_self_check() {
+    test_self_check_with::<PallasEngine, VestaEngine>();
+    test_self_check_with::<Bn256EngineKZG, GrumpkinEngine>();
+  }
+
+  #[test]
+  fn test_queue_primary_steps() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    let circuit = CubicCircuit::default();
+    let z0 = [F::ONE];
+
+    // the balanced approach folds the secondary circuit at every step
+    let pp = PublicParams::<E1, E2, CubicCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    let mut outputs = Vec::new();
+    for _i in 0..10 {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+      outputs.push(recursive_snark.outputs().to_vec());
+    }
+
+    // fold the secondary circuit once for every four steps
+    let pp_batched = PublicParams::<E1, E2, CubicCircuit<F>>::setup_with_steps_per_fold(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+      4,
+    )
+    .unwrap();
+    assert_eq!(pp_batched.steps_per_fold(), 4);
+    assert_ne!(pp_batched.digest(), pp.digest());
+
+    let mut batched = RecursiveSNARK::new(&pp_batched, &circuit, &z0).unwrap();
+    for num_steps in [4, 8] {
+      assert_eq!(
+        batched.queue_primary_steps(&pp_batched, &circuit, 4),
+        Ok(num_steps)
+      );
+      assert_eq!(batched.sync_secondary(&pp_batched), Ok(num_steps));
+      assert_eq!(
+        batched.verify(&pp_batched, num_steps, &z0).unwrap(),
+        outputs[num_steps - 1]
+      );
+    }
+
+    // a fold may execute fewer steps, but no more than the parameters allow
+    assert_eq!(
+      batched.queue_primary_steps(&pp_batched, &circuit, 5),
+      Err(NovaError::InvalidNumSteps)
+    );
+    assert_eq!(
+      batched.queue_primary_steps(&pp_batched, &circuit, 2),
+      Ok(10)
+    );
+    assert_eq!(
+      batched.prove_step(&pp_batched, &circuit),
+      Err(NovaError::PendingPrimarySteps)
+    );
+    // queued steps are not part of the proof, so they cannot be serialized
+    assert!(bincode::serialize(&batched).is_err());
+    assert_eq!(batched.sync_secondary(&pp_batched), Ok(10));
+    let bytes = bincode::serialize(&batched).unwrap();
+    let mut batched: RecursiveSNARK<E1, E2, CubicCircuit<F>> =
+      bincode::deserialize(&bytes).unwrap();
+    assert_eq!(batched.sync_secondary(&pp_batched), Ok(10));
+    assert_eq!(batched.verify(&pp_batched, 10, &z0).unwrap(), outputs[9]);
+
+    // and a fold of a single step is the same as `prove_step`
+    batched.prove_step(&pp_batched, &circuit).unwrap();
+    assert!(batched.verify(&pp_batched, 11, &z0).is_ok());
+  }
+
+  #[test]
+  fn test_setup_with_options() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
 
-    // verify the recursive SNARK
-    let res = recursive_snark.verify(&pp, num_steps, &[<E1 as Engine>::Scalar::ZERO]);
-    assert!(res.is_ok());
+    let circuit = CubicCircuit::default();
+    let setup = |options: SetupOptions<E1>| {
+      PublicParams::<E1, E2, CubicCircuit<F>>::setup_with_options(
+        &circuit,
+        &*default_ck_hint(),
+        &*default_ck_hint(),
+        options,
+      )
+    };
 
-    let zn = res.unwrap();
+    // the default options are those of `setup`
+    let plain_pp = PublicParams::<E1, E2, CubicCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    assert_eq!(
+      setup(SetupOptions::default()).unwrap().digest(),
+      plain_pp.digest()
+    );
 
-    assert_eq!(zn, vec![<E1 as Engine>::Scalar::from(5u64)]);
+    // options with separate constructors can be combined
+    let options = SetupOptions::default()
+      .with_data_chain(true)
+      .with_steps_per_fold(2)
+      .with_context(&[F::from(7u64)]);
+    let pp = setup(options.clone()).unwrap();
+    assert_eq!(pp.options(), &options);
+    assert_ne!(pp.digest(), plain_pp.digest());
+
+    let z0 = [F::ONE];
+    let data = <E1 as Engine>::CE::commit(&pp.ck_primary, &[F::from(3u64)], &F::ZERO);
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    assert_eq!(recursive_snark.queue_primary_steps(&pp, &circuit, 2), Ok(2));
+    assert_eq!(recursive_snark.sync_secondary(&pp), Ok(2));
+    assert_eq!(
+      recursive_snark.prove_step_with_data(&pp, &circuit, Some(data)),
+      Ok(3)
+    );
+    assert!(recursive_snark.verify(&pp, 3, &z0).is_ok());
+
+    // a trivial secondary circuit has no state to hold the data chain
+    assert!(matches!(
+      setup(
+        SetupOptions::default()
+          .with_trivial_secondary(true)
+          .with_data_chain(true)
+      ),
+      Err(NovaError::InvalidPublicParams { .. })
+    ));
+    assert_eq!(
+      setup(SetupOptions::default().with_steps_per_fold(0)).err(),
+      Some(NovaError::InvalidNumSteps)
+    );
   }
 
   #[test]
-  fn test_ivc_base() {
-    test_ivc_base_with::<PallasEngine, VestaEngine>();
-    test_ivc_base_with::<Bn256EngineKZG, GrumpkinEngine>();
-    test_ivc_base_with::<Secp256k1Engine, Secq256k1Engine>();
+  fn test_pp_deserialize_without_new_fields() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    // parameters serialized before the seed commitment, the options, and the shape format
+    // version were added deserialize with their defaults
+    let mut value = serde_json::to_value(&pp).unwrap();
+    let fields = value.as_object_mut().unwrap();
+    for field in ["ck_seed_commitment", "options", "shape_format_version"] {
+      assert!(fields.remove(field).is_some());
+    }
+    let old_pp: PublicParams<E1, E2, CubicCircuit<F>> = serde_json::from_value(value).unwrap();
+    assert_eq!(old_pp.options(), &SetupOptions::default());
+    assert_eq!(old_pp.shape_format_version(), SHAPE_FORMAT_VERSION);
+    assert_eq!(old_pp.self_check(), Ok(()));
+    assert_eq!(old_pp.digest(), pp.digest());
+  }
+
+  #[test]
+  fn test_new_with_stale_digest() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    let circuit = CubicCircuit::default();
+    let mut pp = PublicParams::<E1, E2, CubicCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    assert_eq!(pp.check_digest(), Ok(()));
+
+    // a corrupted cached digest is rejected rather than baked into the proof
+    let digest = pp.digest();
+    pp.digest = OnceCell::from(digest + F::ONE);
+    assert_eq!(pp.check_digest(), Err(NovaError::StalePublicParamsDigest));
+    assert_eq!(
+      RecursiveSNARK::new(&pp, &circuit, &[F::ONE]).err(),
+      Some(NovaError::StalePublicParamsDigest)
+    );
+
+    // as is a digest that was cached before the parameters changed
+    pp.digest = OnceCell::from(digest);
+    pp.options.context = vec![F::ONE];
+    assert_eq!(pp.check_digest(), Err(NovaError::StalePublicParamsDigest));
+    assert_eq!(
+      RecursiveSNARK::new(&pp, &circuit, &[F::ONE]).err(),
+      Some(NovaError::StalePublicParamsDigest)
+    );
+
+    // once the digest is recomputed, a proof can be produced
+    pp.digest = OnceCell::new();
+    pp.digest_fingerprint = OnceCell::new();
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &[F::ONE]).unwrap();
+    recursive_snark.prove_step(&pp, &circuit).unwrap();
+    assert!(recursive_snark.verify(&pp, 1, &[F::ONE]).is_ok());
+
+    // growing the keys recomputes the digest
+    pp.options.context = vec![];
+    pp.digest = OnceCell::from(digest);
+    pp.grow_keys(pp.commitment_key_sizes().0 * 2, 0).unwrap();
+    assert_ne!(pp.digest(), digest);
+    assert_eq!(pp.check_digest(), Ok(()));
+  }
+
+  #[test]
+  fn test_shape_format_version() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+
+    let circuit = CubicCircuit::default();
+    let mut pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    assert_eq!(pp.shape_format_version(), SHAPE_FORMAT_VERSION);
+    let digest = pp.digest();
+
+    // parameters produced with another version have another digest, and are rejected
+    pp.shape_format_version = SHAPE_FORMAT_VERSION + 1;
+    pp.digest = OnceCell::new();
+    pp.digest_fingerprint = OnceCell::new();
+    assert_ne!(pp.digest(), digest);
+    assert_eq!(
+      pp.self_check(),
+      Err(NovaError::InvalidPublicParams {
+        reason: "R1CS shapes were synthesized with a different shape format version".to_string()
+      })
+    );
+  }
+
+  #[test]
+  fn test_recursive_snark_bytes() {
+    test_recursive_snark_bytes_with::<PallasEngine, VestaEngine>();
+  }
+
+  fn test_ro_constants_with<E1, E2, EE1, EE2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+    EE1: EvaluationEngineTrait<E1>,
+    EE2: EvaluationEngineTrait<E2>,
+  {
+    let circuit = CubicCircuit::<<E1 as Engine>::Scalar>::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let num_steps = 3;
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp, &circuit, &z0).unwrap();
+    for _i in 0..num_steps {
+      recursive_snark.prove_step(&pp, &circuit).unwrap();
+    }
+    assert!(recursive_snark.verify(&pp, num_steps, &z0).is_ok());
+
+    // export the constants and reconstruct them elsewhere
+    let (ro_consts_primary, ro_consts_secondary) = pp.ro_constants();
+    let ro_consts_primary: ROConstants<E1> =
+      bincode::deserialize(&bincode::serialize(ro_consts_primary).unwrap()).unwrap();
+    let ro_consts_secondary: ROConstants<E2> =
+      bincode::deserialize(&bincode::serialize(ro_consts_secondary).unwrap()).unwrap();
+
+    // replicate the hash chain checked by `verify`
+    let mut hasher = <E2 as Engine>::RO::new(ro_consts_secondary);
+    hasher.absorb(pp.digest());
+    hasher.absorb(<E1 as Engine>::Scalar::from(num_steps as u64));
+    for e in z0.iter().chain(recursive_snark.outputs()) {
+      hasher.absorb(*e);
+    }
+    recursive_snark.r_U_secondary.absorb_in_ro(&mut hasher);
+    hasher.absorb(recursive_snark.ri_primary);
+    assert_eq!(
+      hasher.squeeze(NUM_HASH_BITS),
+      scalar_as_base::<E2>(recursive_snark.l_u_secondary.X[0])
+    );
+
+    let mut hasher2 = <E1 as Engine>::RO::new(ro_consts_primary);
+    hasher2.absorb(scalar_as_base::<E1>(pp.digest()));
+    hasher2.absorb(<E2 as Engine>::Scalar::from(num_steps as u64));
+    hasher2.absorb(<E2 as Engine>::Scalar::ZERO);
+    hasher2.absorb(<E2 as Engine>::Scalar::ZERO);
+    recursive_snark.r_U_primary.absorb_in_ro(&mut hasher2);
+    hasher2.absorb(recursive_snark.ri_secondary);
+    assert_eq!(
+      hasher2.squeeze(NUM_HASH_BITS),
+      recursive_snark.l_u_secondary.X[1]
+    );
+
+    // the verifier key exposes the same constants
+    let (_pk, vk) = CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::setup(&pp).unwrap();
+    assert_eq!(
+      bincode::serialize(&vk.ro_constants()).unwrap(),
+      bincode::serialize(&pp.ro_constants()).unwrap()
+    );
+  }
+
+  #[test]
+  fn test_ro_constants() {
+    test_ro_constants_with::<PallasEngine, VestaEngine, EE<_>, EE<_>>();
+  }
+
+  fn test_setup_with_ck_seed_with<E1, E2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+  {
+    let circuit = TrivialCircuit::<<E1 as Engine>::Scalar>::default();
+    let setup = |seed: [u8; 32]| {
+      PublicParams::<E1, E2, TrivialCircuit<<E1 as Engine>::Scalar>>::setup_with_ck_seed(
+        &circuit,
+        &*default_ck_hint(),
+        &*default_ck_hint(),
+        seed,
+      )
+      .unwrap()
+    };
+    let ck_bytes = |pp: &PublicParams<E1, E2, TrivialCircuit<<E1 as Engine>::Scalar>>| {
+      (
+        bincode::serialize(&pp.ck_primary).unwrap(),
+        bincode::serialize(&pp.ck_secondary).unwrap(),
+      )
+    };
+
+    // the same seed reproduces identical keys and digests
+    let pp1 = setup([1u8; 32]);
+    let pp2 = setup([1u8; 32]);
+    assert_eq!(ck_bytes(&pp1), ck_bytes(&pp2));
+    assert_eq!(pp1.digest(), pp2.digest());
+
+    // a different seed yields different keys and digests
+    let pp3 = setup([2u8; 32]);
+    let (ck_primary1, ck_secondary1) = ck_bytes(&pp1);
+    let (ck_primary3, ck_secondary3) = ck_bytes(&pp3);
+    assert_ne!(ck_primary1, ck_primary3);
+    assert_ne!(ck_secondary1, ck_secondary3);
+    assert_ne!(pp1.digest(), pp3.digest());
+
+    // the seeded parameters differ from the unseeded ones
+    let pp = PublicParams::<E1, E2, TrivialCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    assert_ne!(pp.digest(), pp1.digest());
+
+    // the seeded parameters can be used to prove and verify
+    let mut recursive_snark =
+      RecursiveSNARK::new(&pp1, &circuit, &[<E1 as Engine>::Scalar::ZERO]).unwrap();
+    assert!(recursive_snark.prove_step(&pp1, &circuit).is_ok());
+    assert!(recursive_snark
+      .verify(&pp1, 1, &[<E1 as Engine>::Scalar::ZERO])
+      .is_ok());
+  }
+
+  #[test]
+  fn test_setup_with_ck_seed() {
+    test_setup_with_ck_seed_with::<PallasEngine, VestaEngine>();
+    test_setup_with_ck_seed_with::<Secp256k1Engine, Secq256k1Engine>();
+
+    // HyperKZG cannot derive its commitment key from a public seed
+    let circuit = TrivialCircuit::<<Bn256EngineKZG as Engine>::Scalar>::default();
+    let res = PublicParams::<Bn256EngineKZG, GrumpkinEngine, _>::setup_with_ck_seed(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+      [1u8; 32],
+    );
+    assert_eq!(res.err(), Some(NovaError::UnsupportedCommitmentKeySeed));
+  }
+
+  fn test_setup_optimized_trivial_secondary_with<E1, E2, EE1, EE2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+    EE1: EvaluationEngineTrait<E1>,
+    EE2: EvaluationEngineTrait<E2>,
+  {
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let pp_optimized =
+      PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup_optimized_trivial_secondary(
+        &circuit,
+        &*default_ck_hint(),
+        &*default_ck_hint(),
+      )
+      .unwrap();
+
+    // only the secondary circuit is specialized
+    assert_eq!(pp_optimized.num_constraints().0, pp.num_constraints().0);
+    assert!(pp_optimized.num_constraints().1 < pp.num_constraints().1);
+    assert_ne!(pp_optimized.digest(), pp.digest());
+
+    let num_steps = 3;
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp_optimized, &circuit, &z0).unwrap();
+    for _i in 0..num_steps {
+      recursive_snark.prove_step(&pp_optimized, &circuit).unwrap();
+    }
+    let zn = recursive_snark
+      .verify(&pp_optimized, num_steps, &z0)
+      .unwrap();
+    assert_eq!(zn, vec![<E1 as Engine>::Scalar::from(2460515u64)]);
+
+    // the proof is bound to the optimized parameters
+    assert!(recursive_snark.verify(&pp, num_steps, &z0).is_err());
+
+    let (pk, vk) =
+      CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::setup(&pp_optimized).unwrap();
+    let compressed_snark = CompressedSNARK::<_, _, _, S<E1, EE1>, S<E2, EE2>>::prove(
+      &pp_optimized,
+      &pk,
+      &recursive_snark,
+    )
+    .unwrap();
+    assert_eq!(compressed_snark.verify(&vk, num_steps, &z0).unwrap(), zn);
+  }
+
+  #[test]
+  fn test_setup_optimized_trivial_secondary() {
+    test_setup_optimized_trivial_secondary_with::<PallasEngine, VestaEngine, EE<_>, EE<_>>();
+    test_setup_optimized_trivial_secondary_with::<Secp256k1Engine, Secq256k1Engine, EE<_>, EE<_>>();
+  }
+
+  fn test_derive_for_with<E1, E2>()
+  where
+    E1: Engine<Base = <E2 as Engine>::Scalar>,
+    E2: Engine<Base = <E1 as Engine>::Scalar>,
+  {
+    #[derive(Clone, Debug, Default)]
+    struct SquaringCircuit<F: PrimeField> {
+      num_squares: usize,
+      _p: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> StepCircuit<F> for SquaringCircuit<F> {
+      fn arity(&self) -> usize {
+        1
+      }
+
+      fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<F>],
+      ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+        let mut x = z[0].clone();
+        for i in 0..self.num_squares {
+          x = x.square(cs.namespace(|| format!("x_{i}")))?;
+        }
+        Ok(vec![x])
+      }
+    }
+
+    // over-provision the primary key so that it can be shared with larger circuits
+    let trivial = TrivialCircuit::<<E1 as Engine>::Scalar>::default();
+    let pp = PublicParams::<E1, E2, _>::setup(&trivial, &|_| 1 << 15, &*default_ck_hint()).unwrap();
+
+    let circuit = CubicCircuit::<<E1 as Engine>::Scalar>::default();
+    let pp_derived = pp.derive_for(&circuit).unwrap();
+    assert!(pp_derived.num_constraints().0 > pp.num_constraints().0);
+    assert_ne!(pp_derived.digest(), pp.digest());
+
+    let num_steps = 3;
+    let z0 = [<E1 as Engine>::Scalar::ZERO];
+    let mut recursive_snark = RecursiveSNARK::new(&pp_derived, &circuit, &z0).unwrap();
+    for _i in 0..num_steps {
+      recursive_snark.prove_step(&pp_derived, &circuit).unwrap();
+    }
+    let zn = recursive_snark.verify(&pp_derived, num_steps, &z0).unwrap();
+    assert_eq!(zn, vec![<E1 as Engine>::Scalar::from(2460515u64)]);
+
+    // a circuit that does not fit in the shared keys is rejected
+    let oversize = SquaringCircuit::<<E1 as Engine>::Scalar> {
+      num_squares: 1 << 15,
+      _p: PhantomData,
+    };
+    assert_eq!(
+      pp.derive_for(&oversize).err(),
+      Some(NovaError::InvalidCommitmentKeyLength)
+    );
+  }
+
+  #[test]
+  fn test_derive_for() {
+    test_derive_for_with::<PallasEngine, VestaEngine>();
+    test_derive_for_with::<Bn256EngineKZG, GrumpkinEngine>();
+  }
+
+  #[test]
+  fn test_setup_with_constraint_limit() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    // a step circuit that never stops squaring, as if driven by a runaway loop
+    #[derive(Clone, Debug, Default)]
+    struct RunawayCircuit<F: PrimeField> {
+      _p: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> StepCircuit<F> for RunawayCircuit<F> {
+      fn arity(&self) -> usize {
+        1
+      }
+
+      fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<F>],
+      ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+        let mut x = z[0].clone();
+        for i in 0.. {
+          x = x.square(cs.namespace(|| format!("x_{i}")))?;
+        }
+        Ok(vec![x])
+      }
+    }
+
+    // the runaway circuit fails cleanly at the limit
+    let limit = 1 << 16;
+    let res = PublicParams::<E1, E2, RunawayCircuit<F>>::setup_with_constraint_limit(
+      &RunawayCircuit::default(),
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+      limit,
+    );
+    assert_eq!(
+      res.err(),
+      Some(NovaError::from(SynthesisError::ConstraintLimitExceeded(
+        limit
+      )))
+    );
+
+    // a circuit within the limit yields the same parameters as `setup`
+    let circuit = CubicCircuit::default();
+    let pp = PublicParams::<E1, E2, CubicCircuit<F>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+    let pp_limit = PublicParams::<E1, E2, CubicCircuit<F>>::setup_with_constraint_limit(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+      pp.num_constraints().0,
+    )
+    .unwrap();
+    assert_eq!(pp_limit.digest(), pp.digest());
+
+    // and fails if the limit is below its number of constraints
+    assert!(
+      PublicParams::<E1, E2, CubicCircuit<F>>::setup_with_constraint_limit(
+        &circuit,
+        &*default_ck_hint(),
+        &*default_ck_hint(),
+        pp.num_constraints().0 - 1,
+      )
+      .is_err()
+    );
+  }
+
+  #[test]
+  fn test_min_key_sizes() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+
+    let circuit = CubicCircuit::default();
+    let (min_primary, min_secondary) =
+      PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::min_key_sizes(&circuit)
+        .unwrap();
+    let pp = PublicParams::<E1, E2, CubicCircuit<<E1 as Engine>::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    // the minimum is that of the shapes, and no larger than the keys `default_ck_hint` produces
+    assert_eq!(min_primary, pp.r1cs_shape_primary.min_commitment_key_size());
+    assert_eq!(
+      min_secondary,
+      pp.r1cs_shape_secondary.min_commitment_key_size()
+    );
+    assert!(min_primary <= pp.ck_primary.length());
+    assert!(min_secondary <= pp.ck_secondary.length());
   }
 
   fn test_setup_with<E1, E2>()
@@ -1481,6 +6006,40 @@ mod tests {
     );
     assert!(pp.is_err());
     assert_eq!(pp.err(), Some(NovaError::InvalidStepCircuitIO));
+
+    #[derive(Clone, Debug, Default)]
+    struct ZeroArityCircuit<F: PrimeField> {
+      _p: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> StepCircuit<F> for ZeroArityCircuit<F> {
+      fn arity(&self) -> usize {
+        0
+      }
+
+      fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        _cs: &mut CS,
+        _z: &[AllocatedNum<F>],
+      ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+        Ok(vec![])
+      }
+    }
+
+    // a circuit without state is rejected before generating commitment keys
+    let circuit = ZeroArityCircuit::<E1::Scalar>::default();
+    let pp = PublicParams::<E1, E2, ZeroArityCircuit<E1::Scalar>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    );
+    assert_eq!(pp.err(), Some(NovaError::ZeroArity));
+    let pp = PublicParams::<E1, E2, ZeroArityCircuit<E1::Scalar>>::setup_with_data_chain(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    );
+    assert_eq!(pp.err(), Some(NovaError::ZeroArity));
   }
 
   #[test]
@@ -9,7 +9,7 @@ use crate::{
   Commitment, CommitmentKey,
 };
 use ff::Field;
-use rand_core::OsRng;
+use rand_core::{OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 
 /// An NIFS message from Nova's folding scheme
@@ -42,6 +42,21 @@ impl<E: Engine> NIFS<E> {
     W1: &RelaxedR1CSWitness<E>,
     U2: &R1CSInstance<E>,
     W2: &R1CSWitness<E>,
+  ) -> Result<(NIFS<E>, (RelaxedR1CSInstance<E>, RelaxedR1CSWitness<E>)), NovaError> {
+    Self::prove_with_rng(ck, ro_consts, pp_digest, S, U1, W1, U2, W2, OsRng)
+  }
+
+  /// Same as `prove`, but samples the blind of the cross-term commitment from `rng`
+  pub fn prove_with_rng(
+    ck: &CommitmentKey<E>,
+    ro_consts: &ROConstants<E>,
+    pp_digest: &E::Scalar,
+    S: &R1CSShape<E>,
+    U1: &RelaxedR1CSInstance<E>,
+    W1: &RelaxedR1CSWitness<E>,
+    U2: &R1CSInstance<E>,
+    W2: &R1CSWitness<E>,
+    mut rng: impl RngCore,
   ) -> Result<(NIFS<E>, (RelaxedR1CSInstance<E>, RelaxedR1CSWitness<E>)), NovaError> {
     // initialize a new RO
     let mut ro = E::RO::new(ro_consts.clone());
@@ -53,7 +68,7 @@ impl<E: Engine> NIFS<E> {
     U2.absorb_in_ro(&mut ro);
 
     // compute a commitment to the cross-term
-    let r_T = E::Scalar::random(&mut OsRng);
+    let r_T = E::Scalar::random(&mut rng);
     let (T, comm_T) = S.commit_T(ck, U1, W1, U2, W2, &r_T)?;
 
     // append `comm_T` to the transcript and obtain a challenge
@@ -63,7 +78,7 @@ impl<E: Engine> NIFS<E> {
     let r = base_as_scalar::<E>(ro.squeeze(NUM_CHALLENGE_BITS));
 
     // fold the instance using `r` and `comm_T`
-    let U = U1.fold(U2, &comm_T, &r);
+    let U = U1.fold_with_challenge(U2, &comm_T, &r);
 
     // fold the witness using `r` and `T`
     let W = W1.fold(W2, &T, &r_T, &r)?;
@@ -84,6 +99,27 @@ impl<E: Engine> NIFS<E> {
     U1: &RelaxedR1CSInstance<E>,
     U2: &R1CSInstance<E>,
   ) -> Result<RelaxedR1CSInstance<E>, NovaError> {
+    // derive the challenge from the transcript
+    let r = self.challenge(ro_consts, pp_digest, U2);
+
+    // fold the instance using `r` and `comm_T`
+    let U = U1.fold_with_challenge(U2, &self.comm_T, &r);
+
+    // return the folded instance
+    Ok(U)
+  }
+
+  /// Derives the challenge with which `verify` folds the incoming instance `U2`.
+  ///
+  /// This separates hashing the transcript from the folding arithmetic: the challenge can
+  /// be computed as soon as `U2` and `comm_T` are known, and the fold performed later with
+  /// `RelaxedR1CSInstance::fold_with_challenge`, which yields the same instance as `verify`.
+  pub fn challenge(
+    &self,
+    ro_consts: &ROConstants<E>,
+    pp_digest: &E::Scalar,
+    U2: &R1CSInstance<E>,
+  ) -> E::Scalar {
     // initialize a new RO
     let mut ro = E::RO::new(ro_consts.clone());
 
@@ -97,13 +133,113 @@ impl<E: Engine> NIFS<E> {
     self.comm_T.absorb_in_ro(&mut ro);
 
     // compute a challenge from the RO
-    let r = ro.squeeze(NUM_CHALLENGE_BITS);
+    base_as_scalar::<E>(ro.squeeze(NUM_CHALLENGE_BITS))
+  }
+}
 
-    // fold the instance using `r` and `comm_T`
-    let U = U1.fold(U2, &self.comm_T, &base_as_scalar::<E>(r));
+/// An NIFS message from Nova's folding scheme when folding a batch of R1CS instances at once
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct NIFSMulti<E: Engine> {
+  pub(crate) comm_T: Vec<Commitment<E>>,
+}
 
-    // return the folded instance
-    Ok(U)
+impl<E: Engine> NIFS<E> {
+  /// Same as `prove`, but folds a batch of `k` R1CS instance-witness tuples `(U2[i], W2[i])`
+  /// into `(U1, W1)` with a single challenge `r`, where the `i`-th tuple is weighted by `r^i`.
+  ///
+  /// This requires a single round of interaction and a single pass over the matrices for
+  /// all the instances, at the cost of committing to `2k` cross-terms.
+  /// Unlike `prove`, the running instance `U1` is absorbed in the RO, as the incoming
+  /// instances are not required to carry its hash in their public IO.
+  pub fn prove_multi(
+    ck: &CommitmentKey<E>,
+    ro_consts: &ROConstants<E>,
+    pp_digest: &E::Scalar,
+    S: &R1CSShape<E>,
+    U1: &RelaxedR1CSInstance<E>,
+    W1: &RelaxedR1CSWitness<E>,
+    U2: &[R1CSInstance<E>],
+    W2: &[R1CSWitness<E>],
+  ) -> Result<
+    (
+      NIFSMulti<E>,
+      (RelaxedR1CSInstance<E>, RelaxedR1CSWitness<E>),
+    ),
+    NovaError,
+  > {
+    if U2.is_empty() || U2.len() != W2.len() {
+      return Err(NovaError::InvalidInputLength);
+    }
+
+    // initialize a new RO
+    let mut ro = E::RO::new(ro_consts.clone());
+
+    // append the digest of pp, U1, and the incoming instances to the transcript
+    ro.absorb(scalar_as_base::<E>(*pp_digest));
+    U1.absorb_in_ro(&mut ro);
+    for U in U2 {
+      U.absorb_in_ro(&mut ro);
+    }
+
+    // compute commitments to the cross-terms
+    let r_T = (0..2 * U2.len())
+      .map(|_| E::Scalar::random(&mut OsRng))
+      .collect::<Vec<_>>();
+    let (T, comm_T) = S.commit_T_multi(ck, U1, W1, U2, W2, &r_T)?;
+
+    // append the commitments to the transcript and obtain a challenge
+    for comm in &comm_T {
+      comm.absorb_in_ro(&mut ro);
+    }
+    let r = base_as_scalar::<E>(ro.squeeze(NUM_CHALLENGE_BITS));
+
+    // fold the instances and the witnesses using powers of `r`
+    let U = U1.fold_multi(U2, &comm_T, &r);
+    let W = W1.fold_multi(W2, &T, &r_T, &r)?;
+
+    Ok((NIFSMulti { comm_T }, (U, W)))
+  }
+}
+
+impl<E: Engine> NIFSMulti<E> {
+  /// Takes as input a relaxed R1CS instance `U1` and a batch of R1CS instances `U2`
+  /// folded by `NIFS::prove_multi`, and outputs the folded instance
+  pub fn verify(
+    &self,
+    ro_consts: &ROConstants<E>,
+    pp_digest: &E::Scalar,
+    U1: &RelaxedR1CSInstance<E>,
+    U2: &[R1CSInstance<E>],
+  ) -> Result<RelaxedR1CSInstance<E>, NovaError> {
+    if U2.is_empty() {
+      return Err(NovaError::InvalidInputLength);
+    }
+    if self.comm_T.len() != 2 * U2.len() {
+      return Err(NovaError::ProofVerifyError {
+        reason: "Invalid number of cross-term commitments".to_string(),
+      });
+    }
+
+    // initialize a new RO
+    let mut ro = E::RO::new(ro_consts.clone());
+
+    // append the digest of pp, U1, and the incoming instances to the transcript
+    ro.absorb(scalar_as_base::<E>(*pp_digest));
+    U1.absorb_in_ro(&mut ro);
+    for U in U2 {
+      U.absorb_in_ro(&mut ro);
+    }
+
+    // append the commitments to the transcript and obtain a challenge
+    for comm in &self.comm_T {
+      comm.absorb_in_ro(&mut ro);
+    }
+    let r = base_as_scalar::<E>(ro.squeeze(NUM_CHALLENGE_BITS));
+
+    // fold the instances using powers of `r`
+    Ok(U1.fold_multi(U2, &self.comm_T, &r))
   }
 }
 
@@ -117,6 +253,7 @@ pub struct NIFSRelaxed<E: Engine> {
 
 impl<E: Engine> NIFSRelaxed<E> {
   /// Same as `prove`, but takes two Relaxed R1CS Instance/Witness pairs
+  /// and samples the blind of the cross-term commitment from `rng`
   pub fn prove(
     ck: &CommitmentKey<E>,
     ro_consts: &ROConstants<E>,
@@ -126,6 +263,7 @@ impl<E: Engine> NIFSRelaxed<E> {
     W1: &RelaxedR1CSWitness<E>,
     U2: &RelaxedR1CSInstance<E>,
     W2: &RelaxedR1CSWitness<E>,
+    mut rng: impl RngCore,
   ) -> Result<
     (
       NIFSRelaxed<E>,
@@ -147,8 +285,8 @@ impl<E: Engine> NIFSRelaxed<E> {
     U2.absorb_in_ro(&mut ro);
 
     // compute a commitment to the cross-term
-    let r_T = E::Scalar::random(&mut OsRng);
-    E::Scalar::random(&mut OsRng);
+    let r_T = E::Scalar::random(&mut rng);
+    E::Scalar::random(&mut rng);
     let (T, comm_T) = S.commit_T_relaxed(ck, U1, W1, U2, W2, &r_T)?;
 
     // append `comm_T` to the transcript and obtain a challenge
@@ -296,6 +434,140 @@ mod tests {
     test_tiny_r1cs_bellpepper_with::<Secp256k1Engine>();
   }
 
+  fn test_tiny_r1cs_multi_with<E: Engine>() {
+    // First create the shape
+    let mut cs: TestShapeCS<E> = TestShapeCS::new();
+    let _ = synthesize_tiny_r1cs_bellpepper(&mut cs, None);
+    let (shape, ck) = cs.r1cs_shape(&*default_ck_hint());
+    let ro_consts = ROConstants::<E>::default();
+    let pp_digest = <E as Engine>::Scalar::ZERO;
+
+    // produce five satisfying instances
+    let (U, W): (Vec<_>, Vec<_>) = (0..5u64)
+      .map(|x| {
+        let mut cs = SatisfyingAssignment::<E>::new();
+        let _ = synthesize_tiny_r1cs_bellpepper(&mut cs, Some(E::Scalar::from(x)));
+        cs.r1cs_instance_and_witness(&shape, &ck).unwrap()
+      })
+      .unzip();
+
+    // fold the first instance into the default running instance
+    let (_nifs, (running_U, running_W)) = NIFS::prove(
+      &ck,
+      &ro_consts,
+      &pp_digest,
+      &shape,
+      &RelaxedR1CSInstance::default(&ck, &shape),
+      &RelaxedR1CSWitness::default(&shape),
+      &U[0],
+      &W[0],
+    )
+    .unwrap();
+
+    // fold the remaining four instances at once
+    let (nifs, (U_multi, W_multi)) = NIFS::prove_multi(
+      &ck,
+      &ro_consts,
+      &pp_digest,
+      &shape,
+      &running_U,
+      &running_W,
+      &U[1..],
+      &W[1..],
+    )
+    .unwrap();
+    let res = nifs.verify(&ro_consts, &pp_digest, &running_U, &U[1..]);
+    assert_eq!(res, Ok(U_multi.clone()));
+    assert!(shape.is_sat_relaxed(&ck, &U_multi, &W_multi).is_ok());
+
+    // the verifier rejects a proof for a different number of instances
+    let res = nifs.verify(&ro_consts, &pp_digest, &running_U, &U[1..4]);
+    assert!(res.is_err());
+
+    // folding with the powers of a challenge at once matches folding sequentially with each power
+    let r = E::Scalar::from(7u64);
+    let r_T = vec![E::Scalar::ZERO; 8];
+    let (T, comm_T) = shape
+      .commit_T_multi(&ck, &running_U, &running_W, &U[1..], &W[1..], &r_T)
+      .unwrap();
+    let U_multi = running_U.fold_multi(&U[1..], &comm_T, &r);
+    let W_multi = running_W.fold_multi(&W[1..], &T, &r_T, &r).unwrap();
+
+    let (mut U_seq, mut W_seq) = (running_U, running_W);
+    let mut r_i = E::Scalar::ONE;
+    for (U_i, W_i) in U[1..].iter().zip(&W[1..]) {
+      r_i *= r;
+      let (T_i, comm_T_i) = shape
+        .commit_T(&ck, &U_seq, &W_seq, U_i, W_i, &E::Scalar::ZERO)
+        .unwrap();
+      U_seq = U_seq.fold_with_challenge(U_i, &comm_T_i, &r_i);
+      W_seq = W_seq.fold(W_i, &T_i, &E::Scalar::ZERO, &r_i).unwrap();
+    }
+    assert_eq!(U_multi, U_seq);
+    assert_eq!(W_multi, W_seq);
+    assert!(shape.is_sat_relaxed(&ck, &U_multi, &W_multi).is_ok());
+  }
+
+  #[test]
+  fn test_tiny_r1cs_multi() {
+    test_tiny_r1cs_multi_with::<PallasEngine>();
+    test_tiny_r1cs_multi_with::<Bn256EngineKZG>();
+    test_tiny_r1cs_multi_with::<Secp256k1Engine>();
+  }
+
+  fn test_tiny_r1cs_from_parts_with<E: Engine>() {
+    let mut cs: TestShapeCS<E> = TestShapeCS::new();
+    let _ = synthesize_tiny_r1cs_bellpepper(&mut cs, None);
+    let (shape, ck) = cs.r1cs_shape(&*default_ck_hint());
+    let ro_consts = ROConstants::<E>::default();
+    let pp_digest = <E as Engine>::Scalar::ZERO;
+
+    let (U, W): (Vec<_>, Vec<_>) = (0..2u64)
+      .map(|x| {
+        let mut cs = SatisfyingAssignment::<E>::new();
+        let _ = synthesize_tiny_r1cs_bellpepper(&mut cs, Some(E::Scalar::from(x + 3)));
+        cs.r1cs_instance_and_witness(&shape, &ck).unwrap()
+      })
+      .unzip();
+
+    // scaling a satisfying assignment (W, 1, X) by u yields a relaxed instance with u and E = 0
+    let u = E::Scalar::from(2u64);
+    let W_relaxed = RelaxedR1CSWitness::from_parts(
+      W[0].W.iter().map(|w| *w * u).collect(),
+      vec![E::Scalar::ZERO; shape.num_cons],
+    );
+    let (comm_W, comm_E) = W_relaxed.commit(&ck);
+    let U_relaxed =
+      RelaxedR1CSInstance::from_parts(comm_W, comm_E, u, U[0].X.iter().map(|x| *x * u).collect());
+    assert!(shape.is_sat_relaxed(&ck, &U_relaxed, &W_relaxed).is_ok());
+
+    // the hand-built pair folds with a fresh instance
+    let (nifs, (U_folded, W_folded)) = NIFS::prove(
+      &ck, &ro_consts, &pp_digest, &shape, &U_relaxed, &W_relaxed, &U[1], &W[1],
+    )
+    .unwrap();
+    assert_eq!(
+      nifs.verify(&ro_consts, &pp_digest, &U_relaxed, &U[1]),
+      Ok(U_folded.clone())
+    );
+    assert!(shape.is_sat_relaxed(&ck, &U_folded, &W_folded).is_ok());
+
+    // an instance that does not match its witness does not fold into a satisfiable one
+    let U_bad = RelaxedR1CSInstance::from_parts(comm_W, comm_E, u + E::Scalar::ONE, U_relaxed.X);
+    let (_nifs, (U_folded, W_folded)) = NIFS::prove(
+      &ck, &ro_consts, &pp_digest, &shape, &U_bad, &W_relaxed, &U[1], &W[1],
+    )
+    .unwrap();
+    assert!(shape.is_sat_relaxed(&ck, &U_folded, &W_folded).is_err());
+  }
+
+  #[test]
+  fn test_tiny_r1cs_from_parts() {
+    test_tiny_r1cs_from_parts_with::<PallasEngine>();
+    test_tiny_r1cs_from_parts_with::<Bn256EngineKZG>();
+    test_tiny_r1cs_from_parts_with::<Secp256k1Engine>();
+  }
+
   fn execute_sequence<E: Engine>(
     ck: &CommitmentKey<E>,
     ro_consts: &ROConstants<E>,
@@ -324,6 +596,10 @@ mod tests {
 
     assert_eq!(U, _U);
 
+    // folding with a precomputed challenge matches the output of verify
+    let r = nifs.challenge(ro_consts, pp_digest, U1);
+    assert_eq!(running_U.fold_with_challenge(U1, &nifs.comm_T, &r), U);
+
     // update the running witness and instance
     running_W = W;
     running_U = U;
@@ -366,7 +642,7 @@ mod tests {
 
     // produce a step SNARK with (W1, U1) as the first incoming witness-instance pair
     let res = NIFSRelaxed::prove(
-      ck, ro_consts, pp_digest, shape, &running_U, &running_W, U1, W1,
+      ck, ro_consts, pp_digest, shape, &running_U, &running_W, U1, W1, OsRng,
     );
     assert!(res.is_ok());
     let (nifs, (_U, W)) = res.unwrap();
@@ -384,7 +660,7 @@ mod tests {
 
     // produce a step SNARK with (W2, U2) as the second incoming witness-instance pair
     let res = NIFSRelaxed::prove(
-      ck, ro_consts, pp_digest, shape, &running_U, &running_W, U2, W2,
+      ck, ro_consts, pp_digest, shape, &running_U, &running_W, U2, W2, OsRng,
     );
     assert!(res.is_ok());
     let (nifs, (_U, W)) = res.unwrap();
@@ -0,0 +1,303 @@
+//! This module implements the folding of a step circuit that takes public parameters at each step,
+//! e.g., a round constant fetched from a table, which the verifier supplies rather than the prover.
+//!
+//! `StepParamsCircuit` allocates the parameters of a step inside the step circuit, feeds them to the
+//! wrapped `ParameterizedStepCircuit`, and absorbs the same variables into a hash chain
+//! `p_{k+1} = H(p_k, params_k)`. The chain is carried as an extra element of the state, so the proof
+//! binds it like the rest of the state, and the verifier recomputes it from the expected parameters.
+use super::{
+  chained::{chained, hash, HashChain},
+  PublicParams, RecursiveSNARK,
+};
+use crate::{
+  errors::NovaError,
+  frontend::{num::AllocatedNum, ConstraintSystem, SynthesisError},
+  traits::{
+    circuit::{ParameterizedStepCircuit, StepCircuit},
+    Engine, ROConstantsCircuit,
+  },
+};
+use ff::Field;
+
+/// A step circuit that executes the `ParameterizedStepCircuit` `C` with the public parameters of the
+/// current step, and extends its state with a hash chain over the parameters of every step. Since the
+/// state of `C` is over the scalar field of the primary engine `E1`, this is a
+/// `StepParamsCircuit<E2, C>`.
+///
+/// Its state is the state of `C` followed by the hash chain, which is zero initially. A proof is
+/// verified against the parameters of every step by `RecursiveSNARK::verify_with_public_params`.
+/// The chain is computed with the default constants of the RO of `E2`, which are those of public
+/// parameters created with `setup`, or with those set by `with_ro_consts`.
+#[derive(Clone, Debug)]
+pub struct StepParamsCircuit<E: Engine, C> {
+  circuit: C,
+  params: Vec<E::Base>,
+  chain: HashChain<E>,
+}
+
+impl<E: Engine, C: ParameterizedStepCircuit<E::Base>> StepParamsCircuit<E, C> {
+  /// Wraps the step circuit `circuit`, with zero parameters, e.g., to set up public parameters
+  pub fn new(circuit: C) -> Self {
+    let params = vec![E::Base::ZERO; circuit.num_step_params()];
+    Self {
+      circuit,
+      params,
+      chain: HashChain::new(ROConstantsCircuit::<E>::default()),
+    }
+  }
+
+  /// Computes the chain with the constants `ro_consts` of the RO of `E2`, for use with public
+  /// parameters created by `PublicParams::setup_with_ro_constants` with the same
+  /// `ro_consts_secondary`
+  pub fn with_ro_consts(mut self, ro_consts: ROConstantsCircuit<E>) -> Self {
+    self.chain = HashChain::new(ro_consts);
+    self
+  }
+
+  /// Returns a copy of `self` that executes the step with the public parameters `params`, or
+  /// `NovaError::InvalidInputLength` if they are not `num_step_params()` parameters
+  pub fn with_step_params(&self, params: &[E::Base]) -> Result<Self, NovaError> {
+    if params.len() != self.circuit.num_step_params() {
+      return Err(NovaError::InvalidInputLength);
+    }
+    Ok(Self {
+      circuit: self.circuit.clone(),
+      params: params.to_vec(),
+      chain: self.chain.clone(),
+    })
+  }
+
+  /// Returns the wrapped step circuit
+  pub fn circuit(&self) -> &C {
+    &self.circuit
+  }
+}
+
+impl<E: Engine, C: ParameterizedStepCircuit<E::Base>> StepCircuit<E::Base>
+  for StepParamsCircuit<E, C>
+{
+  fn arity(&self) -> usize {
+    self.circuit.arity() + 1
+  }
+
+  fn num_public_signals(&self) -> usize {
+    self.circuit.num_public_signals()
+  }
+
+  fn synthesize<CS: ConstraintSystem<E::Base>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<E::Base>],
+  ) -> Result<Vec<AllocatedNum<E::Base>>, SynthesisError> {
+    HashChain::<E>::synthesize_step(
+      cs,
+      self.circuit.arity(),
+      self.circuit.num_public_signals(),
+      z,
+      |cs, z, chain| {
+        let params = (0..self.circuit.num_step_params())
+          .map(|j| {
+            AllocatedNum::alloc(cs.namespace(|| format!("step param {j}")), || {
+              self
+                .params
+                .get(j)
+                .copied()
+                .ok_or(SynthesisError::AssignmentMissing)
+            })
+          })
+          .collect::<Result<Vec<_>, _>>()?;
+
+        let z_next =
+          self
+            .circuit
+            .synthesize(&mut cs.namespace(|| "parameterized step"), z, &params)?;
+
+        // p_{k+1} = H(p_k, params_k), over the variables fed to the step
+        let chain_next = self.chain.synthesize_hash(
+          cs.namespace(|| "step params chain"),
+          &[core::slice::from_ref(chain), &params].concat(),
+        )?;
+        Ok((z_next, chain_next))
+      },
+    )
+  }
+
+  fn validate_initial(&self, z0: &[E::Base]) -> Result<(), String> {
+    HashChain::<E>::validate_initial(self.circuit.arity(), z0, |z0| {
+      self.circuit.validate_initial(z0)
+    })
+  }
+}
+
+impl<E1, E2, C> RecursiveSNARK<E1, E2, StepParamsCircuit<E2, C>>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: ParameterizedStepCircuit<E1::Scalar>,
+{
+  /// Creates a recursive SNARK as in `new` for the initial state `z0` of the step circuit wrapped
+  /// by `c`, whose first step is executed with the public parameters `step_params`.
+  ///
+  /// Returns `NovaError::InvalidInputLength` if `step_params` has the wrong length.
+  pub fn new_with_public_params(
+    pp: &PublicParams<E1, E2, StepParamsCircuit<E2, C>>,
+    c: &StepParamsCircuit<E2, C>,
+    z0: &[E1::Scalar],
+    step_params: &[E1::Scalar],
+  ) -> Result<Self, NovaError> {
+    Self::new(pp, &c.with_step_params(step_params)?, &chained(z0))
+  }
+
+  /// Executes a step of the incremental computation as in `prove_step`, with the public parameters
+  /// `step_params`, which the verifier supplies to `verify_with_public_params`.
+  ///
+  /// The first call records the step executed by `new_with_public_params`, which took its
+  /// parameters, so the first call takes empty parameters. Returns
+  /// `NovaError::InvalidInputLength` if `step_params` has the wrong length.
+  pub fn prove_step_with_public_params(
+    &mut self,
+    pp: &PublicParams<E1, E2, StepParamsCircuit<E2, C>>,
+    c: &StepParamsCircuit<E2, C>,
+    step_params: &[E1::Scalar],
+  ) -> Result<usize, NovaError> {
+    if self.i == 0 {
+      if !step_params.is_empty() {
+        return Err(NovaError::InvalidInputLength);
+      }
+      return self.prove_step(pp, c);
+    }
+    self.prove_step(pp, &c.with_step_params(step_params)?)
+  }
+
+  /// Verifies the `RecursiveSNARK` as in `verify` for `num_steps` steps from the initial state `z0`
+  /// of the wrapped step circuit, and checks that step `k` was executed with the public parameters
+  /// `step_params[k]`. Returns the outputs of the computation.
+  pub fn verify_with_public_params(
+    &self,
+    pp: &PublicParams<E1, E2, StepParamsCircuit<E2, C>>,
+    num_steps: usize,
+    z0: &[E1::Scalar],
+    step_params: &[Vec<E1::Scalar>],
+  ) -> Result<Vec<E1::Scalar>, NovaError> {
+    let invalid = |reason: &str| NovaError::ProofVerifyError {
+      reason: reason.to_string(),
+    };
+    if step_params.len() != num_steps {
+      return Err(invalid("expected public parameters for every step"));
+    }
+
+    let mut zn = self.verify(pp, num_steps, &chained(z0))?;
+    let chain = zn.pop().ok_or(NovaError::InvalidStepOutputLength)?;
+
+    // replay the chain from its initial value, with every step absorbing its parameters
+    let expected = step_params.iter().fold(E1::Scalar::ZERO, |chain, p| {
+      hash::<E2>(&pp.ro_consts_circuit_primary, &[&[chain], &p[..]].concat())
+    });
+    if chain != expected {
+      return Err(invalid("public parameters do not match those of the steps"));
+    }
+
+    Ok(zn)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    provider::{PallasEngine, VestaEngine},
+    traits::snark::default_ck_hint,
+  };
+
+  // z -> z * p_0 + p_1, for the public parameters p of the step
+  #[derive(Clone, Debug, Default)]
+  struct AffineCircuit;
+
+  impl<F: ff::PrimeField> ParameterizedStepCircuit<F> for AffineCircuit {
+    fn arity(&self) -> usize {
+      1
+    }
+
+    fn num_step_params(&self) -> usize {
+      2
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+      &self,
+      cs: &mut CS,
+      z: &[AllocatedNum<F>],
+      params: &[AllocatedNum<F>],
+    ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+      let prod = z[0].mul(cs.namespace(|| "z * p_0"), &params[0])?;
+      let y = prod.add(cs.namespace(|| "z * p_0 + p_1"), &params[1])?;
+      Ok(vec![y])
+    }
+  }
+
+  #[test]
+  fn test_prove_step_with_public_params() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    let circuit = StepParamsCircuit::<E2, _>::new(AffineCircuit);
+    let pp = PublicParams::<E1, E2, StepParamsCircuit<E2, AffineCircuit>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let step_params = vec![
+      vec![F::from(2u64), F::from(1u64)],
+      vec![F::from(3u64), F::from(4u64)],
+      vec![F::from(1u64), F::from(0u64)],
+      vec![F::from(5u64), F::from(2u64)],
+    ];
+    let z0 = [F::from(7u64)];
+    let mut recursive_snark =
+      RecursiveSNARK::new_with_public_params(&pp, &circuit, &z0, &step_params[0]).unwrap();
+    recursive_snark
+      .prove_step_with_public_params(&pp, &circuit, &[])
+      .unwrap();
+    for p in &step_params[1..] {
+      recursive_snark
+        .prove_step_with_public_params(&pp, &circuit, p)
+        .unwrap();
+    }
+
+    // the parameters are those fed to the step circuit
+    let zn = step_params.iter().fold(z0[0], |z, p| z * p[0] + p[1]);
+    assert_eq!(
+      recursive_snark.verify_with_public_params(&pp, 4, &z0, &step_params),
+      Ok(vec![zn])
+    );
+
+    // a mismatched sequence of parameters fails verification
+    let mut other_params = step_params.clone();
+    other_params[3][0] += F::ONE;
+    assert!(recursive_snark
+      .verify_with_public_params(&pp, 4, &z0, &other_params)
+      .is_err());
+    let mut other_params = step_params.clone();
+    other_params.swap(1, 3);
+    assert!(recursive_snark
+      .verify_with_public_params(&pp, 4, &z0, &other_params)
+      .is_err());
+    assert!(recursive_snark
+      .verify_with_public_params(&pp, 4, &z0, &step_params[..3])
+      .is_err());
+
+    // parameters of the wrong length are rejected
+    assert_eq!(
+      recursive_snark.prove_step_with_public_params(&pp, &circuit, &[F::ONE]),
+      Err(NovaError::InvalidInputLength)
+    );
+    let mut recursive_snark =
+      RecursiveSNARK::new_with_public_params(&pp, &circuit, &z0, &step_params[0]).unwrap();
+    assert_eq!(
+      recursive_snark.prove_step_with_public_params(&pp, &circuit, &step_params[0]),
+      Err(NovaError::InvalidInputLength)
+    );
+  }
+}
@@ -0,0 +1,114 @@
+//! This module provides a harness for testing step circuits defined outside of this crate against Nova.
+//!
+//! It runs the full pipeline, from setting up the public parameters to verifying the proof,
+//! so that crates defining a `StepCircuit` can check it with a single call.
+use super::{CompressedSNARK, PublicParams, RecursiveSNARK};
+use crate::{
+  errors::NovaError,
+  gadgets::nonnative::util::f_to_nat,
+  traits::{
+    circuit::StepCircuit,
+    snark::{default_ck_hint, RelaxedR1CSSNARKTrait},
+    Engine,
+  },
+};
+
+/// Runs `num_steps` steps of the incremental computation defined by `c` starting from `z0`,
+/// verifies the resulting `RecursiveSNARK`, and returns its outputs.
+pub fn run_ivc<E1, E2, C>(
+  c: &C,
+  z0: &[E1::Scalar],
+  num_steps: usize,
+) -> Result<Vec<E1::Scalar>, NovaError>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+{
+  let pp = PublicParams::<E1, E2, C>::setup(c, &*default_ck_hint(), &*default_ck_hint())?;
+  let recursive_snark = prove_ivc(&pp, c, z0, num_steps)?;
+  recursive_snark.verify(&pp, num_steps, z0)
+}
+
+/// Same as `run_ivc`, but additionally compresses the `RecursiveSNARK` with the SNARKs `S1` and
+/// `S2`, and returns the outputs of the verified `CompressedSNARK`.
+pub fn run_ivc_with_compression<E1, E2, C, S1, S2>(
+  c: &C,
+  z0: &[E1::Scalar],
+  num_steps: usize,
+) -> Result<Vec<E1::Scalar>, NovaError>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+  S1: RelaxedR1CSSNARKTrait<E1>,
+  S2: RelaxedR1CSSNARKTrait<E2>,
+{
+  let pp = PublicParams::<E1, E2, C>::setup(c, &*S1::ck_floor(), &*S2::ck_floor())?;
+  let recursive_snark = prove_ivc(&pp, c, z0, num_steps)?;
+  recursive_snark.verify(&pp, num_steps, z0)?;
+
+  let (pk, vk) = CompressedSNARK::<_, _, _, S1, S2>::setup(&pp)?;
+  let compressed_snark = CompressedSNARK::prove(&pp, &pk, &recursive_snark)?;
+  compressed_snark.verify(&vk, num_steps, z0)
+}
+
+/// Runs `num_steps` steps of the same incremental computation on both orderings of the cycle of
+/// curves, i.e., with `c1` as the primary circuit over `E1` and the trivial secondary over `E2`,
+/// and then with `c2`, the same step circuit over the scalar field of `E2`, as the primary circuit
+/// over `E2` and the trivial secondary over `E1`. This exercises the conversions between the
+/// scalar and base fields of the two curves in both directions.
+///
+/// Panics unless both orderings verify and their outputs are equal as integers, and otherwise
+/// returns the outputs of the first ordering. The initial input `z0` is given as integers, so that
+/// it is the same on both orderings, and the computation must not wrap around either modulus.
+pub fn assert_symmetric_ivc<E1, E2, C1, C2>(
+  c1: &C1,
+  c2: &C2,
+  z0: &[u64],
+  num_steps: usize,
+) -> Vec<E1::Scalar>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C1: StepCircuit<E1::Scalar>,
+  C2: StepCircuit<E2::Scalar>,
+{
+  let z0_primary = z0.iter().map(|z| E1::Scalar::from(*z)).collect::<Vec<_>>();
+  let zn_primary = run_ivc::<E1, E2, C1>(c1, &z0_primary, num_steps)
+    .expect("IVC with the step circuit over the first curve failed");
+
+  let z0_secondary = z0.iter().map(|z| E2::Scalar::from(*z)).collect::<Vec<_>>();
+  let zn_secondary = run_ivc::<E2, E1, C2>(c2, &z0_secondary, num_steps)
+    .expect("IVC with the step circuit over the second curve failed");
+
+  assert_eq!(
+    zn_primary.iter().map(f_to_nat).collect::<Vec<_>>(),
+    zn_secondary.iter().map(f_to_nat).collect::<Vec<_>>(),
+    "outputs differ across the orderings of the cycle of curves"
+  );
+
+  zn_primary
+}
+
+fn prove_ivc<E1, E2, C>(
+  pp: &PublicParams<E1, E2, C>,
+  c: &C,
+  z0: &[E1::Scalar],
+  num_steps: usize,
+) -> Result<RecursiveSNARK<E1, E2, C>, NovaError>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+{
+  if num_steps == 0 {
+    return Err(NovaError::InvalidNumSteps);
+  }
+
+  let mut recursive_snark = RecursiveSNARK::new(pp, c, z0)?;
+  for _ in 0..num_steps {
+    recursive_snark.prove_step(pp, c)?;
+  }
+  Ok(recursive_snark)
+}
@@ -0,0 +1,359 @@
+//! This module implements a commitment to the execution trace of an incremental computation, i.e.,
+//! to the state entering every step, which can later be opened at individual steps.
+//!
+//! The commitment is a hash chain `t_{k+1} = H(t_k, H(z_k))` over the states `z_k` entering each
+//! step, which `TracedCircuit` computes inside the step circuit from the allocated state. The chain
+//! is carried as an extra element of the state, so the proof binds it like the rest of the state,
+//! and a verified proof outputs the trace commitment after the outputs of the computation.
+use super::{
+  chained::{chained, hash, HashChain},
+  PublicParams, RecursiveSNARK,
+};
+use crate::{
+  errors::NovaError,
+  frontend::{num::AllocatedNum, ConstraintSystem, SynthesisError},
+  traits::{circuit::StepCircuit, Engine, ROConstantsCircuit},
+};
+use core::marker::PhantomData;
+use ff::Field;
+use serde::{Deserialize, Serialize};
+
+/// A step circuit that executes the step circuit `C` and extends its state with a commitment to the
+/// execution trace, i.e., to the state entering every step, for use with
+/// `RecursiveSNARK::with_trace_commitment`. Since the state of `C` is over the scalar field of the
+/// primary engine `E1`, this is a `TracedCircuit<E2, C>`.
+///
+/// Its state is the state of `C` followed by the trace commitment, which is zero initially. Hence,
+/// a proof for the initial state `z0` verifies against `z0` followed by zero, and outputs the
+/// outputs of `C` followed by the trace commitment, as returned by `TracedRecursiveSNARK::verify`.
+/// The trace commitment is computed with the default constants of the RO of `E2`, which are those
+/// of public parameters created with `setup`, or with those set by `with_ro_consts`.
+#[derive(Clone, Debug)]
+pub struct TracedCircuit<E: Engine, C> {
+  circuit: C,
+  chain: HashChain<E>,
+}
+
+impl<E: Engine, C: StepCircuit<E::Base>> TracedCircuit<E, C> {
+  /// Wraps the step circuit `circuit`
+  pub fn new(circuit: C) -> Self {
+    Self {
+      circuit,
+      chain: HashChain::new(ROConstantsCircuit::<E>::default()),
+    }
+  }
+
+  /// Computes the trace commitment with the constants `ro_consts` of the RO of `E2`, for use with
+  /// public parameters created by `PublicParams::setup_with_ro_constants` with the same
+  /// `ro_consts_secondary`
+  pub fn with_ro_consts(mut self, ro_consts: ROConstantsCircuit<E>) -> Self {
+    self.chain = HashChain::new(ro_consts);
+    self
+  }
+
+  /// Returns the wrapped step circuit
+  pub fn circuit(&self) -> &C {
+    &self.circuit
+  }
+}
+
+impl<E: Engine, C: StepCircuit<E::Base>> StepCircuit<E::Base> for TracedCircuit<E, C> {
+  fn arity(&self) -> usize {
+    self.circuit.arity() + 1
+  }
+
+  fn num_public_signals(&self) -> usize {
+    self.circuit.num_public_signals()
+  }
+
+  fn synthesize<CS: ConstraintSystem<E::Base>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<E::Base>],
+  ) -> Result<Vec<AllocatedNum<E::Base>>, SynthesisError> {
+    HashChain::<E>::synthesize_step(
+      cs,
+      self.circuit.arity(),
+      self.circuit.num_public_signals(),
+      z,
+      |cs, z, chain| {
+        let z_next = self
+          .circuit
+          .synthesize(&mut cs.namespace(|| "traced step"), z)?;
+
+        // t_{k+1} = H(t_k, H(z_k)), over the state entering the step
+        let state_hash = self
+          .chain
+          .synthesize_hash(cs.namespace(|| "state hash"), z)?;
+        let chain_next = self
+          .chain
+          .synthesize_hash(cs.namespace(|| "trace chain"), &[chain.clone(), state_hash])?;
+        Ok((z_next, chain_next))
+      },
+    )
+  }
+
+  fn validate_initial(&self, z0: &[E::Base]) -> Result<(), String> {
+    HashChain::<E>::validate_initial(self.circuit.arity(), z0, |z0| {
+      self.circuit.validate_initial(z0)
+    })
+  }
+}
+
+/// A `RecursiveSNARK` that commits to the state entering every step it executes, created with
+/// `RecursiveSNARK::with_trace_commitment`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct TracedRecursiveSNARK<E1, E2, C>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+{
+  recursive_snark: RecursiveSNARK<E1, E2, TracedCircuit<E2, C>>,
+  // `states[k]` is the state of `C` after `k` steps, and `chains[k]` the trace commitment then
+  states: Vec<Vec<E1::Scalar>>,
+  chains: Vec<E1::Scalar>,
+  // `state_hashes[k]` is the hash of `states[k]` absorbed by step `k`
+  state_hashes: Vec<E1::Scalar>,
+}
+
+/// An opening of the trace commitment of a `TracedRecursiveSNARK` at a step
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct TraceOpening<E1, E2>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+{
+  step: usize,
+  state: Vec<E1::Scalar>,
+  chain_before: E1::Scalar,
+  later_state_hashes: Vec<E1::Scalar>,
+  #[serde(skip)]
+  _p: PhantomData<E2>,
+}
+
+impl<E1, E2, C> RecursiveSNARK<E1, E2, TracedCircuit<E2, C>>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+{
+  /// Creates a recursive SNARK as in `new` for the initial state `z0` of the step circuit wrapped
+  /// by `c`, which commits to the state entering every step it executes, so that the state of a
+  /// step can later be opened with `TracedRecursiveSNARK::open_step`.
+  pub fn with_trace_commitment(
+    pp: &PublicParams<E1, E2, TracedCircuit<E2, C>>,
+    c: &TracedCircuit<E2, C>,
+    z0: &[E1::Scalar],
+  ) -> Result<TracedRecursiveSNARK<E1, E2, C>, NovaError> {
+    Ok(TracedRecursiveSNARK {
+      recursive_snark: Self::new(pp, c, &chained(z0))?,
+      states: vec![z0.to_vec()],
+      chains: vec![E1::Scalar::ZERO],
+      state_hashes: Vec::new(),
+    })
+  }
+}
+
+impl<E1, E2, C> TracedRecursiveSNARK<E1, E2, C>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+  C: StepCircuit<E1::Scalar>,
+{
+  /// Executes a step of the incremental computation as in `RecursiveSNARK::prove_step`, whose
+  /// circuit absorbs the state entering the step into the trace commitment.
+  ///
+  /// The step executed by `new`, which the first call records, absorbs the initial state.
+  pub fn prove_step(
+    &mut self,
+    pp: &PublicParams<E1, E2, TracedCircuit<E2, C>>,
+    c: &TracedCircuit<E2, C>,
+  ) -> Result<usize, NovaError> {
+    let snark = &mut self.recursive_snark;
+    snark.prove_step(pp, c)?;
+
+    let (z, chain) = snark.zi.split_at(c.circuit.arity());
+    let entering = &self.states[self.states.len() - 1];
+    self.state_hashes.push(c.chain.hash(entering));
+    self.states.push(z.to_vec());
+    self.chains.push(chain[0]);
+    Ok(snark.i)
+  }
+
+  /// Returns the underlying recursive SNARK, which verifies as any other for the initial state
+  /// followed by zero
+  pub fn recursive_snark(&self) -> &RecursiveSNARK<E1, E2, TracedCircuit<E2, C>> {
+    &self.recursive_snark
+  }
+
+  /// Returns the trace commitment, which is the last output of the recursive SNARK and of a
+  /// `CompressedSNARK` produced from it
+  pub fn trace_commitment(&self) -> E1::Scalar {
+    self.chains[self.chains.len() - 1]
+  }
+
+  /// Verifies the recursive SNARK as in `RecursiveSNARK::verify` for `num_steps` steps from the
+  /// initial state `z0` of the wrapped step circuit, and returns the outputs of the computation
+  /// along with the trace commitment bound to them
+  pub fn verify(
+    &self,
+    pp: &PublicParams<E1, E2, TracedCircuit<E2, C>>,
+    num_steps: usize,
+    z0: &[E1::Scalar],
+  ) -> Result<(Vec<E1::Scalar>, E1::Scalar), NovaError> {
+    let mut zn = self.recursive_snark.verify(pp, num_steps, &chained(z0))?;
+    let trace_commitment = zn.pop().ok_or(NovaError::InvalidStepOutputLength)?;
+    Ok((zn, trace_commitment))
+  }
+
+  /// Produces an opening of the trace commitment at the state entering step `step`, i.e., the state
+  /// after `step` steps.
+  ///
+  /// The states entering the steps executed thus far are committed to, which excludes the last
+  /// state, i.e., the output of the computation. Returns `NovaError::InvalidIndex` for any other
+  /// step.
+  pub fn open_step(&self, step: usize) -> Result<TraceOpening<E1, E2>, NovaError> {
+    if step >= self.state_hashes.len() {
+      return Err(NovaError::InvalidIndex);
+    }
+
+    Ok(TraceOpening {
+      step,
+      state: self.states[step].clone(),
+      chain_before: self.chains[step],
+      later_state_hashes: self.state_hashes[step + 1..].to_vec(),
+      _p: PhantomData,
+    })
+  }
+}
+
+impl<E1, E2> TraceOpening<E1, E2>
+where
+  E1: Engine<Base = <E2 as Engine>::Scalar>,
+  E2: Engine<Base = <E1 as Engine>::Scalar>,
+{
+  /// Returns the step at which the trace commitment is opened
+  pub fn step(&self) -> usize {
+    self.step
+  }
+
+  /// Returns the state entering step `step()`
+  pub fn state(&self) -> &[E1::Scalar] {
+    &self.state
+  }
+
+  /// Verifies the opening against the trace commitment `trace_commitment` of a computation of
+  /// `num_steps` steps with the public parameters `pp`, as returned by verifying its
+  /// `TracedRecursiveSNARK` or a `CompressedSNARK` produced from it
+  pub fn verify<C: StepCircuit<E1::Scalar>>(
+    &self,
+    pp: &PublicParams<E1, E2, TracedCircuit<E2, C>>,
+    num_steps: usize,
+    trace_commitment: E1::Scalar,
+  ) -> Result<(), NovaError> {
+    let invalid = |reason: &str| NovaError::ProofVerifyError {
+      reason: reason.to_string(),
+    };
+
+    if self.step + 1 + self.later_state_hashes.len() != num_steps {
+      return Err(invalid("trace opening does not match the number of steps"));
+    }
+
+    let ro_consts = &pp.ro_consts_circuit_primary;
+    let state_hash = hash::<E2>(ro_consts, &self.state);
+    let chain = self.later_state_hashes.iter().fold(
+      hash::<E2>(ro_consts, &[self.chain_before, state_hash]),
+      |chain, state_hash| hash::<E2>(ro_consts, &[chain, *state_hash]),
+    );
+    if chain != trace_commitment {
+      return Err(invalid("trace opening does not match the trace commitment"));
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    gadgets::dsl::{add, constant, input, mul, TransitionCircuit},
+    provider::{PallasEngine, VestaEngine},
+    traits::snark::default_ck_hint,
+  };
+
+  #[test]
+  fn test_trace_commitment() {
+    type E1 = PallasEngine;
+    type E2 = VestaEngine;
+    type F = <E1 as Engine>::Scalar;
+
+    // z -> z^2 + z + 1
+    let step = TransitionCircuit::new(vec![add(
+      add(mul(input(0), input(0)), input(0)),
+      constant(F::ONE),
+    )])
+    .unwrap();
+    let circuit = TracedCircuit::<E2, _>::new(step.clone());
+    let pp = PublicParams::<E1, E2, TracedCircuit<E2, TransitionCircuit<F>>>::setup(
+      &circuit,
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    )
+    .unwrap();
+
+    let num_steps = 5;
+    let z0 = [F::from(2u64)];
+    let mut traced = RecursiveSNARK::with_trace_commitment(&pp, &circuit, &z0).unwrap();
+    for _i in 0..num_steps {
+      traced.prove_step(&pp, &circuit).unwrap();
+    }
+
+    // the trace commitment is bound to the proof as its last output
+    let zn = (0..num_steps).fold(z0.to_vec(), |z, _| step.output(&z));
+    let (outputs, trace_commitment) = traced.verify(&pp, num_steps, &z0).unwrap();
+    assert_eq!(outputs, zn);
+    assert_eq!(trace_commitment, traced.trace_commitment());
+    assert_eq!(
+      traced
+        .recursive_snark()
+        .verify(&pp, num_steps, &[z0[0], F::ZERO])
+        .unwrap(),
+      vec![zn[0], trace_commitment]
+    );
+
+    // the state entering step 3 opens against the committed trace
+    let z3 = (0..3).fold(z0.to_vec(), |z, _| step.output(&z));
+    let opening = traced.open_step(3).unwrap();
+    assert_eq!(opening.step(), 3);
+    assert_eq!(opening.state(), z3.as_slice());
+    assert_eq!(opening.verify(&pp, num_steps, trace_commitment), Ok(()));
+
+    // but not with a different state, number of steps, or trace commitment
+    let mut forged = opening.clone();
+    forged.state[0] += F::ONE;
+    assert!(forged.verify(&pp, num_steps, trace_commitment).is_err());
+    assert!(opening
+      .verify(&pp, num_steps + 1, trace_commitment)
+      .is_err());
+    assert!(opening
+      .verify(&pp, num_steps, trace_commitment + F::ONE)
+      .is_err());
+
+    // the states entering every step are committed to, but not the output
+    for step in 0..num_steps {
+      let opening = traced.open_step(step).unwrap();
+      assert_eq!(opening.verify(&pp, num_steps, trace_commitment), Ok(()));
+    }
+    assert!(traced.open_step(num_steps).is_err());
+
+    // the trace commitment starts at zero
+    assert!(matches!(
+      RecursiveSNARK::new(&pp, &circuit, &[z0[0], F::ONE]),
+      Err(NovaError::InvalidInitialState { .. })
+    ));
+  }
+}
@@ -2,7 +2,7 @@
 use crate::{
   impl_traits,
   provider::{
-    msm::{msm, msm_small},
+    msm::{msm, msm_small, FixedBaseMSM},
     traits::{DlogGroup, DlogGroupExt, PairingGroup},
   },
   traits::{Group, PrimeFieldExt, TranscriptReprTrait},
@@ -47,6 +47,13 @@ impl DlogGroupExt for bn256::Point {
     msm(scalars, bases)
   }
 
+  fn vartime_multiscalar_mul_with_table(
+    scalars: &[Self::Scalar],
+    table: &FixedBaseMSM<Self::AffineGroupElement>,
+  ) -> Self {
+    table.msm(scalars)
+  }
+
   fn vartime_multiscalar_mul_small<T: Integer + Into<u64> + Copy + Sync + ToPrimitive>(
     scalars: &[T],
     bases: &[Self::AffineGroupElement],
@@ -118,7 +125,7 @@ impl DlogGroup for G2 {
     G2::from(*p)
   }
 
-  fn from_label(_label: &'static [u8], _n: usize) -> Vec<Self::AffineGroupElement> {
+  fn from_label(_label: &[u8], _n: usize) -> Vec<Self::AffineGroupElement> {
     unimplemented!()
   }
 
@@ -69,6 +69,16 @@ where
     &self.ck
   }
 
+  /// Returns the number of generators in the key, excluding the blinding generator
+  pub fn len(&self) -> usize {
+    self.ck.len()
+  }
+
+  /// Returns true if the key has no generators, excluding the blinding generator
+  pub fn is_empty(&self) -> bool {
+    self.ck.is_empty()
+  }
+
   /// Returns a reference to the h field
   pub fn h(&self) -> &<E::GE as DlogGroup>::AffineGroupElement {
     &self.h
@@ -460,6 +470,24 @@ where
     Self::CommitmentKey::setup_from_rng(label, n, OsRng)
   }
 
+  fn setup_from_seed(
+    _label: &'static [u8],
+    _seed: &[u8; 32],
+    _n: usize,
+  ) -> Result<Self::CommitmentKey, NovaError> {
+    // the powers of tau derived from a public seed would reveal the trapdoor
+    Err(NovaError::UnsupportedCommitmentKeySeed)
+  }
+
+  fn extend(
+    _ck: &mut Self::CommitmentKey,
+    _label: &'static [u8],
+    _n: usize,
+  ) -> Result<(), NovaError> {
+    // further powers of tau cannot be computed without the trapdoor
+    Err(NovaError::UnsupportedCommitmentKeyExtension)
+  }
+
   fn derand_key(ck: &Self::CommitmentKey) -> Self::DerandKey {
     Self::DerandKey { h: ck.h }
   }
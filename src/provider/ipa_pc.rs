@@ -1,7 +1,7 @@
 //! This module implements `EvaluationEngine` using an IPA-based polynomial commitment scheme
 use crate::{
   errors::NovaError,
-  provider::{pedersen::CommitmentKeyExtTrait, traits::DlogGroup},
+  provider::{msm::FixedBaseMSM, pedersen::CommitmentKeyExtTrait, traits::DlogGroup},
   spartan::polys::eq::EqPolynomial,
   traits::{
     commitment::CommitmentEngineTrait, evaluation::EvaluationEngineTrait, Engine,
@@ -25,9 +25,16 @@ pub struct ProverKey<E: Engine> {
 /// Provides an implementation of the verifier key
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
-pub struct VerifierKey<E: Engine> {
+pub struct VerifierKey<E: Engine>
+where
+  E::GE: DlogGroup,
+  CommitmentKey<E>: CommitmentKeyExtTrait<E>,
+{
   ck_v: CommitmentKey<E>,
   ck_s: CommitmentKey<E>,
+  // a table for the multi-scalar multiplication with `ck_v`, precomputed on demand
+  #[serde(skip)]
+  msm_table: Option<FixedBaseMSM<<E::GE as DlogGroup>::AffineGroupElement>>,
 }
 
 /// Provides an implementation of a polynomial evaluation engine using IPA
@@ -55,6 +62,7 @@ where
     let vk = VerifierKey {
       ck_v: ck.clone(),
       ck_s: ck_c,
+      msm_table: None,
     };
 
     (pk, vk)
@@ -89,6 +97,7 @@ where
     arg.verify(
       &vk.ck_v,
       &vk.ck_s,
+      vk.msm_table.as_ref(),
       (2_usize).pow(point.len() as u32),
       &u,
       transcript,
@@ -96,6 +105,12 @@ where
 
     Ok(())
   }
+
+  fn precompute_msm_tables(vk: &mut Self::VerifierKey) {
+    if vk.msm_table.is_none() {
+      vk.msm_table = vk.ck_v.precompute_msm_table();
+    }
+  }
 }
 
 fn inner_product<T: Field + Send + Sync>(a: &[T], b: &[T]) -> T {
@@ -286,6 +301,7 @@ where
     &self,
     ck: &CommitmentKey<E>,
     ck_c: &CommitmentKey<E>,
+    msm_table: Option<&FixedBaseMSM<<E::GE as DlogGroup>::AffineGroupElement>>,
     n: usize,
     U: &InnerProductInstance<E>,
     transcript: &mut E::TE,
@@ -374,7 +390,14 @@ where
     };
 
     let ck_hat = {
-      let c = CE::<E>::commit(&ck, &s, &E::Scalar::ZERO);
+      let c = match msm_table {
+        Some(table) => {
+          #[cfg(test)]
+          tests::TABLE_MSM_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+          CommitmentKey::<E>::commit_with_msm_table(table, &s)?
+        }
+        None => CE::<E>::commit(&ck, &s, &E::Scalar::ZERO),
+      };
       CommitmentKey::<E>::reinterpret_commitments_as_ck(&[c])?
     };
 
@@ -413,3 +436,12 @@ where
     }
   }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use std::sync::atomic::AtomicUsize;
+
+  // the number of verifications that used a precomputed table, across threads since the
+  // verifications of a `CompressedSNARK` run in parallel
+  pub(crate) static TABLE_MSM_COUNT: AtomicUsize = AtomicUsize::new(0);
+}
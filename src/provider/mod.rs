@@ -22,15 +22,18 @@ use crate::{
   provider::{
     bn256_grumpkin::{bn256, grumpkin},
     hyperkzg::CommitmentEngine as HyperKZGCommitmentEngine,
-    keccak::Keccak256Transcript,
     pasta::{pallas, vesta},
-    pedersen::CommitmentEngine as PedersenCommitmentEngine,
     poseidon::{PoseidonRO, PoseidonROCircuit},
     secp_secq::{secp256k1, secq256k1},
   },
   traits::Engine,
 };
 pub use ptau::{check_sanity_of_ptau_file, read_ptau, write_ptau};
+
+// the transcript and commitment engine of the provided engines, which an engine that only swaps
+// the RO, as described in `ROTrait`, can reuse
+pub use keccak::Keccak256Transcript;
+pub use pedersen::CommitmentEngine as PedersenCommitmentEngine;
 use serde::{Deserialize, Serialize};
 
 /// An implementation of Nova traits with HyperKZG over the BN256 curve
@@ -82,10 +85,15 @@ impl Engine for GrumpkinEngine {
 }
 
 /// An implementation of the Nova `Engine` trait with Secp256k1 curve and Pedersen commitment scheme
+///
+/// It forms a cycle with [`Secq256k1Engine`]. Neither curve is pairing-friendly, so the pair
+/// only supports the IPA evaluation engine (`ipa_pc::EvaluationEngine`) and not HyperKZG.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Secp256k1Engine;
 
-/// An implementation of the Nova `Engine` trait with Secp256k1 curve and Pedersen commitment scheme
+/// An implementation of the Nova `Engine` trait with Secq256k1 curve and Pedersen commitment scheme
+///
+/// It forms a cycle with [`Secp256k1Engine`], with the same restriction to the IPA evaluation engine.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Secq256k1Engine;
 
@@ -180,6 +188,9 @@ mod tests {
         assert_eq!(ck_par.len(), n);
         assert_eq!(ck_ser.len(), n);
         assert_eq!(ck_par, ck_ser);
+
+        let ck_range = <$curve::Point as DlogGroup>::from_label_range(label, n / 2, n);
+        assert_eq!(ck_range, ck_ser[n / 2..]);
       }
     };
   }
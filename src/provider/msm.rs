@@ -2,7 +2,10 @@
 //! The generic implementation is adapted from halo2; we add an optimization to commit to bits more efficiently
 //! The specialized implementations are adapted from jolt, with additional optimizations and parallelization.
 use ff::{Field, PrimeField};
-use halo2curves::{group::Group, CurveAffine};
+use halo2curves::{
+  group::{Curve, Group},
+  CurveAffine,
+};
 use num_integer::Integer;
 use num_traits::{ToPrimitive, Zero};
 use rayon::{current_num_threads, prelude::*};
@@ -32,6 +35,27 @@ impl<C: CurveAffine> Bucket<C> {
   }
 }
 
+// returns the `segment`-th window of `c` bits of the little-endian representation `bytes`
+fn get_at<F: PrimeField>(segment: usize, c: usize, bytes: &F::Repr) -> usize {
+  let skip_bits = segment * c;
+  let skip_bytes = skip_bits / 8;
+
+  if skip_bytes >= 32 {
+    return 0;
+  }
+
+  let mut v = [0; 8];
+  for (v, o) in v.iter_mut().zip(bytes.as_ref()[skip_bytes..].iter()) {
+    *v = *o;
+  }
+
+  let mut tmp = u64::from_le_bytes(v);
+  tmp >>= skip_bits - (skip_bytes * 8);
+  tmp %= 1 << c;
+
+  tmp as usize
+}
+
 fn cpu_msm_serial<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
   let c = if bases.len() < 4 {
     1
@@ -41,26 +65,6 @@ fn cpu_msm_serial<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve
     (f64::from(bases.len() as u32)).ln().ceil() as usize
   };
 
-  fn get_at<F: PrimeField>(segment: usize, c: usize, bytes: &F::Repr) -> usize {
-    let skip_bits = segment * c;
-    let skip_bytes = skip_bits / 8;
-
-    if skip_bytes >= 32 {
-      return 0;
-    }
-
-    let mut v = [0; 8];
-    for (v, o) in v.iter_mut().zip(bytes.as_ref()[skip_bytes..].iter()) {
-      *v = *o;
-    }
-
-    let mut tmp = u64::from_le_bytes(v);
-    tmp >>= skip_bits - (skip_bytes * 8);
-    tmp %= 1 << c;
-
-    tmp as usize
-  }
-
   let boolean_sum = coeffs
     .iter()
     .zip(bases.iter())
@@ -126,6 +130,98 @@ pub fn msm<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -> C::Curve {
   }
 }
 
+/// A table of multiples of a fixed set of bases, precomputed to speed up repeated
+/// multi-scalar multiplications with these bases.
+///
+/// The table holds `2^(c * j)` times each base for every window `j` of `c` bits of a scalar, so that
+/// a multi-scalar multiplication adds every window of every scalar into a single set of buckets,
+/// with no doublings and a single bucket reduction. It holds about `256 / c` points per base.
+#[derive(Clone, Debug)]
+pub struct FixedBaseMSM<C: CurveAffine> {
+  window: usize,
+  // `tables[j][i]` holds `2^(window * j)` times the `i`-th base
+  tables: Vec<Vec<C>>,
+}
+
+impl<C: CurveAffine> FixedBaseMSM<C> {
+  /// Precomputes the table for the provided bases
+  pub fn new(bases: &[C]) -> Self {
+    let window = num_bits(bases.len()).clamp(4, 16);
+    let num_windows = (C::Scalar::NUM_BITS as usize).div_ceil(window);
+
+    let mut tables = Vec::with_capacity(num_windows);
+    tables.push(bases.to_vec());
+    for j in 1..num_windows {
+      let shifted = tables[j - 1]
+        .par_iter()
+        .map(|base| {
+          let mut p: C::Curve = (*base).into();
+          (0..window).for_each(|_| p = p.double());
+          p
+        })
+        .collect::<Vec<_>>();
+      let mut affine = vec![C::identity(); shifted.len()];
+      C::Curve::batch_normalize(&shifted, &mut affine);
+      tables.push(affine);
+    }
+
+    Self { window, tables }
+  }
+
+  /// Returns the number of bases of the table
+  pub fn len(&self) -> usize {
+    self.tables[0].len()
+  }
+
+  /// Returns true if the table has no bases
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Computes the multi-scalar multiplication of `coeffs` with the first `coeffs.len()` bases,
+  /// which equals `msm(coeffs, &bases[..coeffs.len()])`.
+  ///
+  /// This function will panic if there are more coeffs than bases.
+  pub fn msm(&self, coeffs: &[C::Scalar]) -> C::Curve {
+    assert!(coeffs.len() <= self.len());
+
+    let c = self.window;
+    let process_chunk = |start: usize, coeffs: &[C::Scalar]| {
+      let mut buckets = vec![Bucket::None; (1 << c) - 1];
+      for (i, coeff) in coeffs.iter().enumerate() {
+        let repr = coeff.to_repr();
+        for (j, table) in self.tables.iter().enumerate() {
+          let digit = get_at::<C::Scalar>(j, c, &repr);
+          if digit != 0 {
+            buckets[digit - 1].add_assign(&table[start + i]);
+          }
+        }
+      }
+
+      // Summation by parts
+      let mut running_sum = C::Curve::identity();
+      let mut acc = C::Curve::identity();
+      for exp in buckets.into_iter().rev() {
+        running_sum = exp.add(running_sum);
+        acc += &running_sum;
+      }
+      acc
+    };
+
+    let num_threads = current_num_threads();
+    if coeffs.len() > num_threads {
+      let chunk = coeffs.len() / num_threads;
+      coeffs
+        .par_chunks(chunk)
+        .enumerate()
+        .map(|(k, coeffs)| process_chunk(k * chunk, coeffs))
+        .reduce(C::Curve::identity, |sum, evl| sum + evl)
+    } else {
+      process_chunk(0, coeffs)
+    }
+  }
+}
+
 fn num_bits(n: usize) -> usize {
   if n == 0 {
     0
@@ -158,7 +254,7 @@ fn msm_binary<C: CurveAffine, T: Integer + Sync>(scalars: &[T], bases: &[C]) ->
     scalars
       .iter()
       .zip(bases.iter())
-      .filter(|(scalar, _)| (!scalar.is_zero()))
+      .filter(|(scalar, _)| !scalar.is_zero())
       .for_each(|(_, base)| {
         acc += *base;
       });
@@ -384,6 +480,34 @@ mod tests {
     test_general_msm_with::<secq256k1::Scalar, secq256k1::Affine>();
   }
 
+  fn test_fixed_base_msm_with<F: Field, A: CurveAffine<ScalarExt = F>>() {
+    let n = 100;
+    let bases = (0..n)
+      .map(|_| A::from(A::generator() * F::random(OsRng)))
+      .collect::<Vec<_>>();
+    let table = FixedBaseMSM::new(&bases);
+    assert_eq!(table.len(), n);
+
+    for len in [0, 1, 7, n] {
+      let mut coeffs = (0..len).map(|_| F::random(OsRng)).collect::<Vec<_>>();
+      if len > 1 {
+        coeffs[0] = F::ZERO;
+        coeffs[1] = -F::ONE;
+      }
+      assert_eq!(table.msm(&coeffs), msm(&coeffs, &bases[..len]));
+    }
+  }
+
+  #[test]
+  fn test_fixed_base_msm() {
+    test_fixed_base_msm_with::<pallas::Scalar, pallas::Affine>();
+    test_fixed_base_msm_with::<vesta::Scalar, vesta::Affine>();
+    test_fixed_base_msm_with::<bn256::Scalar, bn256::Affine>();
+    test_fixed_base_msm_with::<grumpkin::Scalar, grumpkin::Affine>();
+    test_fixed_base_msm_with::<secp256k1::Scalar, secp256k1::Affine>();
+    test_fixed_base_msm_with::<secq256k1::Scalar, secq256k1::Affine>();
+  }
+
   fn test_msm_ux_with<F: PrimeField, A: CurveAffine<ScalarExt = F>>() {
     let n = 8;
     let bases = (0..n)
@@ -3,11 +3,12 @@ use crate::{
   errors::NovaError,
   gadgets::utils::to_bignat_repr,
   provider::{
+    msm::FixedBaseMSM,
     ptau::{read_points, write_points, PtauFileError},
     traits::{DlogGroup, DlogGroupExt},
   },
   traits::{
-    commitment::{CommitmentEngineTrait, CommitmentTrait, Len},
+    commitment::{check_sparse_entries, CommitmentEngineTrait, CommitmentTrait, Len},
     AbsorbInRO2Trait, AbsorbInROTrait, Engine, ROTrait, TranscriptReprTrait,
   },
 };
@@ -192,6 +193,31 @@ impl<E: Engine> CommitmentKey<E>
 where
   E::GE: DlogGroup,
 {
+  /// Returns the number of generators in the key, excluding the blinding generator
+  pub fn len(&self) -> usize {
+    self.ck.len()
+  }
+
+  /// Returns true if the key has no generators, excluding the blinding generator
+  pub fn is_empty(&self) -> bool {
+    self.ck.is_empty()
+  }
+
+  /// Extends the key to the one derived from `label` for `n` generators, as by `setup`, or by
+  /// `setup_from_seed` if `label` is followed by the seed, where the existing generators must have
+  /// been derived from the same label. Only the appended generators are derived, so that this is
+  /// cheaper than deriving the whole key again, and a key with enough generators is left unchanged.
+  pub fn extend_to(&mut self, n: usize, label: &[u8]) {
+    // keys have a power of two generators, which follow the blinding generator
+    let len = self.ck.len();
+    let target = n.next_power_of_two();
+    if target > len {
+      self
+        .ck
+        .extend(E::GE::from_label_range(label, len + 1, target + 1));
+    }
+  }
+
   pub fn save_to(&self, writer: &mut impl std::io::Write) -> Result<(), PtauFileError> {
     writer.write_all(&KEY_FILE_HEAD)?;
     let mut points = Vec::with_capacity(self.ck.len() + 1);
@@ -220,6 +246,26 @@ where
     }
   }
 
+  fn setup_from_seed(
+    label: &'static [u8],
+    seed: &[u8; 32],
+    n: usize,
+  ) -> Result<Self::CommitmentKey, NovaError> {
+    let gens = E::GE::from_label(&[label, seed].concat(), n.next_power_of_two() + 1);
+
+    let (h, ck) = gens.split_first().unwrap();
+
+    Ok(Self::CommitmentKey {
+      ck: ck.to_vec(),
+      h: *h,
+    })
+  }
+
+  fn extend(ck: &mut Self::CommitmentKey, label: &'static [u8], n: usize) -> Result<(), NovaError> {
+    ck.extend_to(n, label);
+    Ok(())
+  }
+
   fn derand_key(ck: &Self::CommitmentKey) -> Self::DerandKey {
     Self::DerandKey { h: ck.h }
   }
@@ -227,12 +273,54 @@ where
   fn commit(ck: &Self::CommitmentKey, v: &[E::Scalar], r: &E::Scalar) -> Self::Commitment {
     assert!(ck.ck.len() >= v.len());
 
+    #[cfg(test)]
+    tests::MSM_TERMS.with(|terms| terms.set(terms.get() + v.len()));
+
     Commitment {
       comm: E::GE::vartime_multiscalar_mul(v, &ck.ck[..v.len()])
         + <E::GE as DlogGroup>::group(&ck.h) * r,
     }
   }
 
+  fn commit_streaming(
+    ck: &Self::CommitmentKey,
+    v: &[E::Scalar],
+    r: &E::Scalar,
+    window: usize,
+  ) -> Self::Commitment {
+    assert!(ck.ck.len() >= v.len());
+    assert!(window > 0);
+
+    let comm = v.chunks(window).zip(ck.ck.chunks(window)).fold(
+      <E::GE as DlogGroup>::group(&ck.h) * r,
+      |acc, (v_i, ck_i)| acc + E::GE::vartime_multiscalar_mul(v_i, &ck_i[..v_i.len()]),
+    );
+
+    Commitment { comm }
+  }
+
+  fn commit_sparse(
+    ck: &Self::CommitmentKey,
+    entries: &[(usize, E::Scalar)],
+    r: &E::Scalar,
+  ) -> Self::Commitment {
+    check_sparse_entries::<E>(entries, ck.ck.len());
+
+    let (scalars, bases): (Vec<_>, Vec<_>) = entries
+      .iter()
+      .filter(|(_, s)| !bool::from(s.is_zero()))
+      .map(|(i, s)| (*s, ck.ck[*i]))
+      .unzip();
+
+    #[cfg(test)]
+    tests::MSM_TERMS.with(|terms| terms.set(terms.get() + scalars.len()));
+
+    Commitment {
+      comm: E::GE::vartime_multiscalar_mul(&scalars, &bases)
+        + <E::GE as DlogGroup>::group(&ck.h) * r,
+    }
+  }
+
   fn commit_small<T: Integer + Into<u64> + Copy + Sync + ToPrimitive>(
     ck: &Self::CommitmentKey,
     v: &[T],
@@ -306,6 +394,22 @@ where
   ) -> Result<Self, NovaError>
   where
     Self: Sized;
+
+  /// Precomputes a table of multiples of the generators of the commitment key for repeated
+  /// commitments with the same key. By default, no table is precomputed.
+  fn precompute_msm_table(&self) -> Option<FixedBaseMSM<<E::GE as DlogGroup>::AffineGroupElement>> {
+    None
+  }
+
+  /// Commits to `v` without blinding using a table produced by `precompute_msm_table`,
+  /// which results in the same commitment as committing with the commitment key.
+  /// By default, no table is precomputed, so this returns an error.
+  fn commit_with_msm_table(
+    _table: &FixedBaseMSM<<E::GE as DlogGroup>::AffineGroupElement>,
+    _v: &[E::Scalar],
+  ) -> Result<<E::CE as CommitmentEngineTrait<E>>::Commitment, NovaError> {
+    Err(NovaError::InternalError)
+  }
 }
 
 impl<E: Engine<CE = CommitmentEngine<E>>> CommitmentKeyExtTrait<E> for CommitmentKey<E>
@@ -380,6 +484,19 @@ where
                                  // bc we don't use ZK IPA
     })
   }
+
+  fn precompute_msm_table(&self) -> Option<FixedBaseMSM<<E::GE as DlogGroup>::AffineGroupElement>> {
+    Some(FixedBaseMSM::new(&self.ck))
+  }
+
+  fn commit_with_msm_table(
+    table: &FixedBaseMSM<<E::GE as DlogGroup>::AffineGroupElement>,
+    v: &[E::Scalar],
+  ) -> Result<Commitment<E>, NovaError> {
+    Ok(Commitment {
+      comm: E::GE::vartime_multiscalar_mul_with_table(v, table),
+    })
+  }
 }
 
 #[cfg(test)]
@@ -387,10 +504,16 @@ mod tests {
   use super::*;
 
   use crate::{provider::GrumpkinEngine, CommitmentKey};
-  use std::{fs::File, io::BufWriter};
+  use rand_core::OsRng;
+  use std::{cell::Cell, fs::File, io::BufWriter};
 
   type E = GrumpkinEngine;
 
+  thread_local! {
+    // the number of terms of the multi-scalar multiplications computed by `commit` and `commit_sparse`
+    pub(super) static MSM_TERMS: Cell<usize> = const { Cell::new(0) };
+  }
+
   #[test]
   fn test_key_save_load() {
     let path = "/tmp/pedersen_test.keys";
@@ -411,4 +534,104 @@ mod tests {
     assert_eq!(keys_read.h, keys.h);
     assert_eq!(keys_read.ck, keys.ck);
   }
+
+  #[test]
+  fn test_extend_to() {
+    let mut ck = CommitmentEngine::<E>::setup(b"test", 10);
+    ck.extend_to(100, b"test");
+    assert_eq!(ck, CommitmentEngine::<E>::setup(b"test", 100));
+
+    // a key with enough generators is left unchanged
+    ck.extend_to(20, b"test");
+    assert_eq!(ck.len(), 128);
+
+    // a key derived from a seed is extended with the label followed by the seed
+    let seed = [7u8; 32];
+    let mut ck = CommitmentEngine::<E>::setup_from_seed(b"test", &seed, 3).unwrap();
+    ck.extend_to(40, &[b"test".as_slice(), &seed].concat());
+    assert_eq!(
+      ck,
+      CommitmentEngine::<E>::setup_from_seed(b"test", &seed, 40).unwrap()
+    );
+  }
+
+  #[test]
+  fn test_commit_streaming() {
+    let ck = CommitmentEngine::<E>::setup(b"test", 300);
+    let v = (0..257)
+      .map(|_| <E as Engine>::Scalar::random(&mut OsRng))
+      .collect::<Vec<_>>();
+    let r = <E as Engine>::Scalar::random(&mut OsRng);
+
+    let expected = CommitmentEngine::<E>::commit(&ck, &v, &r);
+    for window in [1, 7, 64, 257, 1024] {
+      assert_eq!(
+        CommitmentEngine::<E>::commit_streaming(&ck, &v, &r, window),
+        expected
+      );
+    }
+  }
+
+  #[test]
+  fn test_commit_sparse() {
+    let n = 1000;
+    let ck = CommitmentEngine::<E>::setup(b"test", n);
+    // a witness with 95% zeros
+    let v = (0..n)
+      .map(|i| {
+        if i % 20 == 0 {
+          <E as Engine>::Scalar::random(&mut OsRng)
+        } else {
+          <E as Engine>::Scalar::ZERO
+        }
+      })
+      .collect::<Vec<_>>();
+    let entries = v
+      .iter()
+      .enumerate()
+      .filter(|(_, s)| !bool::from(s.is_zero()))
+      .map(|(i, s)| (i, *s))
+      .collect::<Vec<_>>();
+    let r = <E as Engine>::Scalar::random(&mut OsRng);
+
+    let terms = MSM_TERMS.with(Cell::get);
+    let expected = CommitmentEngine::<E>::commit(&ck, &v, &r);
+    let dense_terms = MSM_TERMS.with(Cell::get) - terms;
+
+    let terms = MSM_TERMS.with(Cell::get);
+    assert_eq!(
+      CommitmentEngine::<E>::commit_sparse(&ck, &entries, &r),
+      expected
+    );
+    let sparse_terms = MSM_TERMS.with(Cell::get) - terms;
+    assert_eq!(dense_terms, n);
+    assert_eq!(sparse_terms, n / 20);
+
+    // explicit zero entries are skipped
+    let mut with_zeros = entries.clone();
+    with_zeros.insert(1, (1, <E as Engine>::Scalar::ZERO));
+    assert_eq!(
+      CommitmentEngine::<E>::commit_sparse(&ck, &with_zeros, &r),
+      expected
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "out of range")]
+  fn test_commit_sparse_out_of_range() {
+    let ck = CommitmentEngine::<E>::setup(b"test", 8);
+    let entries = [(ck.ck.len(), <E as Engine>::Scalar::ONE)];
+    CommitmentEngine::<E>::commit_sparse(&ck, &entries, &<E as Engine>::Scalar::ZERO);
+  }
+
+  #[test]
+  #[should_panic(expected = "strictly increasing")]
+  fn test_commit_sparse_duplicate_index() {
+    let ck = CommitmentEngine::<E>::setup(b"test", 8);
+    let entries = [
+      (3, <E as Engine>::Scalar::ONE),
+      (3, <E as Engine>::Scalar::ONE),
+    ];
+    CommitmentEngine::<E>::commit_sparse(&ck, &entries, &<E as Engine>::Scalar::ZERO);
+  }
 }
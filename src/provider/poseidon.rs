@@ -25,6 +25,23 @@ impl<Scalar: PrimeField> Default for PoseidonConstantsCircuit<Scalar> {
   }
 }
 
+impl<Scalar: PrimeField> PoseidonConstantsCircuit<Scalar> {
+  /// Returns the width of the permutation along with its number of full and partial rounds
+  pub(crate) fn rounds(&self) -> (usize, usize, usize) {
+    (self.0.width(), self.0.full_rounds, self.0.partial_rounds)
+  }
+
+  /// Returns the (uncompressed) round constants of the permutation
+  pub(crate) fn round_constants(&self) -> &[Scalar] {
+    self.0.round_constants.as_deref().unwrap_or_default()
+  }
+
+  /// Returns the MDS matrix of the permutation in row-major order
+  pub(crate) fn mds_matrix(&self) -> &[Vec<Scalar>] {
+    &self.0.mds_matrices.m
+  }
+}
+
 /// A Poseidon-based RO to use outside circuits
 #[derive(Serialize, Deserialize)]
 pub struct PoseidonRO<Base: PrimeField> {
@@ -52,6 +69,11 @@ where
     self.state.push(e);
   }
 
+  /// Absorb a slice of numbers into the state of the oracle with a single copy
+  fn absorb_batch(&mut self, es: &[Base]) {
+    self.state.extend_from_slice(es);
+  }
+
   /// Compute a challenge by hashing the current state
   fn squeeze(&mut self, num_bits: usize) -> Base {
     let mut sponge = Sponge::new_with_constants(&self.constants.0, Simplex);
@@ -200,6 +222,45 @@ mod tests {
     assert_eq!(num, num2.get_value().unwrap());
   }
 
+  fn test_poseidon_ro_absorb_batch_with<E: Engine>() {
+    // Check that absorbing a batch yields the same hash as absorbing its elements one at a time
+    let mut csprng: OsRng = OsRng;
+    let constants = PoseidonConstantsCircuit::<E::Scalar>::default();
+    for arity in [1, 16] {
+      let z0 = (0..arity)
+        .map(|_| E::Scalar::random(&mut csprng))
+        .collect::<Vec<_>>();
+      let zi = (0..arity)
+        .map(|_| E::Scalar::random(&mut csprng))
+        .collect::<Vec<_>>();
+      let digest = E::Scalar::random(&mut csprng);
+
+      let mut ro: PoseidonRO<E::Scalar> = PoseidonRO::new(constants.clone());
+      ro.absorb(digest);
+      for e in z0.iter().chain(zi.iter()) {
+        ro.absorb(*e);
+      }
+
+      let mut ro_batch: PoseidonRO<E::Scalar> = PoseidonRO::new(constants.clone());
+      ro_batch.absorb(digest);
+      ro_batch.absorb_batch(&z0);
+      ro_batch.absorb_batch(&zi);
+
+      assert_eq!(
+        ro.squeeze(NUM_CHALLENGE_BITS),
+        ro_batch.squeeze(NUM_CHALLENGE_BITS)
+      );
+    }
+  }
+
+  #[test]
+  fn test_poseidon_ro_absorb_batch() {
+    test_poseidon_ro_absorb_batch_with::<PallasEngine>();
+    test_poseidon_ro_absorb_batch_with::<VestaEngine>();
+    test_poseidon_ro_absorb_batch_with::<Bn256EngineKZG>();
+    test_poseidon_ro_absorb_batch_with::<GrumpkinEngine>();
+  }
+
   #[test]
   fn test_poseidon_ro() {
     test_poseidon_ro_with::<PallasEngine>();
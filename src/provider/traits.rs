@@ -1,4 +1,7 @@
-use crate::traits::{commitment::ScalarMul, Group, TranscriptReprTrait};
+use crate::{
+  provider::msm::FixedBaseMSM,
+  traits::{commitment::ScalarMul, Group, TranscriptReprTrait},
+};
 use core::{
   fmt::Debug,
   ops::{Add, AddAssign, Sub, SubAssign},
@@ -51,8 +54,14 @@ pub trait DlogGroup:
     + CurveAffine
     + SerdeObject;
 
-  /// Produce a vector of group elements using a static label
-  fn from_label(label: &'static [u8], n: usize) -> Vec<Self::AffineGroupElement>;
+  /// Produce a vector of group elements using a label
+  fn from_label(label: &[u8], n: usize) -> Vec<Self::AffineGroupElement>;
+
+  /// Produces the group elements at positions `start..n` of those produced by `from_label(label, n)`,
+  /// e.g., to extend a vector of group elements without producing its existing elements again
+  fn from_label_range(label: &[u8], start: usize, n: usize) -> Vec<Self::AffineGroupElement> {
+    Self::from_label(label, n).split_off(start)
+  }
 
   /// Produces a preprocessed element
   fn affine(&self) -> Self::AffineGroupElement;
@@ -86,6 +95,13 @@ pub trait DlogGroupExt: DlogGroup {
       .collect::<Vec<_>>()
   }
 
+  /// A method to compute a multiexponentation with the first `scalars.len()` bases of a
+  /// precomputed table of bases
+  fn vartime_multiscalar_mul_with_table(
+    scalars: &[Self::Scalar],
+    table: &FixedBaseMSM<Self::AffineGroupElement>,
+  ) -> Self;
+
   /// A method to compute a multiexponentation with small scalars
   fn vartime_multiscalar_mul_small<T: Integer + Into<u64> + Copy + Sync + ToPrimitive>(
     scalars: &[T],
@@ -152,10 +168,20 @@ macro_rules! impl_traits_no_dlog_ext {
         $name::Point::from(*p)
       }
 
-      fn from_label(label: &'static [u8], n: usize) -> Vec<Self::AffineGroupElement> {
+      fn from_label(label: &[u8], n: usize) -> Vec<Self::AffineGroupElement> {
+        Self::from_label_range(label, 0, n)
+      }
+
+      fn from_label_range(label: &[u8], start: usize, n: usize) -> Vec<Self::AffineGroupElement> {
         let mut shake = Shake256::default();
         shake.update(label);
         let mut reader = shake.finalize_xof();
+        // skip the uniform bytes of the elements before `start`, which are not hashed to the curve
+        let mut skipped = [0u8; 32];
+        for _ in 0..start.min(n) {
+          reader.read_exact(&mut skipped).unwrap();
+        }
+        let n = n.saturating_sub(start);
         let mut uniform_bytes_vec = Vec::new();
         for _ in 0..n {
           let mut uniform_bytes = [0u8; 32];
@@ -268,6 +294,13 @@ macro_rules! impl_traits {
         msm(scalars, bases)
       }
 
+      fn vartime_multiscalar_mul_with_table(
+        scalars: &[Self::Scalar],
+        table: &FixedBaseMSM<Self::AffineGroupElement>,
+      ) -> Self {
+        table.msm(scalars)
+      }
+
       fn vartime_multiscalar_mul_small<T: Integer + Into<u64> + Copy + Sync + ToPrimitive>(
         scalars: &[T],
         bases: &[Self::AffineGroupElement],
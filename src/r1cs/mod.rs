@@ -8,15 +8,15 @@ use crate::{
     utils::scalar_as_base,
   },
   traits::{
-    commitment::CommitmentEngineTrait, AbsorbInRO2Trait, AbsorbInROTrait, Engine, ROTrait,
-    TranscriptReprTrait,
+    commitment::{CommitmentEngineTrait, CommitmentTrait},
+    AbsorbInRO2Trait, AbsorbInROTrait, Engine, ROTrait, TranscriptReprTrait,
   },
   Commitment, CommitmentKey, DerandKey, CE,
 };
 use core::cmp::max;
 use ff::Field;
 use once_cell::sync::OnceCell;
-use rand_core::OsRng;
+use rand_core::{OsRng, RngCore};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -74,6 +74,29 @@ pub struct RelaxedR1CSInstance<E: Engine> {
 
 pub type CommitmentKeyHint<E> = dyn Fn(&R1CSShape<E>) -> usize;
 
+// a vector with at most one nonzero entry in this many is committed to with `commit_sparse`
+const SPARSE_COMMIT_RATIO: usize = 4;
+
+// commits to `v` as `commit` does, skipping the zero entries of `v` if it is sparse
+fn commit_maybe_sparse<E: Engine>(
+  ck: &CommitmentKey<E>,
+  v: &[E::Scalar],
+  r: &E::Scalar,
+) -> Commitment<E> {
+  let num_nonzero = v.par_iter().filter(|s| !bool::from(s.is_zero())).count();
+  if num_nonzero * SPARSE_COMMIT_RATIO > v.len() {
+    return CE::<E>::commit(ck, v, r);
+  }
+
+  let entries = v
+    .iter()
+    .enumerate()
+    .filter(|(_, s)| !bool::from(s.is_zero()))
+    .map(|(i, s)| (i, *s))
+    .collect::<Vec<_>>();
+  CE::<E>::commit_sparse(ck, &entries, r)
+}
+
 impl<E: Engine> R1CSShape<E> {
   /// Create an object of type `R1CSShape` from the explicitly specified R1CS matrices
   pub fn new(
@@ -127,10 +150,25 @@ impl<E: Engine> R1CSShape<E> {
   ///   to provide is the ck_floor field defined in the trait `RelaxedR1CSSNARKTrait`.
   ///
   pub fn commitment_key(&self, ck_floor: &CommitmentKeyHint<E>) -> CommitmentKey<E> {
-    let num_cons = self.num_cons;
-    let num_vars = self.num_vars;
     let ck_hint = ck_floor(self);
-    E::CE::setup(b"ck", max(max(num_cons, num_vars), ck_hint))
+    E::CE::setup(b"ck", max(self.min_commitment_key_size(), ck_hint))
+  }
+
+  /// Returns the minimum number of generators that a commitment key needs to fold instances of
+  /// `self`, i.e., to commit to a witness and to the error and cross-term vectors, independently
+  /// of the floor that a SNARK for `self` may put on it
+  pub fn min_commitment_key_size(&self) -> usize {
+    max(self.num_cons, self.num_vars)
+  }
+
+  /// Same as `commitment_key`, but derives the generators deterministically from `seed`.
+  pub fn commitment_key_from_seed(
+    &self,
+    ck_floor: &CommitmentKeyHint<E>,
+    seed: &[u8; 32],
+  ) -> Result<CommitmentKey<E>, NovaError> {
+    let ck_hint = ck_floor(self);
+    E::CE::setup_from_seed(b"ck", seed, max(self.min_commitment_key_size(), ck_hint))
   }
 
   /// returned the digest of the `R1CSShape`
@@ -152,6 +190,7 @@ impl<E: Engine> R1CSShape<E> {
     cons_valid && vars_valid && io_lt_vars
   }
 
+  /// Computes the products `(Az, Bz, Cz)` of the constraint matrices with `z = (W, u, X)`
   pub fn multiply_vec(
     &self,
     z: &[E::Scalar],
@@ -193,8 +232,8 @@ impl<E: Engine> R1CSShape<E> {
     // verify if comm_E and comm_W are commitments to E and W
     let res_comm = {
       let (comm_W, comm_E) = rayon::join(
-        || CE::<E>::commit(ck, &W.W, &W.r_W),
-        || CE::<E>::commit(ck, &W.E, &W.r_E),
+        || commit_maybe_sparse::<E>(ck, &W.W, &W.r_W),
+        || commit_maybe_sparse::<E>(ck, &W.E, &W.r_E),
       );
       U.comm_W == comm_W && U.comm_E == comm_E
     };
@@ -214,10 +253,10 @@ impl<E: Engine> R1CSShape<E> {
     Ok(())
   }
 
-  /// Checks if the R1CS instance is satisfiable given a witness and its shape
-  pub fn is_sat(
+  /// Checks if the R1CS instance is satisfiable given a witness, without checking the
+  /// commitment to the witness, which is left to `is_sat`
+  pub fn is_sat_constraints(
     &self,
-    ck: &CommitmentKey<E>,
     U: &R1CSInstance<E>,
     W: &R1CSWitness<E>,
   ) -> Result<(), NovaError> {
@@ -225,25 +264,33 @@ impl<E: Engine> R1CSShape<E> {
     assert_eq!(U.X.len(), self.num_io);
 
     // verify if Az * Bz = u*Cz
-    let res_eq = {
-      let z = [W.W.clone(), vec![E::Scalar::ONE], U.X.clone()].concat();
-      let (Az, Bz, Cz) = self.multiply_vec(&z)?;
-      assert_eq!(Az.len(), self.num_cons);
-      assert_eq!(Bz.len(), self.num_cons);
-      assert_eq!(Cz.len(), self.num_cons);
-
-      (0..self.num_cons).all(|i| Az[i] * Bz[i] == Cz[i])
-    };
-
-    // verify if comm_W is a commitment to W
-    let res_comm = U.comm_W == CE::<E>::commit(ck, &W.W, &W.r_W);
+    let z = [W.W.clone(), vec![E::Scalar::ONE], U.X.clone()].concat();
+    let (Az, Bz, Cz) = self.multiply_vec(&z)?;
+    assert_eq!(Az.len(), self.num_cons);
+    assert_eq!(Bz.len(), self.num_cons);
+    assert_eq!(Cz.len(), self.num_cons);
 
-    if !res_eq {
+    if !(0..self.num_cons).all(|i| Az[i] * Bz[i] == Cz[i]) {
       return Err(NovaError::UnSat {
         reason: "R1CS is unsatisfiable".to_string(),
       });
     }
 
+    Ok(())
+  }
+
+  /// Checks if the R1CS instance is satisfiable given a witness and its shape
+  pub fn is_sat(
+    &self,
+    ck: &CommitmentKey<E>,
+    U: &R1CSInstance<E>,
+    W: &R1CSWitness<E>,
+  ) -> Result<(), NovaError> {
+    self.is_sat_constraints(U, W)?;
+
+    // verify if comm_W is a commitment to W
+    let res_comm = U.comm_W == commit_maybe_sparse::<E>(ck, &W.W, &W.r_W);
+
     if !res_comm {
       return Err(NovaError::UnSat {
         reason: "Invalid commitment".to_string(),
@@ -286,7 +333,7 @@ impl<E: Engine> R1CSShape<E> {
       .map(|(((az, bz), cz), e)| *az * *bz - u * *cz - *e)
       .collect::<Vec<E::Scalar>>();
 
-    let comm_T = CE::<E>::commit(ck, &T, r_T);
+    let comm_T = commit_maybe_sparse::<E>(ck, &T, r_T);
 
     Ok((T, comm_T))
   }
@@ -325,7 +372,63 @@ impl<E: Engine> R1CSShape<E> {
       .map(|((((az, bz), cz), e1), e2)| *az * *bz - u * *cz - *e1 - *e2)
       .collect::<Vec<E::Scalar>>();
 
-    let comm_T = CE::<E>::commit(ck, &T, r_T);
+    let comm_T = commit_maybe_sparse::<E>(ck, &T, r_T);
+
+    Ok((T, comm_T))
+  }
+
+  /// A method to compute the cross-terms `T_1, ..., T_{2k}` and their commitments that arise when
+  /// folding `k` R1CS instance-witness pairs into a Relaxed R1CS instance-witness pair at once,
+  /// where the `i`-th incoming pair is weighted by the `i`-th power of a single challenge
+  pub fn commit_T_multi(
+    &self,
+    ck: &CommitmentKey<E>,
+    U1: &RelaxedR1CSInstance<E>,
+    W1: &RelaxedR1CSWitness<E>,
+    U2: &[R1CSInstance<E>],
+    W2: &[R1CSWitness<E>],
+    r_T: &[E::Scalar],
+  ) -> Result<(Vec<Vec<E::Scalar>>, Vec<Commitment<E>>), NovaError> {
+    let k = U2.len();
+    if W2.len() != k || r_T.len() != 2 * k {
+      return Err(NovaError::InvalidInputLength);
+    }
+
+    let u = [vec![U1.u], vec![E::Scalar::ONE; k]].concat();
+    let Z = [[W1.W.clone(), vec![U1.u], U1.X.clone()].concat()]
+      .into_iter()
+      .chain(
+        U2.iter()
+          .zip(W2)
+          .map(|(U, W)| [W.W.clone(), vec![E::Scalar::ONE], U.X.clone()].concat()),
+      )
+      .collect::<Vec<_>>();
+    let ABC = Z
+      .par_iter()
+      .map(|z| self.multiply_vec(z))
+      .collect::<Result<Vec<_>, _>>()?;
+
+    // T_d collects the terms of `AZ * BZ - u * CZ` for `Z = sum_i r^i Z_i` whose degree in `r` is `d`
+    let T = (1..=2 * k)
+      .into_par_iter()
+      .map(|d| {
+        let mut T_d = vec![E::Scalar::ZERO; self.num_cons];
+        for i in d.saturating_sub(k)..=d.min(k) {
+          let (AZ_i, _, _) = &ABC[i];
+          let (_, BZ_j, CZ_j) = &ABC[d - i];
+          for (t, ((az, bz), cz)) in T_d.iter_mut().zip(AZ_i.iter().zip(BZ_j).zip(CZ_j)) {
+            *t += *az * *bz - u[i] * *cz;
+          }
+        }
+        T_d
+      })
+      .collect::<Vec<_>>();
+
+    let comm_T = T
+      .par_iter()
+      .zip(r_T)
+      .map(|(T_d, r_T_d)| CE::<E>::commit(ck, T_d, r_T_d))
+      .collect();
 
     Ok((T, comm_T))
   }
@@ -405,6 +508,34 @@ impl<E: Engine> R1CSShape<E> {
     let r_W = E::Scalar::random(&mut OsRng);
     let r_E = E::Scalar::random(&mut OsRng);
 
+    self.random_instance_witness_from(ck, Z, r_W, r_E)
+  }
+
+  /// Same as `sample_random_instance_witness`, but draws all randomness from the supplied `rng`
+  pub fn sample_random_instance_witness_with_rng(
+    &self,
+    ck: &CommitmentKey<E>,
+    mut rng: impl RngCore,
+  ) -> Result<(RelaxedR1CSInstance<E>, RelaxedR1CSWitness<E>), NovaError> {
+    // sample Z = (W, u, X) sequentially so that the result only depends on `rng`
+    let Z = (0..self.num_vars + self.num_io + 1)
+      .map(|_| E::Scalar::random(&mut rng))
+      .collect::<Vec<E::Scalar>>();
+
+    let r_W = E::Scalar::random(&mut rng);
+    let r_E = E::Scalar::random(&mut rng);
+
+    self.random_instance_witness_from(ck, Z, r_W, r_E)
+  }
+
+  /// Builds a satisfying relaxed instance/witness pair from a sampled `Z = (W, u, X)` and blinds
+  fn random_instance_witness_from(
+    &self,
+    ck: &CommitmentKey<E>,
+    Z: Vec<E::Scalar>,
+    r_W: E::Scalar,
+    r_E: E::Scalar,
+  ) -> Result<(RelaxedR1CSInstance<E>, RelaxedR1CSWitness<E>), NovaError> {
     let u = Z[self.num_vars];
 
     // compute E <- AZ o BZ - u * CZ
@@ -518,6 +649,19 @@ impl<E: Engine> RelaxedR1CSWitness<E> {
     }
   }
 
+  /// Builds a `RelaxedR1CSWitness` from raw components, with both blinds set to zero.
+  ///
+  /// This is meant for tests that need arbitrary witnesses. It bypasses the satisfiability invariant,
+  /// so the result need not satisfy any shape, and its commitments are computed with zero blinds.
+  pub fn from_parts(W: Vec<E::Scalar>, E: Vec<E::Scalar>) -> RelaxedR1CSWitness<E> {
+    RelaxedR1CSWitness {
+      W,
+      r_W: E::Scalar::ZERO,
+      E,
+      r_E: E::Scalar::ZERO,
+    }
+  }
+
   /// Initializes a new `RelaxedR1CSWitness` from an `R1CSWitness`
   pub fn from_r1cs_witness(S: &R1CSShape<E>, witness: &R1CSWitness<E>) -> RelaxedR1CSWitness<E> {
     RelaxedR1CSWitness {
@@ -568,6 +712,41 @@ impl<E: Engine> RelaxedR1CSWitness<E> {
     Ok(RelaxedR1CSWitness { W, r_W, E, r_E })
   }
 
+  /// Folds a batch of incoming `R1CSWitness`es into the current one, weighting the `i`-th witness
+  /// and the `d`-th cross-term by the `i`-th and `d`-th powers of `r`, respectively
+  pub fn fold_multi(
+    &self,
+    W2: &[R1CSWitness<E>],
+    T: &[Vec<E::Scalar>],
+    r_T: &[E::Scalar],
+    r: &E::Scalar,
+  ) -> Result<RelaxedR1CSWitness<E>, NovaError> {
+    let mut W = self.W.clone();
+    let mut r_W = self.r_W;
+    let mut r_i = E::Scalar::ONE;
+    for W2_i in W2 {
+      if W2_i.W.len() != W.len() {
+        return Err(NovaError::InvalidWitnessLength);
+      }
+      r_i *= r;
+      W.par_iter_mut()
+        .zip(&W2_i.W)
+        .for_each(|(a, b)| *a += r_i * *b);
+      r_W += r_i * W2_i.r_W;
+    }
+
+    let mut E = self.E.clone();
+    let mut r_E = self.r_E;
+    let mut r_d = E::Scalar::ONE;
+    for (T_d, r_T_d) in T.iter().zip(r_T) {
+      r_d *= r;
+      E.par_iter_mut().zip(T_d).for_each(|(a, b)| *a += r_d * *b);
+      r_E += r_d * r_T_d;
+    }
+
+    Ok(RelaxedR1CSWitness { W, r_W, E, r_E })
+  }
+
   /// Folds an incoming `RelaxedR1CSWitness` into the current one
   /// E2 is not necessarily zero vec  
   pub fn fold_relaxed(
@@ -618,6 +797,7 @@ impl<E: Engine> RelaxedR1CSWitness<E> {
     }
   }
 
+  /// Returns the witness with both blinds set to zero, along with the blinds of `W` and `E`
   pub fn derandomize(&self) -> (Self, E::Scalar, E::Scalar) {
     (
       RelaxedR1CSWitness {
@@ -644,6 +824,25 @@ impl<E: Engine> RelaxedR1CSInstance<E> {
     }
   }
 
+  /// Builds a `RelaxedR1CSInstance` from raw components.
+  ///
+  /// This is meant for tests that need arbitrary instances, e.g., to exercise edge cases in
+  /// `NIFS::verify`. It bypasses the satisfiability invariant, so the result need not be satisfied
+  /// by any witness.
+  pub fn from_parts(
+    comm_W: Commitment<E>,
+    comm_E: Commitment<E>,
+    u: E::Scalar,
+    X: Vec<E::Scalar>,
+  ) -> RelaxedR1CSInstance<E> {
+    RelaxedR1CSInstance {
+      comm_W,
+      comm_E,
+      u,
+      X,
+    }
+  }
+
   /// Initializes a new `RelaxedR1CSInstance` from an `R1CSInstance`
   pub fn from_r1cs_instance(
     ck: &CommitmentKey<E>,
@@ -671,8 +870,9 @@ impl<E: Engine> RelaxedR1CSInstance<E> {
     }
   }
 
-  /// Folds an incoming `R1CSInstance` into the current one
-  pub fn fold(
+  /// Folds an incoming `R1CSInstance` into the current one with an already-derived challenge `r`,
+  /// e.g., one derived by `NIFS::challenge` before the fold, as `NIFS::verify` does
+  pub fn fold_with_challenge(
     &self,
     U2: &R1CSInstance<E>,
     comm_T: &Commitment<E>,
@@ -700,6 +900,42 @@ impl<E: Engine> RelaxedR1CSInstance<E> {
     }
   }
 
+  /// Folds a batch of incoming `R1CSInstance`s into the current one, weighting the `i`-th instance
+  /// and the `d`-th cross-term commitment by the `i`-th and `d`-th powers of `r`, respectively
+  pub fn fold_multi(
+    &self,
+    U2: &[R1CSInstance<E>],
+    comm_T: &[Commitment<E>],
+    r: &E::Scalar,
+  ) -> RelaxedR1CSInstance<E> {
+    let mut X = self.X.clone();
+    let mut comm_W = self.comm_W;
+    let mut u = self.u;
+    let mut r_i = E::Scalar::ONE;
+    for U2_i in U2 {
+      r_i *= r;
+      X.par_iter_mut()
+        .zip(&U2_i.X)
+        .for_each(|(a, b)| *a += r_i * *b);
+      comm_W = comm_W + U2_i.comm_W * r_i;
+      u += r_i;
+    }
+
+    let mut comm_E = self.comm_E;
+    let mut r_d = E::Scalar::ONE;
+    for comm_T_d in comm_T {
+      r_d *= r;
+      comm_E = comm_E + *comm_T_d * r_d;
+    }
+
+    RelaxedR1CSInstance {
+      comm_W,
+      comm_E,
+      X,
+      u,
+    }
+  }
+
   /// Folds an incoming `RelaxedR1CSInstance` into the current one
   pub fn fold_relaxed(
     &self,
@@ -729,6 +965,8 @@ impl<E: Engine> RelaxedR1CSInstance<E> {
     }
   }
 
+  /// Removes the blinds `r_W` and `r_E` from the commitments to `W` and `E`, as returned by
+  /// `RelaxedR1CSWitness::derandomize`
   pub fn derandomize(
     &self,
     dk: &DerandKey<E>,
@@ -756,18 +994,40 @@ impl<E: Engine> TranscriptReprTrait<E::GE> for RelaxedR1CSInstance<E> {
   }
 }
 
-impl<E: Engine> AbsorbInROTrait<E> for RelaxedR1CSInstance<E> {
-  fn absorb_in_ro(&self, ro: &mut E::RO) {
-    self.comm_W.absorb_in_ro(ro);
-    self.comm_E.absorb_in_ro(ro);
-    ro.absorb(scalar_as_base::<E>(self.u));
+impl<E: Engine> RelaxedR1CSInstance<E> {
+  /// Returns the elements that `absorb_in_ro` absorbs, in order
+  pub(crate) fn ro_elements(&self) -> Vec<E::Base> {
+    let comm_elements = |comm: &Commitment<E>| {
+      let (x, y, is_infinity) = comm.to_coordinates();
+      [
+        x,
+        y,
+        if is_infinity {
+          E::Base::ONE
+        } else {
+          E::Base::ZERO
+        },
+      ]
+    };
 
-    // absorb each element of self.X in bignum format
+    let mut elements = Vec::with_capacity(7 + self.X.len() * BN_N_LIMBS);
+    elements.extend(comm_elements(&self.comm_W));
+    elements.extend(comm_elements(&self.comm_E));
+    elements.push(scalar_as_base::<E>(self.u));
+
+    // each element of self.X is absorbed in bignum format
     for x in &self.X {
       let limbs: Vec<E::Scalar> = nat_to_limbs(&f_to_nat(x), BN_LIMB_WIDTH, BN_N_LIMBS).unwrap();
-      for limb in limbs {
-        ro.absorb(scalar_as_base::<E>(limb));
-      }
+      elements.extend(limbs.into_iter().map(scalar_as_base::<E>));
+    }
+    elements
+  }
+}
+
+impl<E: Engine> AbsorbInROTrait<E> for RelaxedR1CSInstance<E> {
+  fn absorb_in_ro(&self, ro: &mut E::RO) {
+    for e in self.ro_elements() {
+      ro.absorb(e);
     }
   }
 }
@@ -23,6 +23,27 @@ use crate::{
 use ff::Field;
 use itertools::Itertools as _;
 use rayon::{iter::IntoParallelRefIterator, prelude::*};
+use serde::{Deserialize, Serialize};
+
+/// Tuning knobs for the Spartan prover, which affect its performance but not the proofs it produces
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpartanConfig {
+  /// The minimum number of evaluation points handled by each rayon task in a sum-check round.
+  /// Zero (the default) selects a chunk size based on `rayon::current_num_threads()`.
+  pub sumcheck_chunk_size: usize,
+}
+
+impl SpartanConfig {
+  /// Returns the chunk size to use in a sum-check over polynomials with `len` evaluations
+  fn chunk_size_for(&self, len: usize) -> usize {
+    if self.sumcheck_chunk_size > 0 {
+      self.sumcheck_chunk_size
+    } else {
+      // leave a few tasks per thread so that work stealing can balance the load
+      (len / (4 * rayon::current_num_threads())).max(1)
+    }
+  }
+}
 
 // Creates a vector of the first `n` powers of `s`.
 fn powers<E: Engine>(s: &E::Scalar, n: usize) -> Vec<E::Scalar> {
@@ -277,3 +298,57 @@ fn compute_eval_table_sparse<E: Engine>(
 
   (A_evals, B_evals, C_evals)
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use crate::{
+    frontend::{
+      num::AllocatedNum,
+      r1cs::{NovaShape, NovaWitness},
+      shape_cs::ShapeCS,
+      solver::SatisfyingAssignment,
+      ConstraintSystem,
+    },
+    r1cs::{CommitmentKeyHint, R1CSShape, RelaxedR1CSInstance, RelaxedR1CSWitness},
+    traits::{commitment::CommitmentEngineTrait, Engine},
+    CommitmentKey,
+  };
+
+  // repeatedly squares a single input, producing `num_squarings` multiplication constraints
+  fn synthesize_squarings<E: Engine, CS: ConstraintSystem<E::Scalar>>(
+    cs: &mut CS,
+    num_squarings: usize,
+  ) {
+    let x = AllocatedNum::alloc_input(cs.namespace(|| "x"), || Ok(E::Scalar::from(3u64))).unwrap();
+    let mut y = x.clone();
+    for i in 0..num_squarings {
+      y = y.square(cs.namespace(|| format!("square {i}"))).unwrap();
+    }
+    y.inputize(cs.namespace(|| "y")).unwrap();
+  }
+
+  // returns the shape of a circuit of `num_squarings` squarings with a commitment key sampled with
+  // `ck_hint`, and a derandomized relaxed instance and witness that satisfy it
+  pub(crate) fn squarings_instance<E: Engine>(
+    num_squarings: usize,
+    ck_hint: &CommitmentKeyHint<E>,
+  ) -> (
+    R1CSShape<E>,
+    CommitmentKey<E>,
+    RelaxedR1CSInstance<E>,
+    RelaxedR1CSWitness<E>,
+  ) {
+    let mut cs: ShapeCS<E> = ShapeCS::new();
+    synthesize_squarings::<E, _>(&mut cs, num_squarings);
+    let (S, ck) = cs.r1cs_shape(ck_hint);
+
+    let mut cs = SatisfyingAssignment::<E>::new();
+    synthesize_squarings::<E, _>(&mut cs, num_squarings);
+    let (u, w) = cs.r1cs_instance_and_witness(&S, &ck).unwrap();
+    let U = RelaxedR1CSInstance::from_r1cs_instance_unchecked(&u.comm_W, &u.X);
+    let W = RelaxedR1CSWitness::from_r1cs_witness(&S, &w);
+    let (W, blind_W, blind_E) = W.derandomize();
+    let U = U.derandomize(&E::CE::derand_key(&ck), &blind_W, &blind_E);
+    (S, ck, U, W)
+  }
+}
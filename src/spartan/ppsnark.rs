@@ -35,6 +35,7 @@ use itertools::Itertools as _;
 use once_cell::sync::OnceCell;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 
 fn padded<E: Engine>(v: &[E::Scalar], n: usize, e: &E::Scalar) -> Vec<E::Scalar> {
   let mut v_padded = vec![*e; n];
@@ -843,6 +844,36 @@ pub struct ProverKey<E: Engine, EE: EvaluationEngineTrait<E>> {
   S_repr: R1CSShapeSparkRepr<E>,
   S_comm: R1CSShapeSparkCommitment<E>,
   vk_digest: E::Scalar, // digest of verifier's key
+  // the padded shape cached by `precompute`, along with the digest of the shape it was padded from
+  #[serde(skip)]
+  S_padded: Option<(E::Scalar, R1CSShape<E>)>,
+}
+
+impl<E: Engine, EE: EvaluationEngineTrait<E>> ProverKey<E, EE> {
+  /// Pads the shape `S` once and caches the result in the key, so that subsequent calls to `prove`
+  /// with the same shape skip padding it.
+  ///
+  /// The cache is not serialized, so a deserialized key is cold until `precompute` is called again.
+  pub fn precompute(&mut self, S: &R1CSShape<E>) {
+    self.S_padded = Some((S.digest(), S.pad()));
+  }
+
+  /// Returns true if `precompute` was called on this key
+  pub fn is_warm(&self) -> bool {
+    self.S_padded.is_some()
+  }
+
+  // returns the padded shape, reusing the cached one if it was padded from `S`
+  fn padded_shape<'a>(&'a self, S: &R1CSShape<E>) -> Cow<'a, R1CSShape<E>> {
+    match &self.S_padded {
+      Some((digest, S_padded)) if *digest == S.digest() => Cow::Borrowed(S_padded),
+      _ => {
+        #[cfg(test)]
+        tests::PAD_COUNT.with(|count| count.set(count.get() + 1));
+        Cow::Owned(S.pad())
+      }
+    }
+  }
 }
 
 /// A type that represents the verifier's key
@@ -973,9 +1004,9 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARK<E, EE> {
 
       let evals: Vec<Vec<E::Scalar>> = evals_mem
         .into_iter()
-        .chain(evals_outer.into_iter())
-        .chain(evals_inner.into_iter())
-        .chain(evals_witness.into_iter())
+        .chain(evals_outer)
+        .chain(evals_inner)
+        .chain(evals_witness)
         .collect::<Vec<Vec<E::Scalar>>>();
       assert_eq!(evals.len(), claims.len());
 
@@ -1087,6 +1118,7 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for Relax
       S_repr,
       S_comm,
       vk_digest: vk.digest(),
+      S_padded: None,
     };
 
     Ok((pk, vk))
@@ -1100,8 +1132,8 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for Relax
     U: &RelaxedR1CSInstance<E>,
     W: &RelaxedR1CSWitness<E>,
   ) -> Result<Self, NovaError> {
-    // pad the R1CSShape
-    let S = S.pad();
+    // pad the R1CSShape, unless it was precomputed
+    let S = pk.padded_shape(S);
     // sanity check that R1CSShape has all required size characteristics
     assert!(S.is_regular_shape());
 
@@ -1639,4 +1671,53 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for Relax
 
     Ok(())
   }
+
+  fn precompute_msm_tables(vk: &mut Self::VerifierKey) {
+    EE::precompute_msm_tables(&mut vk.vk_ee);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    provider::{ipa_pc, PallasEngine},
+    spartan::tests::squarings_instance,
+  };
+  use std::cell::Cell;
+
+  thread_local! {
+    // the number of times `prove` padded a shape on this thread
+    pub(super) static PAD_COUNT: Cell<usize> = const { Cell::new(0) };
+  }
+
+  fn test_precompute_with<E: Engine, EE: EvaluationEngineTrait<E>>() {
+    let (S, ck, U, W) = squarings_instance::<E>(100, &*RelaxedR1CSSNARK::<E, EE>::ck_floor());
+
+    let (pk, vk) = RelaxedR1CSSNARK::<E, EE>::setup(&ck, &S).unwrap();
+    assert!(!pk.is_warm());
+
+    // a cold key pads the shape on every call
+    let count = PAD_COUNT.with(Cell::get);
+    let snark = RelaxedR1CSSNARK::<E, EE>::prove(&ck, &pk, &S, &U, &W).unwrap();
+    assert!(snark.verify(&vk, &U).is_ok());
+    assert_eq!(PAD_COUNT.with(Cell::get), count + 1);
+    let expected = bincode::serialize(&snark).unwrap();
+
+    // a warm key produces the same proofs without padding the shape again
+    let mut warm_pk = pk.clone();
+    warm_pk.precompute(&S);
+    assert!(warm_pk.is_warm());
+    let count = PAD_COUNT.with(Cell::get);
+    for _ in 0..2 {
+      let snark = RelaxedR1CSSNARK::<E, EE>::prove(&ck, &warm_pk, &S, &U, &W).unwrap();
+      assert_eq!(bincode::serialize(&snark).unwrap(), expected);
+    }
+    assert_eq!(PAD_COUNT.with(Cell::get), count);
+  }
+
+  #[test]
+  fn test_precompute() {
+    test_precompute_with::<PallasEngine, ipa_pc::EvaluationEngine<_>>();
+  }
 }
@@ -14,7 +14,7 @@ use crate::{
     polys::{eq::EqPolynomial, multilinear::MultilinearPolynomial, multilinear::SparsePolynomial},
     powers,
     sumcheck::SumcheckProof,
-    PolyEvalInstance, PolyEvalWitness,
+    PolyEvalInstance, PolyEvalWitness, SpartanConfig,
   },
   traits::{
     evaluation::EvaluationEngineTrait,
@@ -35,6 +35,21 @@ use serde::{Deserialize, Serialize};
 pub struct ProverKey<E: Engine, EE: EvaluationEngineTrait<E>> {
   pk_ee: EE::ProverKey,
   vk_digest: E::Scalar, // digest of the verifier's key
+  #[serde(default)]
+  config: SpartanConfig,
+}
+
+impl<E: Engine, EE: EvaluationEngineTrait<E>> ProverKey<E, EE> {
+  /// Returns the configuration used by the prover
+  pub fn config(&self) -> &SpartanConfig {
+    &self.config
+  }
+
+  /// Sets the configuration used by the prover
+  pub fn with_config(mut self, config: SpartanConfig) -> Self {
+    self.config = config;
+    self
+  }
 }
 
 /// A type that represents the verifier's key
@@ -106,6 +121,7 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for Relax
     let pk = ProverKey {
       pk_ee,
       vk_digest: vk.digest(),
+      config: SpartanConfig::default(),
     };
 
     Ok((pk, vk))
@@ -172,6 +188,7 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for Relax
       &mut poly_Bz,
       &mut poly_uCz_E,
       comb_func_outer,
+      pk.config.chunk_size_for(S.num_cons),
       &mut transcript,
     )?;
 
@@ -216,6 +233,7 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for Relax
       &mut MultilinearPolynomial::new(poly_ABC),
       &mut MultilinearPolynomial::new(poly_z),
       comb_func,
+      pk.config.chunk_size_for(S.num_vars * 2),
       &mut transcript,
     )?;
 
@@ -403,6 +421,10 @@ impl<E: Engine, EE: EvaluationEngineTrait<E>> RelaxedR1CSSNARKTrait<E> for Relax
 
     Ok(())
   }
+
+  fn precompute_msm_tables(vk: &mut Self::VerifierKey) {
+    EE::precompute_msm_tables(&mut vk.vk_ee);
+  }
 }
 
 /// Reduces a batch of polynomial evaluation claims using Sumcheck
@@ -543,3 +565,36 @@ fn batch_eval_verify<E: Engine>(
 
   Ok(u_joint)
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    provider::{ipa_pc, PallasEngine},
+    spartan::tests::squarings_instance,
+    traits::snark::default_ck_hint,
+  };
+
+  fn test_chunk_size_independence_with<E: Engine, EE: EvaluationEngineTrait<E>>() {
+    let (S, ck, U, W) = squarings_instance::<E>(100, &*default_ck_hint());
+
+    let (pk, vk) = RelaxedR1CSSNARK::<E, EE>::setup(&ck, &S).unwrap();
+    let snark = RelaxedR1CSSNARK::<E, EE>::prove(&ck, &pk, &S, &U, &W).unwrap();
+    assert!(snark.verify(&vk, &U).is_ok());
+    let expected = bincode::serialize(&snark).unwrap();
+
+    let mut pk = pk;
+    for chunk_size in [1, 3, 16, 1 << 10] {
+      pk = pk.with_config(SpartanConfig {
+        sumcheck_chunk_size: chunk_size,
+      });
+      let snark = RelaxedR1CSSNARK::<E, EE>::prove(&ck, &pk, &S, &U, &W).unwrap();
+      assert_eq!(bincode::serialize(&snark).unwrap(), expected);
+    }
+  }
+
+  #[test]
+  fn test_chunk_size_independence() {
+    test_chunk_size_independence_with::<PallasEngine, ipa_pc::EvaluationEngine<_>>();
+  }
+}
@@ -124,6 +124,7 @@ impl<E: Engine> SumcheckProof<E> {
     poly_A: &MultilinearPolynomial<E::Scalar>,
     poly_B: &MultilinearPolynomial<E::Scalar>,
     comb_func: &F,
+    chunk_size: usize,
   ) -> (E::Scalar, E::Scalar)
   where
     F: Fn(&E::Scalar, &E::Scalar) -> E::Scalar + Sync,
@@ -131,6 +132,7 @@ impl<E: Engine> SumcheckProof<E> {
     let len = poly_A.len() / 2;
     (0..len)
       .into_par_iter()
+      .with_min_len(chunk_size)
       .map(|i| {
         // eval 0: bound_func is A(low)
         let eval_point_0 = comb_func(&poly_A[i], &poly_B[i]);
@@ -153,6 +155,7 @@ impl<E: Engine> SumcheckProof<E> {
     poly_A: &mut MultilinearPolynomial<E::Scalar>,
     poly_B: &mut MultilinearPolynomial<E::Scalar>,
     comb_func: F,
+    chunk_size: usize,
     transcript: &mut E::TE,
   ) -> Result<(Self, Vec<E::Scalar>, Vec<E::Scalar>), NovaError>
   where
@@ -164,7 +167,7 @@ impl<E: Engine> SumcheckProof<E> {
     for _ in 0..num_rounds {
       let poly = {
         let (eval_point_0, eval_point_2) =
-          Self::compute_eval_points_quad(poly_A, poly_B, &comb_func);
+          Self::compute_eval_points_quad(poly_A, poly_B, &comb_func, chunk_size);
 
         let evals = vec![eval_point_0, claim_per_round - eval_point_0, eval_point_2];
         UniPoly::from_evals(&evals)
@@ -252,7 +255,7 @@ impl<E: Engine> SumcheckProof<E> {
         (num_rounds, claims, poly_A_vec, poly_B_vec),
         |num_rounds, claim, poly_A, poly_B| {
           if remaining_rounds <= *num_rounds {
-            Self::compute_eval_points_quad(poly_A, poly_B, &comb_func)
+            Self::compute_eval_points_quad(poly_A, poly_B, &comb_func, 1)
           } else {
             let remaining_variables = remaining_rounds - num_rounds - 1;
             let scaled_claim = E::Scalar::from((1 << remaining_variables) as u64) * claim;
@@ -372,12 +375,32 @@ impl<E: Engine> SumcheckProof<E> {
     poly_D: &MultilinearPolynomial<E::Scalar>,
     comb_func: &F,
   ) -> (E::Scalar, E::Scalar, E::Scalar)
+  where
+    F: Fn(&E::Scalar, &E::Scalar, &E::Scalar, &E::Scalar) -> E::Scalar + Sync,
+  {
+    Self::compute_eval_points_cubic_with_additive_term_chunked(
+      poly_A, poly_B, poly_C, poly_D, comb_func, 1,
+    )
+  }
+
+  /// Same as `compute_eval_points_cubic_with_additive_term`, but hands at least `chunk_size`
+  /// evaluation points to each rayon task
+  #[inline]
+  fn compute_eval_points_cubic_with_additive_term_chunked<F>(
+    poly_A: &MultilinearPolynomial<E::Scalar>,
+    poly_B: &MultilinearPolynomial<E::Scalar>,
+    poly_C: &MultilinearPolynomial<E::Scalar>,
+    poly_D: &MultilinearPolynomial<E::Scalar>,
+    comb_func: &F,
+    chunk_size: usize,
+  ) -> (E::Scalar, E::Scalar, E::Scalar)
   where
     F: Fn(&E::Scalar, &E::Scalar, &E::Scalar, &E::Scalar) -> E::Scalar + Sync,
   {
     let len = poly_A.len() / 2;
     (0..len)
       .into_par_iter()
+      .with_min_len(chunk_size)
       .map(|i| {
         // eval 0: bound_func is A(low)
         let eval_point_0 = comb_func(&poly_A[i], &poly_B[i], &poly_C[i], &poly_D[i]);
@@ -421,6 +444,7 @@ impl<E: Engine> SumcheckProof<E> {
     poly_C: &mut MultilinearPolynomial<E::Scalar>,
     poly_D: &mut MultilinearPolynomial<E::Scalar>,
     comb_func: F,
+    chunk_size: usize,
     transcript: &mut E::TE,
   ) -> Result<(Self, Vec<E::Scalar>, Vec<E::Scalar>), NovaError>
   where
@@ -434,8 +458,8 @@ impl<E: Engine> SumcheckProof<E> {
       let poly = {
         // Make an iterator returning the contributions to the evaluations
         let (eval_point_0, eval_point_2, eval_point_3) =
-          Self::compute_eval_points_cubic_with_additive_term(
-            poly_A, poly_B, poly_C, poly_D, &comb_func,
+          Self::compute_eval_points_cubic_with_additive_term_chunked(
+            poly_A, poly_B, poly_C, poly_D, &comb_func, chunk_size,
           );
 
         let evals = vec![
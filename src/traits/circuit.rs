@@ -1,5 +1,8 @@
 //! This module defines traits that a step function must implement
-use crate::frontend::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use crate::{
+  errors::StateError,
+  frontend::{num::AllocatedNum, ConstraintSystem, SynthesisError},
+};
 use core::marker::PhantomData;
 use ff::PrimeField;
 
@@ -12,12 +15,79 @@ pub trait StepCircuit<F: PrimeField>: Send + Sync + Clone {
   fn arity(&self) -> usize;
 
   /// Synthesize the circuit for a computation step and return variable
-  /// that corresponds to the output of the step `z_{i+1}`
+  /// that corresponds to the output of the step `z_{i+1}`, followed by
+  /// the `num_public_signals()` public signals of the step, if any
   fn synthesize<CS: ConstraintSystem<F>>(
     &self,
     cs: &mut CS,
     z: &[AllocatedNum<F>],
   ) -> Result<Vec<AllocatedNum<F>>, SynthesisError>;
+
+  /// Returns the number of public signals that the step exposes in addition to its outputs, which
+  /// `synthesize` returns after the outputs. Unlike the outputs, they are not fed to the next step,
+  /// but the signals of the last step are bound into the proof alongside the outputs, as returned
+  /// by `RecursiveSNARK::public_signals`.
+  ///
+  /// The signals are public inputs of the primary augmented circuit, which has `2 + k` public
+  /// inputs for `k` signals: its two hashes, followed by the signals. Like the outputs, they are
+  /// also absorbed into the hashes, which binds them across steps. Each signal must be less than
+  /// 2^250, so that it has the same representation in the scalar fields of both curves.
+  fn num_public_signals(&self) -> usize {
+    0
+  }
+
+  /// Checks the preconditions of the circuit on the initial input `z0` of an incremental
+  /// computation, e.g., that a counter starts at zero, and returns the reason they do not hold.
+  /// `RecursiveSNARK::new` calls it before synthesizing the first step.
+  fn validate_initial(&self, _z0: &[F]) -> Result<(), String> {
+    Ok(())
+  }
+}
+
+/// A step circuit that also takes public parameters at each step, e.g., a round constant fetched
+/// from a table. Unlike its state, the parameters of every step are known to the verifier. It is
+/// folded by wrapping it in `nova::StepParamsCircuit`, which allocates the parameters and binds them
+/// into the proof.
+pub trait ParameterizedStepCircuit<F: PrimeField>: Send + Sync + Clone {
+  /// Return the number of inputs or outputs of each step, as in `StepCircuit::arity`
+  fn arity(&self) -> usize;
+
+  /// Return the number of public parameters of each step
+  fn num_step_params(&self) -> usize;
+
+  /// Synthesize the circuit for a computation step as in `StepCircuit::synthesize`, given the
+  /// `num_step_params()` allocated public parameters `params` of the step
+  fn synthesize<CS: ConstraintSystem<F>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<F>],
+    params: &[AllocatedNum<F>],
+  ) -> Result<Vec<AllocatedNum<F>>, SynthesisError>;
+
+  /// Returns the number of public signals of the step, as in `StepCircuit::num_public_signals`
+  fn num_public_signals(&self) -> usize {
+    0
+  }
+
+  /// Checks the preconditions of the circuit on the initial input `z0`, as in
+  /// `StepCircuit::validate_initial`
+  fn validate_initial(&self, _z0: &[F]) -> Result<(), String> {
+    Ok(())
+  }
+}
+
+/// A helper trait for an application state that is carried through the incremental computation
+/// as a vector of field elements, i.e., the `z_i` of the step circuit
+pub trait IvcState<F: PrimeField>: Sized {
+  /// Encodes the state as a vector of `arity()` field elements
+  fn to_field_vec(&self) -> Vec<F>;
+
+  /// Decodes the state from a vector of field elements produced by `to_field_vec`
+  fn from_field_vec(v: &[F]) -> Result<Self, StateError>;
+
+  /// Returns the number of field elements encoding the state,
+  /// which must match the arity of the step circuit
+  fn arity() -> usize;
 }
 
 /// A trivial step circuit that simply returns the input
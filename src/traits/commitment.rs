@@ -1,6 +1,7 @@
 //! This module defines a collection of traits that define the behavior of a commitment engine
 //! We require the commitment engine to provide a commitment to vectors with a single group element
 use crate::{
+  errors::NovaError,
   provider::ptau::PtauFileError,
   traits::{AbsorbInRO2Trait, AbsorbInROTrait, Engine, TranscriptReprTrait},
 };
@@ -8,6 +9,7 @@ use core::{
   fmt::Debug,
   ops::{Add, Mul, MulAssign},
 };
+use ff::Field;
 use num_integer::Integer;
 use num_traits::ToPrimitive;
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
@@ -70,6 +72,27 @@ pub trait CommitmentEngineTrait<E: Engine>: Clone + Send + Sync {
   /// Samples a new commitment key of a specified size
   fn setup(label: &'static [u8], n: usize) -> Self::CommitmentKey;
 
+  /// Derives a commitment key of a specified size deterministically from a public seed,
+  /// so that anyone holding the seed can reproduce it. By default, this is unsupported.
+  fn setup_from_seed(
+    _label: &'static [u8],
+    _seed: &[u8; 32],
+    _n: usize,
+  ) -> Result<Self::CommitmentKey, NovaError> {
+    Err(NovaError::UnsupportedCommitmentKeySeed)
+  }
+
+  /// Extends a commitment key sampled by `setup` with `label` to the key that `setup` samples for
+  /// `n` generators, keeping its existing generators rather than sampling them again. A key that
+  /// already has enough generators is left unchanged. By default, this is unsupported.
+  fn extend(
+    _ck: &mut Self::CommitmentKey,
+    _label: &'static [u8],
+    _n: usize,
+  ) -> Result<(), NovaError> {
+    Err(NovaError::UnsupportedCommitmentKeyExtension)
+  }
+
   /// Extracts the blinding generator
   fn derand_key(ck: &Self::CommitmentKey) -> Self::DerandKey;
 
@@ -89,6 +112,39 @@ pub trait CommitmentEngineTrait<E: Engine>: Clone + Send + Sync {
       .collect()
   }
 
+  /// Commits to the provided vector using the provided generators and random blind as in `commit`,
+  /// but computes the multi-scalar multiplication in windows of `window` generators, so that the
+  /// additional memory it needs is bounded by the window rather than by the length of the vector.
+  /// By default, this falls back to `commit`.
+  fn commit_streaming(
+    ck: &Self::CommitmentKey,
+    v: &[E::Scalar],
+    r: &E::Scalar,
+    window: usize,
+  ) -> Self::Commitment {
+    assert!(window > 0);
+    Self::commit(ck, v, r)
+  }
+
+  /// Commits to the vector whose nonzero entries are `v[i] = s` for each `(i, s)` in `entries`
+  /// using the provided generators and random blind, so that the result equals that of `commit`
+  /// on the dense vector, but the multi-scalar multiplication only includes the nonzero terms.
+  /// The indices must be strictly increasing and within the commitment key, otherwise this panics.
+  /// By default, this falls back to `commit` on the dense vector.
+  fn commit_sparse(
+    ck: &Self::CommitmentKey,
+    entries: &[(usize, E::Scalar)],
+    r: &E::Scalar,
+  ) -> Self::Commitment {
+    check_sparse_entries::<E>(entries, ck.length());
+
+    let mut v = vec![E::Scalar::ZERO; entries.last().map_or(0, |(i, _)| i + 1)];
+    for (i, s) in entries {
+      v[*i] = *s;
+    }
+    Self::commit(ck, &v, r)
+  }
+
   /// Commits to the provided vector of "small" scalars (at most 64 bits) using the provided generators and random blind
   fn commit_small<T: Integer + Into<u64> + Copy + Sync + ToPrimitive>(
     ck: &Self::CommitmentKey,
@@ -116,3 +172,16 @@ pub trait CommitmentEngineTrait<E: Engine>: Clone + Send + Sync {
     r: &E::Scalar,
   ) -> Self::Commitment;
 }
+
+/// Checks that the indices of the entries of a sparse vector are strictly increasing, so that no
+/// entry is counted twice, and that they are all below `n`
+pub(crate) fn check_sparse_entries<E: Engine>(entries: &[(usize, E::Scalar)], n: usize) {
+  assert!(
+    entries.windows(2).all(|w| w[0].0 < w[1].0),
+    "sparse entries must have strictly increasing indices"
+  );
+  assert!(
+    entries.last().map_or(true, |(i, _)| *i < n),
+    "sparse entry index out of range of the commitment key"
+  );
+}
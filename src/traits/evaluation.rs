@@ -43,4 +43,9 @@ pub trait EvaluationEngineTrait<E: Engine>: Clone + Send + Sync {
     eval: &E::Scalar,
     arg: &Self::EvaluationArgument,
   ) -> Result<(), NovaError>;
+
+  /// A method to precompute tables for the multi-scalar multiplications with fixed bases in
+  /// `verify`, which speed up repeated verifications with the same verifier key at the cost of
+  /// memory. It does not affect the result of `verify`, and is a no-op unless overridden.
+  fn precompute_msm_tables(_vk: &mut Self::VerifierKey) {}
 }
@@ -64,6 +64,10 @@ pub trait Engine: Clone + Copy + Debug + Send + Sync + Sized + Eq + PartialEq {
 }
 
 /// A helper trait to absorb different objects in RO
+///
+/// Implementations absorb a fixed number of base field elements for a given shape, in the same
+/// order as the corresponding allocated value is absorbed in `E::ROCircuit` by the augmented
+/// circuits, so that the hashes computed outside and inside the circuits agree.
 pub trait AbsorbInROTrait<E: Engine> {
   /// Absorbs the value in the provided RO
   fn absorb_in_ro(&self, ro: &mut E::RO);
@@ -76,6 +80,18 @@ pub trait AbsorbInRO2Trait<E: Engine> {
 }
 
 /// A helper trait that defines the behavior of a hash function that we use as an RO
+///
+/// The library only relies on this trait and `ROCircuitTrait`, so an engine can use any
+/// algebraic sponge, e.g., Rescue or Griffin instead of the provided Poseidon, by implementing
+/// both traits and setting them as `Engine::RO` and `Engine::ROCircuit`. An implementation must
+/// satisfy the following, which Nova's soundness relies on:
+///
+/// * for the same constants and the same absorbed elements, `squeeze` returns the value of the
+///   bits returned by `CircuitRO::squeeze`, in little-endian order;
+/// * the value returned by `squeeze` fits in `num_bits` bits.
+///
+/// The constants are serialized into the digest of the public parameters, which can be created
+/// with non-default constants by `PublicParams::setup_with_ro_constants`.
 pub trait ROTrait<Base: PrimeField> {
   /// The circuit alter ego of this trait impl - this constrains it to use the same constants
   type CircuitRO: ROCircuitTrait<Base, Constants = Self::Constants>;
@@ -89,11 +105,21 @@ pub trait ROTrait<Base: PrimeField> {
   /// Adds a scalar to the internal state
   fn absorb(&mut self, e: Base);
 
+  /// Adds a slice of scalars to the internal state, which is equivalent to absorbing them one
+  /// at a time. Implementations may override it to prepare the inputs in bulk.
+  fn absorb_batch(&mut self, es: &[Base]) {
+    for e in es {
+      self.absorb(*e);
+    }
+  }
+
   /// Returns a challenge of `num_bits` by hashing the internal state
   fn squeeze(&mut self, num_bits: usize) -> Base;
 }
 
 /// A helper trait that defines the behavior of a hash function that we use as an RO in the circuit model
+///
+/// This is the in-circuit counterpart of `ROTrait`, with which it must agree as described there.
 pub trait ROCircuitTrait<Base: PrimeField> {
   /// the vanilla alter ego of this trait - this constrains it to use the same constants
   type NativeRO: ROTrait<Base, Constants = Self::Constants>;
@@ -52,6 +52,10 @@ pub trait RelaxedR1CSSNARKTrait<E: Engine>:
 
   /// Verifies a SNARK for a relaxed R1CS
   fn verify(&self, vk: &Self::VerifierKey, U: &RelaxedR1CSInstance<E>) -> Result<(), NovaError>;
+
+  /// Precomputes tables for the multi-scalar multiplications with fixed bases in `verify`,
+  /// which does not affect its result. This is a no-op unless overridden.
+  fn precompute_msm_tables(_vk: &mut Self::VerifierKey) {}
 }
 
 /// A helper trait that defines the behavior of a verifier key of `zkSNARK`